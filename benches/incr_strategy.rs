@@ -0,0 +1,72 @@
+//! Compares the [`IncrStrategy`]s of [`AtomicUInt::saturating_incr_with`], by how long some
+//! threads take to all increment the same counter, contending with each other.
+//!
+//! Run with `cargo bench --bench incr_strategy`.  Only meaningful with more than one CPU, because
+//! otherwise there's no real parallel contention.
+
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::as_conversions,
+    clippy::cast_precision_loss,
+    clippy::float_arithmetic,
+    clippy::print_stdout,
+    clippy::std_instead_of_core,
+    unused_crate_dependencies // Ignore the lib crate's deps that are supplied here also.
+)]
+
+use core::{hint::black_box, iter, sync::atomic::AtomicU64, time::Duration};
+use signals_receipts::{AtomicUInt as _, IncrStrategy};
+use std::{thread, time::Instant};
+
+
+const INCREMENTS_PER_THREAD: u32 = 1_000_000;
+const ROUNDS: usize = 5;
+
+
+fn main() {
+    let strategies = [
+        ("Spin", IncrStrategy::Spin),
+        ("SpinThenAdd { spin_limit: 0 }", IncrStrategy::SpinThenAdd { spin_limit: 0 }),
+        ("SpinThenAdd { spin_limit: 4 }", IncrStrategy::SpinThenAdd { spin_limit: 4 }),
+        ("SpinThenAdd { spin_limit: 64 }", IncrStrategy::SpinThenAdd { spin_limit: 64 }),
+    ];
+    let cpus = thread::available_parallelism().map_or(1, usize::from);
+    println!("available parallelism: {cpus}");
+
+    for threads in [1, 2, 4, 8, 16] {
+        for (name, strategy) in strategies {
+            // The best of the rounds, to lessen the noise from other activity on the machine.
+            let best = iter::repeat_with(|| run(threads, strategy))
+                .take(ROUNDS)
+                .min()
+                .unwrap_or_default();
+            let millis = best.as_secs_f64() * 1e3;
+            let per_incr = best.as_nanos() as f64
+                / f64::from(INCREMENTS_PER_THREAD)
+                / threads as f64;
+            println!("{threads:>2} threads, {name:<30} {millis:>9.3} ms ({per_incr:.2} ns/incr)");
+        }
+    }
+}
+
+
+fn run(threads: usize, strategy: IncrStrategy) -> Duration {
+    let counter = AtomicU64::new(0);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0 .. threads {
+            scope.spawn(|| {
+                for _ in 0 .. INCREMENTS_PER_THREAD {
+                    black_box(counter.saturating_incr_with(strategy));
+                }
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+    assert_eq!(
+        counter.into_inner(),
+        u64::from(INCREMENTS_PER_THREAD) * threads as u64,
+        "no increments are lost below the maximum"
+    );
+    elapsed
+}
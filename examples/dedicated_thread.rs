@@ -33,7 +33,7 @@ fn main() {
     mask_all_signals_of_current_thread();
 
     // This also disables `SA_RESTART`, so our "dont-interrupt" thread is properly tested.
-    SignalsReceipts::install_all_handlers_with(true, false);
+    SignalsReceipts::install_all_handlers_with(true, false, false);
 
     // Not masking signals for this thread allows it to have signal handlers called on it.  This
     // thread is the only one that can have signal handlers called on it.
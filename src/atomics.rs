@@ -1,6 +1,6 @@
 use core::{hint,
-           ops::Add,
-           sync::atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicU8,
+           ops::{Add, Rem, Sub},
+           sync::atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize,
                           Ordering::{self, Relaxed}}};
 
 
@@ -13,7 +13,17 @@ use core::{hint,
 /// that type must really have lock-free atomic operations.
 pub trait AtomicUInt: Default + 'static {
     /// The corresponding primitive unsigned integer type, with the needed operations.
-    type UInt: Add<Output = Self::UInt> + Copy + Eq + From<u8>;
+    ///
+    /// (Since version 0.3.0, `Sub`, `Rem`, and `Ord` are also required, for
+    /// [`IncrStrategy::SpinThenAdd`], for
+    /// [`SignalReceipt::WAKE_EVERY`](crate::SignalReceipt::WAKE_EVERY), and for
+    /// [`Self::saturating_add`] et al.  The primitive unsigned integers all have those.)
+    type UInt: Add<Output = Self::UInt>
+        + Sub<Output = Self::UInt>
+        + Rem<Output = Self::UInt>
+        + Copy
+        + Ord
+        + From<u8>;
     /// The largest value that can be represented by [`Self::UInt`].
     const MAX: Self::UInt;
 
@@ -35,27 +45,189 @@ pub trait AtomicUInt: Default + 'static {
         failure: Ordering,
     ) -> Result<Self::UInt, Self::UInt>;
 
+    /// Like [`Atomic*::fetch_add` et al](`AtomicU64::fetch_add`), wrapping around on overflow.
+    ///
+    /// The default implementation uses a loop of [`Self::compare_exchange`].  Implementations
+    /// should override this if they have a native operation.
+    #[inline]
+    fn fetch_add(&self, val: Self::UInt, order: Ordering) -> Self::UInt {
+        let mut cur = self.load(Relaxed);
+        loop {
+            #[allow(clippy::arithmetic_side_effects)]
+            let room = Self::MAX - cur; // (Can't underflow.)
+            let new = if val > room {
+                #[allow(clippy::arithmetic_side_effects)] // (Can't underflow.)
+                let wrapped = val - room - 1.into();
+                wrapped
+            } else {
+                #[allow(clippy::arithmetic_side_effects)] // (Can't overflow.)
+                let sum = cur + val;
+                sum
+            };
+            match self.compare_exchange(cur, new, order, Relaxed) {
+                Ok(prev) => break prev,
+                Err(latest) => {
+                    cur = latest;
+                    hint::spin_loop();
+                },
+            }
+        }
+    }
+
     /// Like [`Atomic*::fetch_add` et al](`AtomicU64::fetch_add`) of `1, Relaxed`, but saturates
     /// at the numeric bounds instead of overflowing, and returns the new value.
+    ///
+    /// Same as [`Self::saturating_incr_with`] with [`IncrStrategy::Spin`].
+    #[inline]
+    fn saturating_incr(&self) -> Self::UInt { self.saturating_incr_with(IncrStrategy::Spin) }
+
+    /// Like [`Self::saturating_incr`], but with the given strategy for what to do when other
+    /// threads contend to increment at the same time.
+    #[inline]
+    fn saturating_incr_with(&self, strategy: IncrStrategy) -> Self::UInt {
+        self.saturating_incr_reporting_with(strategy).0
+    }
+
+    /// Like [`Self::saturating_incr`], and also return whether the increment was lost because
+    /// the value was already saturated at [`Self::MAX`].
+    #[inline]
+    fn saturating_incr_reporting(&self) -> (Self::UInt, bool) {
+        self.saturating_incr_reporting_with(IncrStrategy::Spin)
+    }
+
+    /// Like [`Self::saturating_incr_with`], and also return whether the increment was lost
+    /// because the value was already saturated at [`Self::MAX`].
     #[inline]
-    fn saturating_incr(&self) -> Self::UInt {
+    fn saturating_incr_reporting_with(&self, strategy: IncrStrategy) -> (Self::UInt, bool) {
         let mut cur = self.load(Relaxed);
+        let mut failures: u32 = 0;
         loop {
             if cur == Self::MAX {
-                break cur;
-            } else {
+                break (cur, true);
+            }
+            if let IncrStrategy::SpinThenAdd { spin_limit } = strategy {
                 #[allow(clippy::arithmetic_side_effects)]
-                let incr = cur + 1.into(); // (Can't overflow.)
-                match self.compare_exchange(cur, incr, Relaxed, Relaxed) {
-                    Ok(_) => break incr,
-                    Err(latest) => {
-                        cur = latest;
-                        hint::spin_loop();
-                    },
+                let room = Self::MAX - cur; // (Can't underflow.)
+                if failures >= spin_limit && room > IncrStrategy::HEADROOM.into() {
+                    let prev = self.fetch_add(1.into(), Relaxed);
+                    if prev == Self::MAX {
+                        // More than the headroom contended, and so this wrapped around.  Restore
+                        // the saturation.  (Any others' increments since then, from zero, are
+                        // overwritten, which is the same as their being lost to the saturation.)
+                        let _wrapped = self.swap(Self::MAX, Relaxed);
+                        break (Self::MAX, true);
+                    }
+                    #[allow(clippy::arithmetic_side_effects)]
+                    break (prev + 1.into(), false); // (Can't overflow, because of the above.)
                 }
             }
+            #[allow(clippy::arithmetic_side_effects)]
+            let incr = cur + 1.into(); // (Can't overflow.)
+            match self.compare_exchange(cur, incr, Relaxed, Relaxed) {
+                Ok(_) => break (incr, false),
+                Err(latest) => {
+                    cur = latest;
+                    failures = failures.saturating_add(1);
+                    hint::spin_loop();
+                },
+            }
         }
     }
+
+    /// Like [`Self::saturating_incr`], but adds `n`, saturating at [`Self::MAX`], and returns
+    /// the new value.  E.g. for a delegate to return the remainder of a partially-processed
+    /// count to the counter.
+    #[inline]
+    fn saturating_add(&self, n: Self::UInt) -> Self::UInt {
+        let mut cur = self.load(Relaxed);
+        loop {
+            #[allow(clippy::arithmetic_side_effects)]
+            let room = Self::MAX - cur; // (Can't underflow.)
+            #[allow(clippy::arithmetic_side_effects)] // (Can't overflow.)
+            let new = if n > room { Self::MAX } else { cur + n };
+            match self.compare_exchange(cur, new, Relaxed, Relaxed) {
+                Ok(_) => break new,
+                Err(latest) => {
+                    cur = latest;
+                    hint::spin_loop();
+                },
+            }
+        }
+    }
+
+    /// Like [`Self::saturating_add`], but subtracts `n`, saturating at zero, and returns the new
+    /// value.  E.g. for partially draining the counter.
+    #[inline]
+    fn saturating_sub(&self, n: Self::UInt) -> Self::UInt {
+        let mut cur = self.load(Relaxed);
+        loop {
+            #[allow(clippy::arithmetic_side_effects)] // (Can't underflow.)
+            let new = if n > cur { 0.into() } else { cur - n };
+            match self.compare_exchange(cur, new, Relaxed, Relaxed) {
+                Ok(_) => break new,
+                Err(latest) => {
+                    cur = latest;
+                    hint::spin_loop();
+                },
+            }
+        }
+    }
+
+    /// Like [`Self::saturating_incr_with`], and return whether the new value is a multiple of
+    /// `n` (where zero is treated as one).  E.g. for doing something only every `n`th increment,
+    /// like rate-limiting the wakeups done by a signal handler.
+    ///
+    /// Once saturated, the value stays the same, and so this keeps returning the same answer.
+    #[inline]
+    fn saturating_incr_is_multiple(&self, strategy: IncrStrategy, n: u8) -> bool {
+        Self::is_multiple(self.saturating_incr_with(strategy), n)
+    }
+
+    /// Whether `value` is a multiple of `n` (where zero is treated as one).
+    #[must_use]
+    #[inline]
+    fn is_multiple(value: Self::UInt, n: u8) -> bool {
+        #[allow(clippy::arithmetic_side_effects)]
+        let rem = value % n.max(1).into(); // (Can't divide by zero.)
+        rem == 0.into()
+    }
+}
+
+
+/// What [`AtomicUInt::saturating_incr_with`] does when its compare-and-exchange fails due to
+/// other threads contending to increment at the same time.
+///
+/// Because incrementing is done in the async-signal handler, yielding to the scheduler is not an
+/// option, because that's not async-signal-safe.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[non_exhaustive]
+pub enum IncrStrategy {
+    /// Retry the compare-and-exchange until it succeeds.  This is exact, but, under extreme
+    /// contention, a thread might spin for a while.
+    #[default]
+    Spin,
+    /// Retry the compare-and-exchange up to `spin_limit` times, and then fall back to an
+    /// unconditional `fetch_add`, which always completes in one operation.
+    ///
+    /// The fallback is only done while the value is more than [`Self::HEADROOM`] below the
+    /// maximum, which ensures that the in-flight increments of up to that many
+    /// concurrently-contending threads (i.e. handler executions for the same counter) can't
+    /// exceed the maximum.  Nearer to the maximum, this spins like [`Self::Spin`], and so
+    /// saturating remains exact within that limit of concurrency.
+    ///
+    /// Beyond that limit, a `fetch_add` could wrap the value around.  That is detected, by the
+    /// thread whose increment wrapped it, which then restores the value to the maximum, and so
+    /// the value still saturates, but the counts taken while it was wrapped are too low.
+    SpinThenAdd {
+        /// How many failed compare-and-exchanges to tolerate before falling back.
+        spin_limit: u32,
+    },
+}
+
+impl IncrStrategy {
+    /// How far below the maximum the value must be for [`Self::SpinThenAdd`] to fall back to
+    /// `fetch_add`, i.e. how many concurrently-contending threads it's exact for.
+    pub const HEADROOM: u8 = u8::MAX;
 }
 
 
@@ -75,6 +247,11 @@ macro_rules! uints_impls {
                     <$t>::swap(self, val, order)
                 }
 
+                #[inline]
+                fn fetch_add(&self, val: Self::UInt, order: Ordering) -> Self::UInt {
+                    <$t>::fetch_add(self, val, order)
+                }
+
                 #[inline]
                 fn compare_exchange(
                     &self,
@@ -92,7 +269,9 @@ macro_rules! uints_impls {
         };
     }
 
-uints_impls! { (AtomicU8, u8); (AtomicU16, u16); (AtomicU32, u32); (AtomicU64, u64); }
+uints_impls! {
+    (AtomicU8, u8); (AtomicU16, u16); (AtomicU32, u32); (AtomicU64, u64); (AtomicUsize, usize);
+}
 
 
 #[cfg(test)]
@@ -106,4 +285,127 @@ mod tests {
         let a2 = AtomicU8::new(u8::MAX);
         assert_eq!(a2.saturating_incr(), u8::MAX);
     }
+
+    #[test]
+    fn reporting() {
+        let a = AtomicU8::new(u8::MAX - 1);
+        assert_eq!(a.saturating_incr_reporting(), (u8::MAX, false));
+        assert_eq!(a.saturating_incr_reporting(), (u8::MAX, true));
+        let strategy = IncrStrategy::SpinThenAdd { spin_limit: 0 };
+        assert_eq!(a.saturating_incr_reporting_with(strategy), (u8::MAX, true));
+    }
+
+    #[test]
+    fn usize() {
+        let a1 = AtomicUsize::new(1);
+        assert_eq!(a1.saturating_incr(), 2);
+        let a2 = AtomicUsize::new(usize::MAX - 1);
+        assert_eq!(a2.saturating_incr(), usize::MAX);
+        assert_eq!(a2.saturating_incr(), usize::MAX);
+        assert_eq!(<AtomicUsize as AtomicUInt>::MAX, usize::MAX);
+    }
+
+    #[test]
+    fn spin_then_add() {
+        let strategy = IncrStrategy::SpinThenAdd { spin_limit: 0 };
+        let a1 = AtomicU64::new(1);
+        assert_eq!(a1.saturating_incr_with(strategy), 2);
+        let a2 = AtomicU8::new(u8::MAX - 1);
+        assert_eq!(a2.saturating_incr_with(strategy), u8::MAX);
+        assert_eq!(a2.saturating_incr_with(strategy), u8::MAX);
+        let a3 = AtomicU16::new(u16::MAX - 300);
+        assert_eq!(a3.saturating_incr_with(strategy), u16::MAX - 299);
+    }
+
+    #[test]
+    fn spin_then_add_wrapped() {
+        /// Always loads zero, like a thread that observed that before more than the headroom of
+        /// other threads incremented it to the maximum.
+        #[derive(Default)]
+        struct Stale(AtomicU16);
+
+        impl AtomicUInt for Stale {
+            type UInt = u16;
+
+            const MAX: Self::UInt = u16::MAX;
+
+            fn load(&self, _order: Ordering) -> Self::UInt { 0 }
+
+            fn swap(&self, val: Self::UInt, order: Ordering) -> Self::UInt {
+                self.0.swap(val, order)
+            }
+
+            fn fetch_add(&self, val: Self::UInt, order: Ordering) -> Self::UInt {
+                self.0.fetch_add(val, order)
+            }
+
+            fn compare_exchange(
+                &self,
+                current: Self::UInt,
+                new: Self::UInt,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<Self::UInt, Self::UInt> {
+                self.0.compare_exchange(current, new, success, failure)
+            }
+        }
+
+        let s = Stale(AtomicU16::new(u16::MAX));
+        let strategy = IncrStrategy::SpinThenAdd { spin_limit: 0 };
+        assert_eq!(s.saturating_incr_reporting_with(strategy), (u16::MAX, true));
+        assert_eq!(s.0.load(Relaxed), u16::MAX);
+    }
+
+    #[test]
+    fn is_multiple() {
+        let a = AtomicU32::new(0);
+        let wakes =
+            (0 .. 25).filter(|_| a.saturating_incr_is_multiple(IncrStrategy::Spin, 10)).count();
+        assert_eq!(wakes, 2);
+        assert_eq!(a.load(Relaxed), 25);
+        assert!(a.saturating_incr_is_multiple(IncrStrategy::Spin, 0));
+    }
+
+    #[test]
+    fn saturating_add_sub() {
+        let a = AtomicU8::new(250);
+        assert_eq!(a.saturating_add(3), 253);
+        assert_eq!(a.saturating_add(10), u8::MAX);
+        assert_eq!(a.saturating_sub(200), 55);
+        assert_eq!(a.saturating_sub(100), 0);
+        assert_eq!(a.load(Relaxed), 0);
+    }
+
+    #[test]
+    fn default_fetch_add() {
+        #[derive(Default)]
+        struct Emulated(AtomicU8);
+
+        impl AtomicUInt for Emulated {
+            type UInt = u8;
+
+            const MAX: Self::UInt = u8::MAX;
+
+            fn load(&self, order: Ordering) -> Self::UInt { self.0.load(order) }
+
+            fn swap(&self, val: Self::UInt, order: Ordering) -> Self::UInt {
+                self.0.swap(val, order)
+            }
+
+            fn compare_exchange(
+                &self,
+                current: Self::UInt,
+                new: Self::UInt,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<Self::UInt, Self::UInt> {
+                self.0.compare_exchange(current, new, success, failure)
+            }
+        }
+
+        let e = Emulated(AtomicU8::new(250));
+        assert_eq!(e.fetch_add(3, Relaxed), 250);
+        assert_eq!(e.fetch_add(5, Relaxed), 253);
+        assert_eq!(e.load(Relaxed), 2);
+    }
 }
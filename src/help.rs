@@ -1,4 +1,10 @@
+#[cfg(not(target_os = "macos"))]
+use core::fmt;
 use errno::errno;
+#[cfg(not(target_os = "macos"))]
+use errno::{set_errno, Errno};
+#[cfg(not(target_os = "macos"))]
+use sem_safe::SemaphoreRef;
 
 
 /// Only intended to be called after `SemaphoreRef::post()` to check its result.
@@ -16,3 +22,96 @@ pub(crate) fn assert_errno_is_overflow(impossible: fn() -> !) {
         impossible(); // Impossible - `sem_safe` ensures the semaphores are valid.
     }
 }
+
+
+/// Like [`SemaphoreRef::wait`], but via `sem_timedwait()`, which stops blocking once the
+/// absolute `deadline`, by `CLOCK_REALTIME`, has passed.  (`sem_safe` doesn't provide this yet.)
+///
+/// # Errors
+/// If `sem_timedwait()` does.  `errno` is set to indicate the error, e.g. `ETIMEDOUT` or `EINTR`.
+/// Or `EINVAL`, if the underlying `sem_t` couldn't be gotten, which won't happen.
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn sem_timedwait(
+    sem: SemaphoreRef<'_>,
+    deadline: &libc::timespec,
+) -> Result<(), ()> {
+    #![allow(unsafe_code)]
+
+    let Some(raw) = raw_sem(sem) else {
+        set_errno(Errno(libc::EINVAL));
+        return Err(());
+    };
+    // SAFETY: `raw` is the same `sem_t *` that `sem`'s own operations use, which `sem_safe`
+    // guarantees is initialized and remains valid for the lifetime of `sem`, which outlives this
+    // call.  `sem_timedwait()` is thread-safe like `sem_wait()` is, and `deadline` is valid.
+    let r = unsafe { libc::sem_timedwait(raw, deadline) };
+    if r == 0 { Ok(()) } else { Err(()) }
+}
+
+/// Like [`sem_timedwait`], but via `sem_clockwait()`, which measures the absolute `deadline` by
+/// the given clock, which must be `CLOCK_MONOTONIC` or `CLOCK_REALTIME`.  (Neither `sem_safe`
+/// nor `libc` provide this yet.)  Only glibc has this, since version 2.30.
+///
+/// # Errors
+/// Same as `sem_timedwait`.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub(crate) fn sem_clockwait(
+    sem: SemaphoreRef<'_>,
+    clock_id: libc::clockid_t,
+    deadline: &libc::timespec,
+) -> Result<(), ()> {
+    #![allow(unsafe_code)]
+
+    extern "C" {
+        fn sem_clockwait(
+            sem: *mut libc::sem_t,
+            clock_id: libc::clockid_t,
+            abstime: *const libc::timespec,
+        ) -> core::ffi::c_int;
+    }
+
+    let Some(raw) = raw_sem(sem) else {
+        set_errno(Errno(libc::EINVAL));
+        return Err(());
+    };
+    // SAFETY: Same as for `sem_timedwait`, and the declaration matches glibc's.
+    let r = unsafe { sem_clockwait(raw, clock_id, deadline) };
+    if r == 0 { Ok(()) } else { Err(()) }
+}
+
+/// The `sem_t *` that the given `SemaphoreRef` operates on.
+///
+/// `sem_safe` (as of 0.2) doesn't expose this other than via its `Debug` impl, which shows the
+/// pointer, and so this recovers it from that.  The formatting of a pointer exposes its
+/// provenance, and so converting the address back to a pointer is sound.  Returns `None` if the
+/// representation isn't as expected, e.g. if a future version of `sem_safe` changes it, instead
+/// of guessing.
+#[cfg(not(target_os = "macos"))]
+fn raw_sem(sem: SemaphoreRef<'_>) -> Option<*mut libc::sem_t> {
+    use core::fmt::Write as _;
+
+    /// Enough for `SemaphoreRef(0x` and 16 hex digits and `)`.
+    struct Buf {
+        bytes: [u8; 40],
+        len:   usize,
+    }
+
+    impl fmt::Write for Buf {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len.checked_add(s.len()).ok_or(fmt::Error)?;
+            self.bytes.get_mut(self.len .. end).ok_or(fmt::Error)?.copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    let mut buf = Buf { bytes: [0; 40], len: 0 };
+    #[allow(clippy::use_debug)] // It's the only access to the pointer.
+    write!(buf, "{sem:?}").ok()?;
+    let text = core::str::from_utf8(buf.bytes.get(.. buf.len)?).ok()?;
+    let hex = text.strip_prefix("SemaphoreRef(0x")?.strip_suffix(')')?;
+    let addr = usize::from_str_radix(hex, 16).ok()?;
+    #[allow(clippy::as_conversions)] // Same as `ptr::with_exposed_provenance_mut`, for our MSRV.
+    let raw = addr as *mut libc::sem_t;
+    (!raw.is_null()).then_some(raw)
+}
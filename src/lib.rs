@@ -1,6 +1,7 @@
 #![cfg_attr(not(all(doctest, not(feature = "channel_notify_facility"))),
             doc = include_str!("../README.md"))]
-// By default, this crate is no-std, unless the "channel_notify_facility" feature is enabled.
+// By default, this crate is no-std, unless the "channel_notify_facility", "registry", or "spawn"
+// feature is enabled.
 // Require explicit conditional `use` of non-`core` items.
 #![no_std]
 //
@@ -11,34 +12,91 @@ core::compile_error!("Only supported on POSIX.");
 pub use atomics::*;
 mod atomics;
 
+pub use sig_info::*;
+mod sig_info;
+
+// Real-time signals are only resolved on these, as `rt` explains.
+cfg_if::cfg_if! { if #[cfg(any(target_os = "linux", target_os = "android"))] {
+    pub use sig_value::*;
+    mod sig_value;
+} }
+
 cfg_if::cfg_if! { if #[cfg(feature = "premade")] {
     pub use premade::*;
     mod premade;
 } }
 
+cfg_if::cfg_if! { if #[cfg(feature = "registry")] {
+    pub use registry::*;
+    mod registry;
+} }
+
 /// Helpers that are sometimes useful when using this crate.
 pub mod util;
 
 mod help;
 
 
-use core::{ffi::c_int,
+use core::{ffi::{c_int, c_void},
+           fmt::{self, Display, Formatter},
            ops::ControlFlow,
            pin::Pin,
-           sync::atomic::{AtomicBool, Ordering::Relaxed}};
-use errno::{errno, set_errno};
+           sync::atomic::{AtomicBool, AtomicU64,
+                          Ordering::{Acquire, Relaxed, Release}},
+           time::Duration};
+use errno::{errno, set_errno, Errno};
 use help::assert_errno_is_overflow;
 // These are re-exported because they're exposed in our public API.
 #[doc(no_inline)]
 pub use sem_safe::{non_named::Semaphore as SemaphoreMethods, plaster::non_named::Semaphore,
                    SemaphoreRef};
+#[cfg(test)]
+use serde_json as _; // Only used by the integration tests.
 use util::{abort, mask_all_signals_of_current_thread, unmask_all_signals_of_current_thread,
-           SigAction};
+           SigAction, SigInfo, WaitClock};
 
 
 /// The type of a signal number as defined by C (C17 7.14).
 pub type SignalNumber = c_int;
 
+/// The least of the values that [`rt`] designates real-time signals by.  These are far from any
+/// real signal number, which are small and positive.
+const RT_BASE: SignalNumber = SignalNumber::MIN;
+
+/// Designate the real-time signal `SIGRTMIN + n`, for use as the `SIGNUM` of this crate's items
+/// (e.g. `SignalReceipt<{rt(0)}>` and `install_handler::<{rt(0)}, _>`), and so with the
+/// [`premade`](crate::premade!) macro.
+///
+/// A real-time signal's number can't be the `const SIGNUM` directly, because `SIGRTMIN` is only
+/// known at run-time (the C library reserves some of them for itself, which varies), but a const
+/// generic must be known at compile-time.  So, this returns a placeholder value, which is never a
+/// real signal number, which this crate resolves, via [`resolve_signum`], to the real number
+/// wherever it's given to the OS or to a delegate.
+///
+/// Only Linux and Android have `SIGRTMIN()`.  On other OSs, the placeholder isn't resolved, and
+/// so installing a handler for it fails as an invalid signal number.
+#[must_use]
+#[inline]
+pub const fn rt(n: u8) -> SignalNumber {
+    #[allow(clippy::as_conversions)] // Lossless, but `From` isn't `const`.
+    let n = n as SignalNumber;
+    RT_BASE.saturating_add(n)
+}
+
+/// The real signal number that `signum` designates: `SIGRTMIN() + n` if `signum` was made by
+/// [`rt`], else `signum` itself.
+///
+/// This is async-signal-safe.
+#[must_use]
+#[inline]
+pub fn resolve_signum(signum: SignalNumber) -> SignalNumber {
+    match signum.checked_sub(RT_BASE) {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Some(n @ 0 ..= 0xFF) => libc::SIGRTMIN().saturating_add(n),
+        _ => signum,
+    }
+}
+
 /// The ability to handle receipt of a particular signal.
 ///
 /// All implementations of these methods must be async-signal-safe, because they're called from
@@ -47,6 +105,34 @@ pub trait SignalReceipt<const SIGNUM: SignalNumber> {
     /// An unsigned integer type with atomic operations as needed by this crate.
     type AtomicUInt: AtomicUInt;
 
+    /// How [`handler`] increments the counter when other threads contend to increment it at the
+    /// same time.  Defaults to [`IncrStrategy::Spin`].
+    const INCR_STRATEGY: IncrStrategy = IncrStrategy::Spin;
+
+    /// [`handler`] only posts the semaphore upon every this-many deliveries (counted since the
+    /// counter was last taken), to reduce the wakeups of the "consuming" thread under a storm of
+    /// signals.  Every delivery is still counted.  Defaults to `1`, i.e. every delivery wakes.
+    /// Zero is treated as one.
+    ///
+    /// This can increase the latency of processing the receipts by up to `WAKE_EVERY - 1`
+    /// deliveries, because those are only noticed once more are delivered (or once the consuming
+    /// thread is woken for another reason).
+    const WAKE_EVERY: u8 = 1;
+
+    /// Whether [`handler`], when `debug_assertions` are enabled, checks that the signal number
+    /// it's called with is `SIGNUM` and aborts the process if not.  Defaults to `true`.
+    ///
+    /// Set this to `false` to knowingly install the same monomorphization of the handler for
+    /// several signal numbers, in which case all of their deliveries are counted as `SIGNUM`'s.
+    /// (The crate feature `no_handler_debug_check` removes the check for all, including those
+    /// generated by `premade!`, which keep this default.)
+    const VERIFY_SIGNO: bool = true;
+
+    /// Which clock [`handler`] reads the time of each delivery from, for
+    /// [`Self::timestamp_slot()`].  Defaults to [`util::ClockSource::Monotonic`].  Only relevant
+    /// when the crate feature `"timestamps"` is enabled.
+    const CLOCK: util::ClockSource = util::ClockSource::Monotonic;
+
     /// Atomically replace the value referred to by [`Self::counter()`] with zero and return its
     /// previous value.
     #[must_use]
@@ -55,11 +141,66 @@ pub trait SignalReceipt<const SIGNUM: SignalNumber> {
         Self::counter().swap(0.into(), Relaxed)
     }
 
+    /// Get the current value of [`Self::counter()`] without resetting it.  E.g. to report the
+    /// running total, without disturbing the accounting of the consuming thread.
+    #[must_use]
+    #[inline]
+    fn peek_count() -> <Self::AtomicUInt as AtomicUInt>::UInt { Self::counter().load(Relaxed) }
+
+    /// Like [`Self::take_count()`], but only if the value of [`Self::counter()`] is at least
+    /// `threshold`, else `None` is returned and the counter is left intact.  E.g. for debouncing,
+    /// to only process once enough deliveries have accumulated, without a separate accumulator.
+    ///
+    /// The check and the taking are one atomic operation, and so concurrent increments by
+    /// [`handler`] are never lost: they're either included in the taken value or remain counted.
+    #[must_use]
+    #[inline]
+    fn take_count_if_at_least(
+        threshold: <Self::AtomicUInt as AtomicUInt>::UInt,
+    ) -> Option<<Self::AtomicUInt as AtomicUInt>::UInt> {
+        let counter = Self::counter();
+        let mut cur = counter.load(Relaxed);
+        while cur >= threshold {
+            match counter.compare_exchange(cur, 0.into(), Relaxed, Relaxed) {
+                Ok(taken) => return Some(taken),
+                // Another delivery incremented it meanwhile (or it was taken elsewhere), so
+                // check again with the newer value.
+                Err(actual) => cur = actual,
+            }
+        }
+        None
+    }
+
+    /// Like [`Self::take_count()`], but, if [`Self::dirty_bit()`] is used, only when the bit is
+    /// set, in which case it's cleared first.  When the bit isn't set, zero is returned without
+    /// accessing the counter, which is quicker when many signals are checked but few were
+    /// delivered.
+    #[must_use]
+    #[inline]
+    fn take_dirty_count() -> <Self::AtomicUInt as AtomicUInt>::UInt {
+        if let Some((word, bit)) = Self::dirty_bit() {
+            let mask = 1_u64.checked_shl(bit).unwrap_or(0);
+            if word.load(Relaxed) & mask == 0 {
+                return 0.into();
+            }
+            // Synchronizes with the setting in `handler`, so that the increment of the counter
+            // that preceded that is visible to our taking next.  If the clearing happens before
+            // a concurrent delivery's setting, the bit remains set for next time.
+            word.fetch_and(!mask, Acquire);
+        }
+        Self::take_count()
+    }
+
     /// Get the reference to the counter that counts how many times the signal specified by
     /// `SIGNUM` has been delivered.
     ///
     /// The lifetime must be `'static` because a signal handler, that accesses a counter, can live
     /// for the rest of the duration of a program once installed.
+    ///
+    /// The counter doesn't have to be a `static` item.  An implementation may return a reference
+    /// to storage that it manages itself, e.g. to an atomic in a shared-memory segment (for
+    /// counting signals across processes) that was mapped before the handler was installed and
+    /// that remains mapped.
     #[must_use]
     fn counter() -> &'static Self::AtomicUInt;
 
@@ -68,30 +209,237 @@ pub trait SignalReceipt<const SIGNUM: SignalNumber> {
     ///
     /// The "consuming" thread should wait by blocking on calling [`SemaphoreRef::wait`].
     ///
+    /// Implementations for different `SIGNUM`s may return different semaphores, so that each
+    /// signal wakes only its own "consuming" thread, for fully independent per-signal processing.
+    /// (The [`premade`] macro instead shares one semaphore among all its declared signals, which
+    /// is what makes sense with a single consuming thread, and which avoids the cost of a
+    /// semaphore per signal.)
+    ///
     /// The lifetime must be `'static` because a signal handler, that accesses a semaphore, can
     /// live for the rest of the duration of a program once installed.
     #[must_use]
     fn semaphore() -> Option<SemaphoreRef<'static>>;
+
+    /// Called by [`handler`] upon each delivery of the signal, after incrementing the counter and
+    /// before posting the semaphore.  Does nothing, by default.
+    ///
+    /// This can be overridden to do something else upon delivery that must happen immediately,
+    /// e.g. to record it with [`util::CrashLog::record`] so that it's known even if the process
+    /// crashes before the consuming thread would process the receipt.
+    #[inline]
+    fn on_delivery() {}
+
+    /// Get the bit, in a shared "dirty" bitmask word, that [`handler`] sets upon each delivery of
+    /// the signal, after incrementing the counter.  Return `None` to not have a dirty bit, which
+    /// is the default.
+    ///
+    /// The bit is given as its index, which must be less than 64, in the word.  Multiple signals
+    /// can share a word by using different indexes, and more than 64 signals need multiple
+    /// words.  This enables a consumer to quickly check which signals were delivered, via
+    /// [`Self::take_dirty_count()`], and skip the rest, which optimizes the case of many signals
+    /// where only a few are delivered at a time.  The [`premade`] macro uses this, with the index
+    /// of each signal being the order in which it's declared.
+    #[must_use]
+    #[inline]
+    fn dirty_bit() -> Option<(&'static AtomicU64, u32)> { None }
+
+    /// Get the flag that [`handler`] sets upon a delivery of the signal when the counter was
+    /// already nonzero, i.e. when a previous delivery hadn't been consumed yet and so the two
+    /// will be coalesced into one receipt.  Return `None` to not have the flag, which is the
+    /// default.
+    ///
+    /// Note that standard (non-realtime) signals are also coalesced by the OS, before delivery,
+    /// which can't be detected.  This only detects the coalescing done by this crate.
+    #[must_use]
+    #[inline]
+    fn coalescing_flag() -> Option<&'static AtomicBool> { None }
+
+    /// Whether [`Self::coalescing_flag()`] is used and has been set.  It stays set until it's
+    /// reset by [`reset_counter`].
+    #[must_use]
+    #[inline]
+    fn coalescing_observed() -> bool { Self::coalescing_flag().is_some_and(|f| f.load(Relaxed)) }
+
+    /// Get the flag that [`handler`] sets upon a delivery of the signal when the counter was
+    /// already saturated at its maximum, i.e. when the delivery couldn't be counted and so was
+    /// lost.  Return `None` to not have the flag, which is the default.
+    ///
+    /// This is most likely with a narrow [`Self::AtomicUInt`], like `AtomicU8`, when signals are
+    /// delivered faster than the consuming thread takes the counter.
+    #[must_use]
+    #[inline]
+    fn overflow_flag() -> Option<&'static AtomicBool> { None }
+
+    /// Whether [`Self::overflow_flag()`] is used and has been set.
+    #[must_use]
+    #[inline]
+    fn overflowed() -> bool { Self::overflow_flag().is_some_and(|f| f.load(Relaxed)) }
+
+    /// Like [`Self::overflowed()`], but also clear the flag, so that later overflows can be
+    /// noticed separately.  E.g. for a delegate to log that some deliveries were lost.
+    #[must_use]
+    #[inline]
+    fn take_overflowed() -> bool { Self::overflow_flag().is_some_and(|f| f.swap(false, Relaxed)) }
+
+    /// Get the slot that [`handler`] stores the time of each delivery of the signal into, in
+    /// nanoseconds of [`Self::CLOCK`].  Return `None` to not have the slot, which is the default.
+    ///
+    /// The storing is only done when the crate feature `"timestamps"` is enabled, so that the
+    /// extra `clock_gettime()` per delivery is only paid for when wanted.  Otherwise, the slot is
+    /// never written.
+    #[must_use]
+    #[inline]
+    fn timestamp_slot() -> Option<&'static AtomicU64> { None }
+
+    /// The time, in nanoseconds of [`Self::CLOCK`], of the most recent delivery of the signal,
+    /// as stored in [`Self::timestamp_slot()`].  `None` if there's no slot or if nothing was
+    /// stored yet.  E.g. for diagnosing the latency of processing a receipt.
+    #[must_use]
+    #[inline]
+    fn last_received_nanos() -> Option<u64> {
+        Self::timestamp_slot().map(|slot| slot.load(Relaxed)).filter(|&nanos| nanos != 0)
+    }
+
+    /// Get the counter that [`handler`] increments upon each delivery of the signal, which,
+    /// unlike [`Self::counter()`], is never taken nor reset, and so it's the "generation" of the
+    /// latest delivery.  Return `None` to not have it, which is the default.
+    ///
+    /// The incrementing is only done when the crate feature `"generations"` is enabled, so that
+    /// the extra atomic write per delivery is only paid for when wanted.  Otherwise, it's never
+    /// written.
+    #[must_use]
+    #[inline]
+    fn generation_slot() -> Option<&'static AtomicU64> { None }
+
+    /// The current generation, as counted in [`Self::generation_slot()`].  `None` if there's no
+    /// slot.  E.g. for a delegate to diff with the generation it saw the previous time it was
+    /// called, to know how many deliveries happened in between, even when those were coalesced
+    /// into fewer receipts or were lost to overflow of the counter.  (It wraps around, so diff
+    /// with `wrapping_sub`.)
+    #[must_use]
+    #[inline]
+    fn generation() -> Option<u64> { Self::generation_slot().map(|slot| slot.load(Relaxed)) }
+}
+
+/// The ability to also record, upon receipt of a particular signal, some of the information
+/// about the delivery that the OS gives with `SA_SIGINFO`, e.g. which process sent the signal.
+///
+/// Used by [`handler_with_info`], as installed by [`install_handler_with_info`].
+pub trait SignalReceiptWithInfo<const SIGNUM: SignalNumber>: SignalReceipt<SIGNUM> {
+    /// Get the reference to the slot that [`handler_with_info`] stores the information of each
+    /// delivery of the signal into, before incrementing the counter.  The consuming thread can
+    /// then [`load`](SigInfoSlot::load) the information of the latest delivery.
+    ///
+    /// The lifetime must be `'static` for the same reason as for [`SignalReceipt::counter()`].
+    #[must_use]
+    fn info_slot() -> &'static SigInfoSlot;
+}
+
+/// The ability to also queue, upon receipt of a particular real-time signal, the value that was
+/// sent with it via `sigqueue()`, so that each is processed, e.g. for IPC where the values are
+/// messages.
+///
+/// Used by [`handler_with_value`], as installed by [`install_handler_with_value`], and by
+/// [`consume_values_then_delegate`].  Only on Linux and Android, like [`rt`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub trait SignalReceiptWithValue<const SIGNUM: SignalNumber>: SignalReceipt<SIGNUM> {
+    /// Get the reference to the queue that [`handler_with_value`] pushes the value of each
+    /// delivery of the signal into, before incrementing the counter.
+    ///
+    /// The lifetime must be `'static` for the same reason as for [`SignalReceipt::counter()`].
+    #[must_use]
+    fn value_queue() -> &'static SigValueQueue;
 }
 
 
 /// A signal handler that increments a receipt counter and posts a semaphore.
 ///
 /// Everything done in this is async-signal-safe.
+///
+/// When `debug_assertions` are enabled, this first checks that the signal number it's called
+/// with is `SIGNUM` (as resolved by [`resolve_signum`]), and aborts the process if not.  Enabling
+/// the crate feature `no_handler_debug_check` removes this check, for extremely hot signal paths,
+/// but that also removes the safety net that catches installing a handler for the wrong signal
+/// number.  [`SignalReceipt::VERIFY_SIGNO`] can also opt-out of this check per `impl`.
 #[allow(clippy::missing_inline_in_public_items)]
 pub extern "C" fn handler<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
-    _signo: SignalNumber,
+    signo: SignalNumber,
+) {
+    handle::<SIGNUM, T>(signo);
+}
+
+/// Like [`handler`], but of the `SA_SIGINFO` type, and it first stores some of the given
+/// information about the delivery in the [`SignalReceiptWithInfo::info_slot()`].
+///
+/// Everything done in this is async-signal-safe.
+#[allow(
+    clippy::missing_inline_in_public_items,
+    clippy::not_unsafe_ptr_arg_deref, // Only the OS calls this, with a proper pointer.
+    unsafe_code
+)]
+pub extern "C" fn handler_with_info<
+    const SIGNUM: SignalNumber,
+    T: SignalReceiptWithInfo<SIGNUM>,
+>(
+    signo: SignalNumber,
+    info: *mut SigInfo,
+    _context: *mut c_void,
 ) {
-    #[cfg(debug_assertions)]
+    // SAFETY: When non-null, the OS gives a valid pointer, to an initialized `siginfo_t`, that
+    // remains valid for the duration of this call.
+    if let Some(info) = unsafe { info.as_ref() } {
+        T::info_slot().store(SigInfoFields::from_raw(info));
+    }
+    handle::<SIGNUM, T>(signo);
+}
+
+/// Like [`handler_with_info`], but it first pushes the `sival_int` that was sent with the signal
+/// onto the [`SignalReceiptWithValue::value_queue()`].
+///
+/// Everything done in this is async-signal-safe.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(
+    clippy::missing_inline_in_public_items,
+    clippy::not_unsafe_ptr_arg_deref, // Only the OS calls this, with a proper pointer.
+    unsafe_code
+)]
+pub extern "C" fn handler_with_value<
+    const SIGNUM: SignalNumber,
+    T: SignalReceiptWithValue<SIGNUM>,
+>(
+    signo: SignalNumber,
+    info: *mut SigInfo,
+    _context: *mut c_void,
+) {
+    // SAFETY: When non-null, the OS gives a valid pointer, to an initialized `siginfo_t`, that
+    // remains valid for the duration of this call.
+    if let Some(info) = unsafe { info.as_ref() } {
+        let _pushed = T::value_queue().push(sival_int(info));
+    }
+    handle::<SIGNUM, T>(signo);
+}
+
+/// The common body of [`handler`] and [`handler_with_info`] (et al).
+#[inline]
+fn handle<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(_signo: SignalNumber) {
+    #[cfg(all(debug_assertions, not(feature = "no_handler_debug_check")))]
     #[allow(clippy::used_underscore_binding)]
-    if _signo != SIGNUM {
-        abort(b"must only be installed for the corresponding `const SIGNUM`.");
+    if T::VERIFY_SIGNO && _signo != resolve_signum(SIGNUM) {
+        util::abort_with_num(
+            b"must only be installed for the corresponding `const SIGNUM`, but was called for: ",
+            _signo,
+        );
     }
 
     // A signal handler must restore `errno` if it might alter it.
     let prev_errno = errno();
 
-    T::counter().saturating_incr();
+    let is_wake = count_delivery::<SIGNUM, T>();
+
+    if !is_wake {
+        // Rate-limited.  The consuming thread will notice this delivery when it's woken later.
+        return;
+    }
 
     if let Some(sem) = T::semaphore() {
         // Our change to the counter will be visible, as happens-before, to the thread that wakes.
@@ -110,6 +458,45 @@ pub extern "C" fn handler<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
     }
 }
 
+/// Count one delivery of the signal, like [`handler`] does but without waking.  Return whether
+/// waking should be done, per [`SignalReceipt::WAKE_EVERY`].
+///
+/// This is async-signal-safe.
+#[inline]
+pub(crate) fn count_delivery<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>() -> bool {
+    // Done first, to be as close as possible to the time of the delivery.
+    #[cfg(feature = "timestamps")]
+    if let Some(slot) = T::timestamp_slot() {
+        slot.store(T::CLOCK.now_nanos(), Relaxed);
+    }
+    #[cfg(feature = "generations")]
+    if let Some(slot) = T::generation_slot() {
+        let _prev = slot.fetch_add(1, Relaxed);
+    }
+
+    let (new, is_lost) = T::counter().saturating_incr_reporting_with(T::INCR_STRATEGY);
+    let is_wake = T::AtomicUInt::is_multiple(new, T::WAKE_EVERY);
+
+    if new > 1.into() {
+        if let Some(flag) = T::coalescing_flag() {
+            flag.store(true, Relaxed);
+        }
+    }
+    if is_lost {
+        if let Some(flag) = T::overflow_flag() {
+            flag.store(true, Relaxed);
+        }
+    }
+
+    if let Some((word, bit)) = T::dirty_bit() {
+        word.fetch_or(1_u64.checked_shl(bit).unwrap_or(0), Release);
+    }
+
+    T::on_delivery();
+
+    is_wake
+}
+
 /// Install [`handler`] for the given `SIGNUM`, using the given `SignalReceipt<SIGNUM>`
 /// implementation.
 ///
@@ -126,17 +513,278 @@ pub fn install_handler<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
     mask: bool,
     restart: bool,
 ) {
+    #![allow(clippy::expect_used)]
+
+    let r = try_install_handler::<SIGNUM, T>(mask, restart);
+    r.expect("signal number should be valid");
+}
+
+/// Like [`install_handler`], but returns an error instead of panicking if installing fails.  E.g.
+/// for signal numbers that come from configuration, which the OS might reject.
+///
+/// # Errors
+/// If `sigaction()` fails, with its `errno`.
+#[inline]
+pub fn try_install_handler<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+    mask: bool,
+    restart: bool,
+) -> Result<(), InstallHandlerError> {
+    install_handler_from_template::<SIGNUM, T>(&handler_template(mask, restart))
+}
+
+/// Like [`install_handler`], but with the mask and flags as given by `flags`, which enables
+/// more of them.
+///
+/// # Panics
+/// If installing the handler fails.  Only possible if an invalid signal number was given.
+#[inline]
+pub fn install_handler_with_flags<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+    flags: SigActionFlags,
+) {
+    #![allow(clippy::expect_used)]
+
+    let mut template = handler_template(flags.mask, flags.restart);
+    if flags.no_defer {
+        // Else, the signal would still be masked during its handler, by being in the mask.
+        template = template.no_defer().unmask(resolve_signum(SIGNUM));
+    }
+    let r = install_handler_from_template::<SIGNUM, T>(&template);
+    r.expect("signal number should be valid");
+}
+
+/// Like [`install_handler`], but instead of all or none, only the given `signums` (except the
+/// exceptional ones, like for `mask`) will be masked during when [`handler`] is called upon
+/// delivery of this signal (as well as the signal itself, as usual).  E.g. for a `SIGHUP` handler
+/// that blocks only `SIGTERM` while it runs.
+///
+/// # Panics
+/// If installing the handler fails.  Only possible if an invalid signal number was given.
+#[inline]
+pub fn install_handler_masking<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+    signums: &[SignalNumber],
+    restart: bool,
+) {
+    #![allow(clippy::expect_used)]
+
+    let template = handler_template(false, restart).mask_these(signums);
+    let r = install_handler_from_template::<SIGNUM, T>(&template);
+    r.expect("signal number should be valid");
+}
+
+/// Like [`install_handler`], but with `SA_ONSTACK`, so that [`handler`] executes on the
+/// alternate signal stack of the thread that the signal is delivered to.  E.g. when another
+/// handler, for `SIGSEGV` due to stack overflow, already needs that stack to exist.
+///
+/// `SA_ONSTACK` only matters for a thread that has an alternate signal stack registered, and so
+/// each thread that could receive the signal must have done
+/// [`install_alt_stack`](util::install_alt_stack) (or the like), else `handler` executes on the
+/// normal stack of that thread.
+///
+/// # Panics
+/// If installing the handler fails.  Only possible if an invalid signal number was given.
+#[inline]
+pub fn install_handler_on_altstack<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+    mask: bool,
+    restart: bool,
+) {
+    #![allow(clippy::expect_used)]
+
+    let template = handler_template(mask, restart).on_stack();
+    let r = install_handler_from_template::<SIGNUM, T>(&template);
+    r.expect("signal number should be valid");
+}
+
+/// The mask and flags for [`install_handler_with_flags`].  None are enabled by default.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[must_use]
+pub struct SigActionFlags {
+    mask:     bool,
+    restart:  bool,
+    no_defer: bool,
+}
+
+impl SigActionFlags {
+    /// Like the `mask` argument of [`install_handler`].
+    #[inline]
+    pub fn mask_all(mut self) -> Self {
+        self.mask = true;
+        self
+    }
+
+    /// Like the `restart` argument of [`install_handler`].
+    #[inline]
+    pub fn restart_intr(mut self) -> Self {
+        self.restart = true;
+        self
+    }
+
+    /// Enable `SA_NODEFER`, so that the signal isn't masked during execution of [`handler`],
+    /// which can then be re-entered by another delivery of it.  E.g. so that edge-triggered
+    /// deliveries aren't delayed while the handler runs.  (The counting done by `handler` is
+    /// still correct when re-entered.)
+    ///
+    /// This also overrides [`Self::mask_all`] for only the signal itself.
+    #[inline]
+    pub fn no_defer(mut self) -> Self {
+        self.no_defer = true;
+        self
+    }
+}
+
+/// Error from [`try_install_handler`] or [`try_uninstall_handler`], when `sigaction()` fails.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
+pub struct InstallHandlerError {
+    /// The signal number that installing was for.
+    pub signum: SignalNumber,
+    /// The `errno` that `sigaction()` set, e.g. `EINVAL` for an invalid signal number.
+    pub errno:  Errno,
+}
+
+impl InstallHandlerError {
+    /// With the current `errno`, as set by the failure.
+    fn last(signum: SignalNumber) -> Self { Self { signum, errno: errno() } }
+}
+
+impl Display for InstallHandlerError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "installing for signal number {} failed: {}", self.signum, self.errno)
+    }
+}
+
+/// Like [`install_handler`], but also returns the disposition that was previously associated
+/// with `SIGNUM`, so that it can be restored exactly, by [`uninstall_handler_restoring_old`],
+/// whether it was another handler, ignoring, or the default.  E.g. to install temporarily, around
+/// a critical section.
+///
+/// # Panics
+/// If installing the handler fails.  Only possible if an invalid signal number was given.
+#[inline]
+pub fn install_handler_returning_old<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+    mask: bool,
+    restart: bool,
+) -> OldDisposition {
     #![allow(unsafe_code, clippy::expect_used)]
 
-    let mut action = SigAction::handler(handler::<SIGNUM, T>);
+    let mut action = handler_template(mask, restart);
+    action.set_handler(handler::<SIGNUM, T>);
+    let signum = resolve_signum(SIGNUM);
+    // SAFETY: `handler` is async-signal-safe.
+    let r = unsafe { action.install_returning_old(signum) };
+    OldDisposition { signum, action: r.expect("signal number should be valid") }
+}
+
+/// The disposition of a signal as it was before [`install_handler_returning_old`] replaced it.
+#[must_use]
+#[derive(Debug)]
+pub struct OldDisposition {
+    signum: SignalNumber,
+    action: SigAction,
+}
+
+impl OldDisposition {
+    /// The signal number that this is the old disposition of.
+    #[must_use]
+    #[inline]
+    pub fn signum(&self) -> SignalNumber { self.signum }
+}
+
+/// The `SigAction`, without the handler yet, as configured for [`install_handler`].  This can be
+/// built once and reused with [`install_handler_from_template`] for installing multiple handlers.
+fn handler_template(mask: bool, restart: bool) -> SigAction {
+    let mut action = SigAction::default();
     if mask {
         action = action.mask_all();
     }
     if restart {
         action = action.restart_intr();
     }
+    action
+}
+
+/// Like [`try_install_handler`], but with the mask and flags as already built in `template`.
+fn install_handler_from_template<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+    template: &SigAction,
+) -> Result<(), InstallHandlerError> {
+    #![allow(unsafe_code)]
+
+    let signum = resolve_signum(SIGNUM);
+    let mut action = template.clone();
+    action.set_handler(handler::<SIGNUM, T>);
     // SAFETY: `handler` is async-signal-safe.
-    let r = unsafe { action.install(SIGNUM) };
+    let r = unsafe { action.install(signum) };
+    r.map_err(|()| InstallHandlerError::last(signum))
+}
+
+/// Like [`install_handler_from_template`], but installs [`handler_with_value`] instead, with
+/// `SA_SIGINFO` added to the flags of `template`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn install_handler_with_value_from_template<
+    const SIGNUM: SignalNumber,
+    T: SignalReceiptWithValue<SIGNUM>,
+>(
+    template: &SigAction,
+) -> Result<(), InstallHandlerError> {
+    #![allow(unsafe_code)]
+
+    let signum = resolve_signum(SIGNUM);
+    let mut action = template.clone();
+    action.set_handler_with_info(handler_with_value::<SIGNUM, T>);
+    // SAFETY: `handler_with_value` is async-signal-safe.
+    let r = unsafe { action.install(signum) };
+    r.map_err(|()| InstallHandlerError::last(signum))
+}
+
+/// Like [`install_handler`], but installs [`handler_with_info`] instead, with `SA_SIGINFO`, so
+/// that the information about each delivery is also recorded.
+///
+/// # Panics
+/// If installing the handler fails.  Only possible if an invalid signal number was given.
+#[inline]
+pub fn install_handler_with_info<const SIGNUM: SignalNumber, T: SignalReceiptWithInfo<SIGNUM>>(
+    mask: bool,
+    restart: bool,
+) {
+    #![allow(unsafe_code, clippy::expect_used)]
+
+    let mut action = SigAction::handler_with_info(handler_with_info::<SIGNUM, T>);
+    if mask {
+        action = action.mask_all();
+    }
+    if restart {
+        action = action.restart_intr();
+    }
+    // SAFETY: `handler_with_info` is async-signal-safe.
+    let r = unsafe { action.install(resolve_signum(SIGNUM)) };
+    r.expect("signal number should be valid");
+}
+
+/// Like [`install_handler_with_info`], but installs [`handler_with_value`] instead, so that the
+/// value sent with each delivery is queued.
+///
+/// # Panics
+/// If installing the handler fails.  Only possible if an invalid signal number was given.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[inline]
+pub fn install_handler_with_value<
+    const SIGNUM: SignalNumber,
+    T: SignalReceiptWithValue<SIGNUM>,
+>(
+    mask: bool,
+    restart: bool,
+) {
+    #![allow(unsafe_code, clippy::expect_used)]
+
+    let mut action = SigAction::handler_with_info(handler_with_value::<SIGNUM, T>);
+    if mask {
+        action = action.mask_all();
+    }
+    if restart {
+        action = action.restart_intr();
+    }
+    // SAFETY: `handler_with_value` is async-signal-safe.
+    let r = unsafe { action.install(resolve_signum(SIGNUM)) };
     r.expect("signal number should be valid");
 }
 
@@ -147,19 +795,88 @@ pub fn install_handler<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
 /// If installing the default fails.  Only possible if an invalid signal number was given.
 #[inline]
 pub fn uninstall_handler<const SIGNUM: SignalNumber>() {
-    #![allow(unsafe_code, clippy::expect_used)]
+    #![allow(clippy::expect_used)]
+
+    let r = try_uninstall_handler::<SIGNUM>();
+    r.expect("signal number should be valid");
+}
+
+/// Like [`uninstall_handler`], but returns an error instead of panicking if installing the
+/// default fails.
+///
+/// # Errors
+/// If `sigaction()` fails, with its `errno`.
+#[inline]
+pub fn try_uninstall_handler<const SIGNUM: SignalNumber>() -> Result<(), InstallHandlerError> {
+    #![allow(unsafe_code)]
 
+    let signum = resolve_signum(SIGNUM);
     let action = SigAction::default();
     // SAFETY: `SIG_DFL` handling is async-signal-safe, because no user function is called.
-    let r = unsafe { action.install(SIGNUM) };
+    let r = unsafe { action.install(signum) };
+    r.map_err(|()| InstallHandlerError::last(signum))
+}
+
+/// Uninstall whatever handler might be installed for the signal of `old`, by restoring its
+/// disposition to `old`, including the flags and mask that it had.
+///
+/// # Panics
+/// If restoring fails.  Not possible, because `old` was associated with its signal number before.
+#[inline]
+pub fn uninstall_handler_restoring_old(old: OldDisposition) {
+    #![allow(unsafe_code, clippy::expect_used)]
+
+    // SAFETY: The old action was already installed before, and so its handler is as
+    // async-signal-safe as whoever installed it ensured.
+    let r = unsafe { old.action.install(old.signum) };
     r.expect("signal number should be valid");
 }
 
-/// Assign zero to the counter of the given `SIGNUM`, using the given `SignalReceipt<SIGNUM>`
-/// implementation.
+/// Whether [`handler`], for the given `SIGNUM` and `SignalReceipt<SIGNUM>` implementation, is the
+/// handler that is currently installed for `SIGNUM`.  E.g. to verify that installing took effect
+/// and wasn't clobbered by something else.
+///
+/// Returns `false` if `SIGNUM` is an invalid signal number.
+#[must_use]
+#[inline]
+pub fn is_handler_installed<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>() -> bool {
+    SigAction::current(resolve_signum(SIGNUM))
+        .is_ok_and(|action| action.is_handler(handler::<SIGNUM, T>))
+}
+
+/// Like [`is_handler_installed`], but for [`handler_with_value`], as installed by
+/// [`install_handler_with_value`].
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[must_use]
+#[inline]
+pub fn is_handler_with_value_installed<
+    const SIGNUM: SignalNumber,
+    T: SignalReceiptWithValue<SIGNUM>,
+>() -> bool {
+    SigAction::current(resolve_signum(SIGNUM))
+        .is_ok_and(|action| action.is_handler_with_info(handler_with_value::<SIGNUM, T>))
+}
+
+/// Assign zero to the counter of the given `SIGNUM`, and clear its coalescing flag and overflow
+/// flag (if any), using the given `SignalReceipt<SIGNUM>` implementation.
 #[inline]
 pub fn reset_counter<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>() {
     let _count = <T as SignalReceipt<SIGNUM>>::take_count();
+    if let Some(flag) = <T as SignalReceipt<SIGNUM>>::coalescing_flag() {
+        flag.store(false, Relaxed);
+    }
+    let _overflowed = <T as SignalReceipt<SIGNUM>>::take_overflowed();
+}
+
+/// Get the current value of the counter of the given `SIGNUM`, without resetting it, using the
+/// given `SignalReceipt<SIGNUM>` implementation.
+///
+/// Same as [`SignalReceipt::peek_count()`].
+#[must_use]
+#[inline]
+pub fn peek_counter<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>(
+) -> <T::AtomicUInt as AtomicUInt>::UInt {
+    <T as SignalReceipt<SIGNUM>>::peek_count()
 }
 
 
@@ -170,6 +887,123 @@ pub fn reset_counter<const SIGNUM: SignalNumber, T: SignalReceipt<SIGNUM>>() {
 /// to process subsequent receipts or breaks to finish immediately.
 pub type Consumer<B = (), C = ()> = dyn FnMut(C) -> ControlFlow<B, C>;
 
+/// What [`consume_loop()`] waits on to be woken when signals have been received.
+///
+/// This is implemented for the real [`Semaphore`], which is what's normally used.  Other
+/// implementations, e.g. a mock that a test drives manually, enable testing the loop's logic
+/// deterministically without needing a real semaphore or real signals.
+pub trait WaitSource {
+    /// Initialize, if not already, retrying up to `limit` times if another thread is currently
+    /// doing so.  Return `false` if that couldn't be done.
+    #[must_use]
+    fn try_init(&self, limit: u64) -> bool;
+
+    /// Block until woken.  Only called after `Self::try_init` succeeded.
+    ///
+    /// # Errors
+    /// If the waiting was interrupted by a signal, in which case `errno` must be `EINTR`.
+    #[allow(clippy::result_unit_err)]
+    fn wait(&self) -> Result<(), ()>;
+
+    /// Wake a waiter.
+    ///
+    /// # Errors
+    /// If waking can't be done.  `errno` is set to indicate the error.
+    #[allow(clippy::result_unit_err)]
+    fn post(&self) -> Result<(), ()>;
+}
+
+impl WaitSource for Pin<&Semaphore> {
+    #[inline]
+    fn try_init(&self, limit: u64) -> bool { SemaphoreMethods::try_init(*self, limit).is_some() }
+
+    #[inline]
+    fn wait(&self) -> Result<(), ()> { self.sem_ref().map_err(drop)?.wait() }
+
+    #[inline]
+    fn post(&self) -> Result<(), ()> { self.sem_ref().map_err(drop)?.post() }
+}
+
+/// Decrement the value of the given semaphore until it's zero, via `sem_trywait()`, but at most
+/// `limit` times, to avoid a long delay when its value is high.  Returns how many times it was
+/// decremented.  Does nothing if it's not initialized.
+///
+/// This is best-effort: it races with concurrent posts (e.g. by [`handler`]), and so the value
+/// might not be zero afterward, and it's not possible to distinguish posts that were made
+/// before this from those made during this.  E.g. to avoid a consuming loop iterating
+/// pointlessly for posts that were left over from before re-installing handling.
+#[must_use]
+#[inline]
+pub fn drain_semaphore(sem: Pin<&Semaphore>, limit: u64) -> u64 {
+    let Ok(sem) = sem.sem_ref() else { return 0 };
+    let mut drained = 0;
+    while drained < limit && sem.try_wait().is_ok() {
+        drained = drained.saturating_add(1);
+    }
+    drained
+}
+
+/// A [`WaitSource`] that can also stop waiting after a timeout, as [`consume_loop_timed()`]
+/// needs.
+///
+/// This is implemented for the real [`Semaphore`], via `sem_clockwait()` or `sem_timedwait()`
+/// (see [`WaitClock`] for which), except on macOS which has neither.
+pub trait TimedWaitSource: WaitSource {
+    /// Like [`WaitSource::wait`], but stop blocking once the `timeout`, as measured by the given
+    /// `clock`, has elapsed.
+    ///
+    /// # Errors
+    /// If the `timeout` elapsed before being woken, in which case `errno` must be `ETIMEDOUT`.
+    /// Or, if the waiting was interrupted by a signal, in which case `errno` must be `EINTR`.
+    #[allow(clippy::result_unit_err)]
+    fn timed_wait(&self, timeout: Duration, clock: WaitClock) -> Result<(), ()>;
+}
+
+#[cfg(not(target_os = "macos"))]
+impl TimedWaitSource for Pin<&Semaphore> {
+    #[inline]
+    fn timed_wait(&self, timeout: Duration, clock: WaitClock) -> Result<(), ()> {
+        let sem = self.sem_ref().map_err(drop)?;
+        match clock {
+            WaitClock::Realtime => {
+                help::sem_timedwait(sem, &util::deadline(libc::CLOCK_REALTIME, timeout))
+            },
+            WaitClock::Monotonic => {
+                cfg_if::cfg_if! {
+                    if #[cfg(all(target_os = "linux", target_env = "gnu"))] {
+                        let deadline = util::deadline(libc::CLOCK_MONOTONIC, timeout);
+                        help::sem_clockwait(sem, libc::CLOCK_MONOTONIC, &deadline)
+                    } else {
+                        timed_wait_monotonic_fallback(sem, timeout)
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// For [`WaitClock::Monotonic`] where `sem_clockwait()` isn't available: wait via
+/// `sem_timedwait()`, by `CLOCK_REALTIME`, but measure the time that actually elapsed by
+/// `CLOCK_MONOTONIC`, and wait again for the rest if the former was adjusted forward.
+#[cfg(not(any(target_os = "macos", all(target_os = "linux", target_env = "gnu"))))]
+fn timed_wait_monotonic_fallback(sem: SemaphoreRef<'_>, timeout: Duration) -> Result<(), ()> {
+    let start = util::ClockSource::Monotonic.now_nanos();
+    let mut remaining = timeout;
+    loop {
+        let r = help::sem_timedwait(sem, &util::deadline(libc::CLOCK_REALTIME, remaining));
+        if r.is_err() && errno().0 == libc::ETIMEDOUT {
+            let now = util::ClockSource::Monotonic.now_nanos();
+            let elapsed = Duration::from_nanos(now.saturating_sub(start));
+            // (Reading the clock doesn't change `errno`, and so it's still `ETIMEDOUT`.)
+            if let Some(rest) = timeout.checked_sub(elapsed).filter(|d| !d.is_zero()) {
+                remaining = rest;
+                continue;
+            }
+        }
+        break r;
+    }
+}
+
 /// The common pattern of a thread that is woken to process signals that were received.
 ///
 /// Intended to be used as (or within) the start function of a dedicated thread.
@@ -181,6 +1015,8 @@ pub type Consumer<B = (), C = ()> = dyn FnMut(C) -> ControlFlow<B, C>;
 /// current thread.  If it's `false`, all signals will be unmasked to be unblocked, in which case
 /// the given `consumers` functions must remain correct when interrupted by signals.
 ///
+/// The `sem` is normally a [`Semaphore`], but it can be any [`WaitSource`].
+///
 /// If `try_init_limit` is positive, initializing `sem` will be retried up to that many times,
 /// which can be useful if other threads might race to initialize it.  Another thread that is
 /// currently executing the initialization will take a short time, in which case it can be useful
@@ -190,16 +1026,237 @@ pub type Consumer<B = (), C = ()> = dyn FnMut(C) -> ControlFlow<B, C>;
 /// - If semaphore operations fail due to the given `sem`, or the system's limits on semaphores,
 ///   being in an unusual state.  Won't happen when used as intended.
 /// - If one of the given `consumers` does.
+#[allow(clippy::needless_pass_by_value)] // So that a `Pin<&Semaphore>` can be passed as is.
 #[inline]
-pub fn consume_loop<B, C>(
+pub fn consume_loop<B, C, W: WaitSource>(
     do_mask: bool,
-    sem: Pin<&Semaphore>,
+    sem: W,
     try_init_limit: u64,
-    mut state: C,
+    state: C,
+    consumers: &mut [&mut Consumer<B, C>],
+    continue_flag: &AtomicBool,
+    finish: B,
+) -> B {
+    consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        None,
+        finish,
+        |_| {},
+        WaitSource::wait,
+    )
+}
+
+/// Like [`consume_loop()`], but returns an error instead of panicking if initializing `sem`
+/// fails, e.g. due to tight limits on semaphores in a container, so that the caller can report
+/// that cleanly.
+///
+/// # Errors
+/// If initializing `sem` fails, even after retrying `try_init_limit` times.
+///
+/// # Panics
+/// Same as `consume_loop`, other than for initializing.
+#[allow(clippy::needless_pass_by_value)] // So that a `Pin<&Semaphore>` can be passed as is.
+#[inline]
+pub fn consume_loop_fallible<B, C, W: WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    state: C,
+    consumers: &mut [&mut Consumer<B, C>],
+    continue_flag: &AtomicBool,
+    finish: B,
+) -> Result<B, ConsumeLoopError> {
+    try_consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        None,
+        finish,
+        |_| {},
+        WaitSource::wait,
+    )
+}
+
+/// Error from [`consume_loop_fallible`] (or the like), when the loop couldn't start.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[non_exhaustive]
+pub enum ConsumeLoopError {
+    /// Initializing the semaphore (or other [`WaitSource`]) failed.
+    SemaphoreInit,
+}
+
+impl Display for ConsumeLoopError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SemaphoreInit => write!(f, "semaphore initialization failed"),
+        }
+    }
+}
+
+/// Like [`consume_loop()`], but `on_start` is called once, with the state, after `sem` is
+/// initialized and before the first wait (and before the `consumers` are first called).  E.g. to
+/// signal readiness to a parent process precisely when the loop is live, since any signal
+/// received from then on will be processed.
+///
+/// This is distinct from the `consumers`, which are called every iteration.
+///
+/// # Panics
+/// Same as `consume_loop`.
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+#[inline]
+pub fn consume_loop_with_start<B, C, W: WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    state: C,
+    consumers: &mut [&mut Consumer<B, C>],
+    continue_flag: &AtomicBool,
+    on_start: impl FnOnce(&mut C),
+    finish: B,
+) -> B {
+    consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        None,
+        finish,
+        on_start,
+        WaitSource::wait,
+    )
+}
+
+/// Like [`consume_loop()`], but the `consumers` aren't called while `pause_flag` is `true`, which
+/// is re-checked each time the loop is woken.  E.g. to suspend processing during a maintenance
+/// window, without uninstalling the handlers.
+///
+/// Pausing doesn't lose any receipts: the counters continue to be incremented, and, once
+/// `pause_flag` is assigned `false` and the loop is woken (e.g. by posting `sem`), the
+/// accumulated counts are processed all at once.  The `continue_flag` is still checked while
+/// paused.
+///
+/// # Panics
+/// Same as `consume_loop`.
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+#[inline]
+pub fn consume_loop_pausable<B, C, W: WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    state: C,
     consumers: &mut [&mut Consumer<B, C>],
     continue_flag: &AtomicBool,
+    pause_flag: &AtomicBool,
     finish: B,
 ) -> B {
+    consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        Some(pause_flag),
+        finish,
+        |_| {},
+        WaitSource::wait,
+    )
+}
+
+/// Like [`consume_loop()`], but each wait stops after `timeout`, as measured by `clock`, and then
+/// the `consumers` are called again as usual, even though no signals might have been received.
+/// E.g. so that they can do periodic housekeeping, like flushing metrics or checking a watchdog.
+///
+/// # Panics
+/// Same as `consume_loop`.
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+#[inline]
+pub fn consume_loop_timed<B, C, W: TimedWaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    timeout: Duration,
+    clock: WaitClock,
+    state: C,
+    consumers: &mut [&mut Consumer<B, C>],
+    continue_flag: &AtomicBool,
+    finish: B,
+) -> B {
+    consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        None,
+        finish,
+        |_| {},
+        |sem| sem.timed_wait(timeout, clock),
+    )
+}
+
+/// The common body of [`consume_loop()`] and [`consume_loop_timed()`] et al, which differ only in
+/// how they `wait` and in their options.
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+pub(crate) fn consume_loop_impl<B, C, W: WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    state: C,
+    consumers: &mut [&mut (dyn FnMut(C) -> ControlFlow<B, C> + '_)],
+    continue_flag: &AtomicBool,
+    pause_flag: Option<&AtomicBool>,
+    finish: B,
+    on_start: impl FnOnce(&mut C),
+    wait: impl Fn(&W) -> Result<(), ()>,
+) -> B {
+    let r = try_consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        pause_flag,
+        finish,
+        on_start,
+        wait,
+    );
+    #[allow(clippy::expect_used)]
+    r.expect("semaphore initialization must succeed")
+}
+
+/// Like [`consume_loop_impl`], but returns an error instead of panicking if initializing `sem`
+/// fails.
+#[allow(
+    clippy::needless_pass_by_value,
+    clippy::too_many_arguments,
+    clippy::panic_in_result_fn // Only for the failures of waiting, which won't happen.
+)]
+pub(crate) fn try_consume_loop_impl<B, C, W: WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    mut state: C,
+    consumers: &mut [&mut (dyn FnMut(C) -> ControlFlow<B, C> + '_)],
+    continue_flag: &AtomicBool,
+    pause_flag: Option<&AtomicBool>,
+    finish: B,
+    on_start: impl FnOnce(&mut C),
+    wait: impl Fn(&W) -> Result<(), ()>,
+) -> Result<B, ConsumeLoopError> {
     if do_mask {
         // If signal(s) are delivered to this thread before we mask to prevent that, our handler
         // will be called as usual, and everything will still work because we check the counters
@@ -207,9 +1264,8 @@ pub fn consume_loop<B, C>(
 
         // Prevent this thread from handling the application's signals, so that it's more
         // efficient at making progress on processing their receipts.  This seems to be helpful
-        // when very many signals are incoming.  Also, this prevents `EINTR` of our `sem_wait()`
-        // (which can be handled (see below) such that our loop would still work, but that isn't
-        // quite as simple).
+        // when very many signals are incoming.  Also, this makes `EINTR` of our `sem_wait()`
+        // rare (but still possible, which is handled (see below) such that our loop still works).
         mask_all_signals_of_current_thread();
     } else {
         // Ensure this thread can and probably will have the application's signals delivered to it
@@ -227,18 +1283,27 @@ pub fn consume_loop<B, C>(
     // Initialize the semaphore if it's not already, retrying the given amount of times.  This
     // supports various use cases where the semaphore might already be initialized or where other
     // threads might race to do the initialization.
-    #[allow(clippy::expect_used)]
-    let sem = sem.try_init(try_init_limit).expect("semaphore initialization must succeed");
+    if !sem.try_init(try_init_limit) {
+        return Err(ConsumeLoopError::SemaphoreInit);
+    }
+
+    // Any signal received from now on will wake us to be processed.
+    on_start(&mut state);
 
     let is_discontinue = || !continue_flag.load(Relaxed);
+    let is_paused = || pause_flag.is_some_and(|f| f.load(Relaxed));
 
-    'outer: loop {
+    let broke_with = 'outer: loop {
         // Check here also, in case `consumers` is empty.
         if is_discontinue() {
             break finish;
         }
 
-        for consume in &mut *consumers {
+        // When paused, the consumers aren't called, and so the counters keep accumulating, to
+        // be taken all at once after resuming.
+        let current: &mut [&mut _] = if is_paused() { &mut [] } else { &mut *consumers };
+
+        for consume in current {
             match consume(state) {
                 ControlFlow::Continue(val) => state = val,
                 ControlFlow::Break(val) => break 'outer val,
@@ -252,15 +1317,19 @@ pub fn consume_loop<B, C>(
         // At the end of the loop, wait, in case any signals were received before the semaphore
         // was initialized.  Changes to the counters or to the continue-flag, that happen-before
         // the semaphore is posted to wake us, will be visible to us next.
-        let r = sem.wait();
-        if do_mask {
-            #[allow(clippy::expect_used)]
-            r.expect("`sem_wait()` will succeed");
-        } else if r.is_err() {
+        //
+        // Even when `do_mask`, the exceptional signals (e.g. `SIGABRT`, for which some other
+        // library might have a handler) aren't masked, and a debugger can interrupt also, and so
+        // `EINTR` is possible either way.  Looping to call the consumers again is harmless then,
+        // the same as for a spurious wake.
+        if let Err(()) = wait(&sem) {
             let errno = errno().0;
-            assert_eq!(errno, libc::EINTR, "`sem_wait()` will only fail by `EINTR`");
-        } else {
-            // Succeeded and `!do_mask`.
+            // `ETIMEDOUT` is only possible with a timed wait.
+            assert!(
+                errno == libc::EINTR || errno == libc::ETIMEDOUT,
+                "`sem_wait()` will only fail by `EINTR` (or `ETIMEDOUT`), not {errno}"
+            );
         }
-    }
+    };
+    Ok(broke_with)
 }
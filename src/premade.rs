@@ -1,21 +1,46 @@
 pub use receipts::*;
 mod receipts;
 
+#[cfg(feature = "spawn")]
+pub use spawn::*;
+#[cfg(feature = "spawn")]
+mod spawn;
+
 #[cfg(feature = "channel_notify_facility")]
 pub mod channel_notify_facility;
 
+#[cfg(all(feature = "signalfd", any(target_os = "linux", target_os = "android")))]
+pub mod signalfd;
+#[cfg(all(feature = "signalfd", not(any(target_os = "linux", target_os = "android"))))]
+core::compile_error!("The \"signalfd\" feature is only supported on Linux.");
+#[cfg(all(feature = "sigwait", not(target_os = "macos")))]
+pub mod sigwait;
+#[cfg(all(feature = "sigwait", target_os = "macos"))]
+core::compile_error!("The \"sigwait\" feature isn't supported on macOS.");
+
 #[doc(hidden)]
 // Not for public use.  This must be `pub` so our macros can refer to it when expanded in other
 // crates.
 pub mod __internal;
 
 
-use crate::{help::assert_errno_is_overflow, AtomicUInt, Semaphore, SemaphoreMethods as _,
+use crate::{help::assert_errno_is_overflow, resolve_signum, util::SigSet, AtomicUInt,
+            ConsumeLoopError, InstallHandlerError, Semaphore, SemaphoreMethods as _,
             SignalNumber, SignalReceipt};
 use __internal::Sealed;
-use core::{ops::ControlFlow,
+#[cfg(not(target_os = "macos"))]
+use crate::util::WaitClock;
+#[cfg(feature = "spawn")]
+extern crate std;
+#[cfg(not(target_os = "macos"))]
+use core::time::Duration;
+use core::{ffi::c_int,
+           fmt::{self, Debug, Display, Formatter},
+           marker::PhantomData,
+           ops::ControlFlow,
            pin::Pin,
-           sync::atomic::{AtomicBool, Ordering::Relaxed}};
+           sync::atomic::{AtomicBool, AtomicU8,
+                          Ordering::{Acquire, Relaxed, Release}}};
 
 
 /// Functions for using a `SignalsReceipts` type to manage the signal handling and processing as
@@ -34,28 +59,110 @@ pub trait Premade: Sealed {
     /// looping to process more or else should finish.
     fn continue_flag() -> &'static AtomicBool;
 
+    /// Get the reference to our flag that indicates if [`Self::quiesce`] was requested and how
+    /// far the consuming thread has progressed with that.
+    fn quiesce_flag() -> &'static AtomicU8;
+
+    /// Get the reference to our flag that indicates if the consuming thread should skip calling
+    /// the delegates, as toggled by [`Self::pause`] and [`Self::resume`].
+    fn pause_flag() -> &'static AtomicBool;
+
     /// Get the reference to our semaphore.
     ///
     /// This is async-signal-safe, and so it's safe for this to be called from a signal handler.
     fn semaphore() -> Pin<&'static Semaphore>;
 
-    /// Like [`Self::install_all_handlers_with`] with `mask = true` and `restart = true`.
+    /// Like [`Self::install_all_handlers_with`] with `mask = true`, `restart = true`, and
+    /// `reset_sem = false`.
     ///
     /// # Panics
     /// Same as `Self::install_all_handlers_with`.
     #[inline]
-    fn install_all_handlers() { Self::install_all_handlers_with(true, true); }
+    fn install_all_handlers() { Self::install_all_handlers_with(true, true, false); }
 
     /// Do [`install_handler()`](crate::install_handler) for all of the declared signal numbers.
     ///
-    /// The arguments are passed to each `install_handler()`.
+    /// The arguments are the same as for each `install_handler()`.  (The mask and flags that
+    /// those determine are built only once and reused for all the handlers.)
     ///
     /// [`Self::reset_all_counters()`] and [`Self::reset_continue_flag()`] will also be done, so
     /// that those start fresh if this call is re-installing our handling.
     ///
+    /// If `reset_sem`, [`Self::reset_semaphore()`] will also be done, with a bounded limit, so
+    /// that the consuming loop doesn't iterate pointlessly for a value that was left over from
+    /// before re-installing.  This is best-effort, and it's unnecessary because the consuming
+    /// loop still works when it's not reset.
+    ///
+    /// All of the declared signal numbers are blocked for the calling thread while the handlers
+    /// are installed, and then its mask is restored, so that none of them can be delivered to it
+    /// while only some of the handlers are installed.  Any that were sent meanwhile are delivered
+    /// (and handled) once the mask is restored.  Other threads aren't affected, and so, to fully
+    /// close that window, this should be called before spawning threads that don't block these.
+    ///
     /// # Panics
     /// If installing a handler fails.  Only possible if an invalid signal number was given.
-    fn install_all_handlers_with(mask: bool, restart: bool);
+    #[inline]
+    fn install_all_handlers_with(mask: bool, restart: bool, reset_sem: bool) {
+        #![allow(clippy::expect_used)]
+
+        let r = Self::try_install_all_handlers_with(mask, restart, reset_sem);
+        r.expect("signal number should be valid");
+    }
+
+    /// Like [`Self::install_all_handlers_with`], but if installing a handler fails, the handlers
+    /// that this already installed are uninstalled, via
+    /// [`uninstall_handler()`](crate::uninstall_handler), so that none of ours remain installed,
+    /// and an error is returned instead of panicking.
+    ///
+    /// # Errors
+    /// If installing a handler fails, with which signal number that was for.  Only possible if
+    /// an invalid signal number was given (e.g. an [`rt`](crate::rt) one beyond `SIGRTMAX`).
+    #[allow(clippy::result_large_err)] // `no_std` can't box it, and it's rare.
+    fn try_install_all_handlers_with(
+        mask: bool,
+        restart: bool,
+        reset_sem: bool,
+    ) -> Result<(), PartialInstallError>;
+
+    /// Like [`Self::install_all_handlers`], but the returned guard does
+    /// [`Self::uninstall_all_handlers`] when it's dropped, so that none of our handlers remain
+    /// installed on any exit path of the logic that needed them.  E.g. in tests and short-lived
+    /// tools.
+    ///
+    /// # Panics
+    /// Same as `Self::install_all_handlers`.
+    #[inline]
+    fn install_all_handlers_guarded() -> InstalledHandlers<Self>
+    where
+        Self: Sized,
+    {
+        Self::install_all_handlers();
+        InstalledHandlers { finish: false, _premade: PhantomData }
+    }
+
+    /// Like [`Self::install_all_handlers`], and then [`Self::verify_all_handlers`], to confirm
+    /// that installing actually took effect.  This catches the rare case where something else
+    /// concurrently clobbers a handler during startup.
+    ///
+    /// # Errors
+    /// Same as `Self::verify_all_handlers`.
+    ///
+    /// # Panics
+    /// Same as `Self::install_all_handlers`.
+    #[allow(clippy::result_large_err)] // `no_std` can't box it, and it's rare.
+    #[inline]
+    fn install_all_handlers_verified() -> Result<(), VerifyError> {
+        Self::install_all_handlers();
+        Self::verify_all_handlers()
+    }
+
+    /// Check that our handler is the one that is currently installed, for all of the declared
+    /// signal numbers, via [`is_handler_installed()`](crate::is_handler_installed).
+    ///
+    /// # Errors
+    /// If any isn't, with all such signal numbers.
+    #[allow(clippy::result_large_err)] // `no_std` can't box it, and it's rare.
+    fn verify_all_handlers() -> Result<(), VerifyError>;
 
     /// Do [`uninstall_handler()`](crate::uninstall_handler) for all of the declared signal
     /// numbers.
@@ -65,12 +172,167 @@ pub trait Premade: Sealed {
     /// was given.
     fn uninstall_all_handlers();
 
-    /// Assign zero to each counter, for all of the declared signal numbers.
+    /// Install our handler for only `sig_num`, which must be one of the declared signal numbers,
+    /// after resetting its counter (like [`Self::install_all_handlers`] does for all of them).
+    /// E.g. for a library that only cares about some of its declared signals depending on its
+    /// configuration at run time.  The consuming loop still works when the handlers of the others
+    /// aren't installed, because their counters just stay zero.
+    ///
+    /// Unlike [`Self::install_all_handlers`], this doesn't reset the continue-flag nor any of the
+    /// other state that is for all of the signals.
+    ///
+    /// The `mask` and `restart` arguments are the same as for
+    /// [`Self::install_all_handlers_with`].
+    ///
+    /// # Errors
+    /// If `sig_num` isn't among the declared signal numbers, or if installing the handler failed.
+    fn install_one(sig_num: SignalNumber, mask: bool, restart: bool)
+    -> Result<(), InstallOneError>;
+
+    /// Do [`uninstall_handler()`](crate::uninstall_handler) for only `sig_num`, which must be one
+    /// of the declared signal numbers.
+    ///
+    /// # Errors
+    /// If `sig_num` isn't among the declared signal numbers.
+    ///
+    /// # Panics
+    /// Same as [`Self::uninstall_all_handlers`].
+    #[allow(clippy::result_unit_err)]
+    fn uninstall_one(sig_num: SignalNumber) -> Result<(), ()>;
+
+    /// Assign zero to each counter, and clear each coalescing flag and overflow flag, for all of
+    /// the declared signal numbers.
     fn reset_all_counters();
 
-    /// Assign `true` to our flag that indicates if the consuming thread should continue.
+    /// The set of all of the declared signal numbers.
+    #[must_use]
+    fn declared_signals() -> SigSet;
+
+    /// Count one delivery of `sig_num`, like our handler does, but without waking the consuming
+    /// thread.  For signals that are received other than via our handler, e.g. read from a
+    /// `signalfd`, so that they're processed by the delegates the same.  Returns `false`, and
+    /// does nothing, if `sig_num` isn't among the declared signal numbers.
+    fn count_delivery(sig_num: SignalNumber) -> bool;
+
+    /// Call all the delegates once, for whatever receipts are currently counted, like one
+    /// iteration of [`Self::consume_loop_with`] but without waiting.  For integrating with some
+    /// other event loop that knows when signals were received.
+    ///
+    /// Unlike the consuming loop, this doesn't check the continue-flag, and it returns `Break`
+    /// as soon as a delegate does.
+    fn consume_once(state: Self::Continue) -> ControlFlow<Self::Break, Self::Continue>;
+
+    /// Like [`Self::consume_once`], for calling once per turn of a single-threaded event loop
+    /// (e.g. one based on `poll(2)`) instead of having a dedicated consuming thread, and so this
+    /// never blocks.
+    ///
+    /// Because no thread waits on our semaphore then, the wakeups that our handlers post to it
+    /// would only accumulate, and so this first drains those (via [`Self::reset_semaphore`]),
+    /// before taking the counts so that no deliveries are missed.
+    #[inline]
+    fn poll_once(state: Self::Continue) -> ControlFlow<Self::Break, Self::Continue> {
+        let _drained = Self::reset_semaphore(__internal::RESET_SEM_LIMIT);
+        Self::consume_once(state)
+    }
+
+    /// Take the counts of all the declared signal numbers, in a single pass, and give those
+    /// that were nonzero, paired with their signal numbers, in the order they were declared.
+    /// For a consumer that dispatches them itself, e.g. in priority order.
+    ///
+    /// All the counters are taken (swapped to zero, like
+    /// [`SignalReceipt::take_count()`](crate::SignalReceipt::take_count)) when this is called,
+    /// not as the returned iterator is advanced, so that it's a snapshot (though not an atomic
+    /// one across all the counters).  The delegates aren't called for what this takes.
+    #[must_use]
+    fn drain_all() -> impl Iterator<Item = (SignalNumber, u64)>;
+
+    /// Like [`Self::drain_all`], but the counts are only loaded, not taken, and so this doesn't
+    /// disturb the consuming.  For diagnostics, e.g. a delegate that dumps which signals have
+    /// outstanding counts.
+    #[must_use]
+    fn pending() -> impl Iterator<Item = (SignalNumber, u64)>;
+
+    /// Whether a delivery of `sig_num` was coalesced with a previous one that hadn't been
+    /// consumed yet, since our handling was last installed.  Returns `false` if `sig_num` isn't
+    /// among the declared signal numbers.
+    ///
+    /// This is per-signal, for targeted visibility, e.g. for `SIGCHLD` where each coalescing
+    /// means possibly more than one child to reap.  Standard (non-realtime) signals are also
+    /// coalesced by the OS before delivery, which can't be detected.  This only detects the case
+    /// where the handler ran while the counter was already nonzero.  See
+    /// [`SignalReceipt::coalescing_flag()`](crate::SignalReceipt::coalescing_flag).
+    #[must_use]
+    fn coalescing_observed_for(sig_num: SignalNumber) -> bool;
+
+    /// Whether a delivery of `sig_num` was lost because its counter was saturated, and clear
+    /// that, so that a delegate can report it.  Returns `false` if `sig_num` isn't among the
+    /// declared signal numbers.  See
+    /// [`SignalReceipt::take_overflowed()`](crate::SignalReceipt::take_overflowed).
+    ///
+    /// (The counters of this macro are 64-bit by default, and so this is practically never
+    /// `true` unless a narrower type was declared for `sig_num`.)
+    #[must_use]
+    fn take_overflowed_for(sig_num: SignalNumber) -> bool;
+
+    /// Decrement the value of our semaphore to zero, but at most `limit` times.  Returns how
+    /// many times it was decremented.  See [`drain_semaphore()`](crate::drain_semaphore), which
+    /// describes how this is best-effort.
+    #[must_use]
+    #[inline]
+    fn reset_semaphore(limit: u64) -> u64 { crate::drain_semaphore(Self::semaphore(), limit) }
+
+    /// The current value of our semaphore, i.e. how many times the consuming thread is yet to be
+    /// woken, as a best-effort gauge of the backlog.  (The counters are the accurate accounting
+    /// of the deliveries, and one wake can process many.)
+    ///
+    /// `None` if the semaphore isn't initialized yet, or on macOS, where `sem_getvalue()` isn't
+    /// supported.
+    #[must_use]
+    #[inline]
+    fn pending_wakeups() -> Option<c_int> {
+        cfg_if::cfg_if! { if #[cfg(not(target_os = "macos"))] {
+            Self::semaphore().sem_ref().ok().map(|sem| sem.get_value())
+        } else {
+            None
+        } }
+    }
+
+    /// Assign `true` to our flag that indicates if the consuming thread should continue, and
+    /// clear any previous request to [`Self::quiesce`] or to [`Self::pause`].
+    #[inline]
+    fn reset_continue_flag() {
+        Self::continue_flag().store(true, Relaxed);
+        Self::quiesce_flag().store(__internal::QUIESCE_NONE, Relaxed);
+        Self::pause_flag().store(false, Relaxed);
+    }
+
+    /// Suspend the processing of receipts, without uninstalling the handlers.  The consuming
+    /// thread still wakes as usual, but it doesn't call the delegates, until [`Self::resume`].
+    /// E.g. during a maintenance window.
+    ///
+    /// No receipts are lost: the counters continue to be incremented while paused, and, upon
+    /// resuming, each delegate is given the accumulated count in one [`Receipt`](crate::Receipt).
     #[inline]
-    fn reset_continue_flag() { Self::continue_flag().store(true, Relaxed); }
+    fn pause() { Self::pause_flag().store(true, Relaxed); }
+
+    /// Resume the processing of receipts after [`Self::pause`], and wake the consuming thread
+    /// to process what accumulated meanwhile.
+    #[inline]
+    fn resume() {
+        Self::pause_flag().store(false, Relaxed);
+        wake::<Self>();
+    }
+
+    /// Wake the consuming thread once, without a signal, so that it calls all the delegates an
+    /// extra time.  E.g. after changing, via interior mutability, some state that the delegates
+    /// check (like a config-reload flag), to have that processed promptly.
+    ///
+    /// This is safe because the loop already tolerates spurious wakes: the delegates of signals
+    /// that weren't received are given a [`Receipt`](crate::Receipt) only when their counts are
+    /// positive, and the `{callback}` is called each iteration regardless.  This is like
+    /// [`Self::finish`] without changing the continue-flag.
+    #[inline]
+    fn wake() { wake::<Self>(); }
 
     /// Intended to be used as (or within) the start function of a dedicated thread.
     ///
@@ -85,6 +347,27 @@ pub trait Premade: Sealed {
         Self::consume_loop_with(true, Default::default(), Default::default())
     }
 
+    /// Do [`Self::install_all_handlers`] and then spawn a thread that does
+    /// [`Self::consume_loop`], in that order so that no deliveries are missed.  The returned
+    /// handle can join the thread, or do [`Self::finish`] and then join it.
+    ///
+    /// # Errors
+    /// If spawning the thread fails, in which case the handlers are uninstalled again, so that
+    /// deliveries aren't counted without anything to consume them.
+    ///
+    /// # Panics
+    /// Same as [`Self::install_all_handlers`].
+    #[cfg(feature = "spawn")]
+    #[inline]
+    fn install_and_spawn() -> std::io::Result<ConsumeHandle<Self>>
+    where
+        Self: Sized + 'static,
+        Self::Continue: Default,
+        Self::Break: Default + Send + 'static,
+    {
+        ConsumeHandle::spawn()
+    }
+
     /// Intended to be used as (or within) the start function of a dedicated thread.
     ///
     /// The current signal mask will be changed to ensure that no signals are masked (i.e. that
@@ -104,12 +387,110 @@ pub trait Premade: Sealed {
     /// Enables more control over the parameters, which are passed to [`crate::consume_loop`].  Is
     /// necessary when `Self`'s associated types don't both `impl`ement `Default`.
     #[must_use]
+    #[inline]
     fn consume_loop_with(
         do_mask: bool,
         state: Self::Continue,
         finish: Self::Break,
+    ) -> Self::Break {
+        Self::consume_loop_with_start(do_mask, state, |_| {}, finish)
+    }
+
+    /// Like [`Self::consume_loop_with`], but `on_start` is called once, with the state, when the
+    /// loop is live, i.e. after the semaphore is initialized and before the first wait.  E.g. to
+    /// do one-time setup, or to signal readiness to a parent process.  See
+    /// [`crate::consume_loop_with_start`].
+    ///
+    /// # Panics
+    /// If initializing the semaphore fails.  See [`Self::try_consume_loop_with_start`].
+    #[must_use]
+    #[inline]
+    fn consume_loop_with_start(
+        do_mask: bool,
+        state: Self::Continue,
+        on_start: impl FnOnce(&mut Self::Continue),
+        finish: Self::Break,
+    ) -> Self::Break {
+        #![allow(clippy::expect_used)]
+
+        let r = Self::try_consume_loop_with_start(do_mask, state, on_start, finish);
+        r.expect("semaphore initialization must succeed")
+    }
+
+    /// Like [`Self::consume_loop_with`], but returns an error instead of panicking if
+    /// initializing the semaphore fails, so that the caller can report a clean startup error.
+    /// See [`crate::consume_loop_fallible`].
+    ///
+    /// # Errors
+    /// If initializing the semaphore fails.
+    #[inline]
+    fn try_consume_loop_with(
+        do_mask: bool,
+        state: Self::Continue,
+        finish: Self::Break,
+    ) -> Result<Self::Break, ConsumeLoopError> {
+        Self::try_consume_loop_with_start(do_mask, state, |_| {}, finish)
+    }
+
+    /// Like [`Self::try_consume_loop_with`], but with the `on_start` of
+    /// [`Self::consume_loop_with_start`].
+    ///
+    /// # Errors
+    /// If initializing the semaphore fails.
+    fn try_consume_loop_with_start(
+        do_mask: bool,
+        state: Self::Continue,
+        on_start: impl FnOnce(&mut Self::Continue),
+        finish: Self::Break,
+    ) -> Result<Self::Break, ConsumeLoopError>;
+
+    /// Like [`Self::consume_loop_with`], but each wait stops after `timeout`, as measured by
+    /// `clock`, and then all the delegates are called again, which enables doing periodic work in
+    /// them even when no signals are received.  The parameters are passed to
+    /// [`crate::consume_loop_timed`].
+    ///
+    /// Not available on macOS, which doesn't have `sem_timedwait()`.
+    #[cfg(not(target_os = "macos"))]
+    #[must_use]
+    fn consume_loop_with_timeout(
+        do_mask: bool,
+        timeout: Duration,
+        clock: WaitClock,
+        state: Self::Continue,
+        finish: Self::Break,
     ) -> Self::Break;
 
+    /// Restart consuming, after a previous [`Self::consume_loop_with`] (or the like) finished
+    /// with the `prev` value, with a new state that the given `restart` function derives from
+    /// `prev`.  E.g. for a supervisor that inspects why the processing finished and decides to
+    /// resume it.
+    ///
+    /// If `reinstall`, [`Self::install_all_handlers`] will be done first, which is needed if
+    /// [`Self::finish`] was done, and which resets the counters.  Otherwise, only
+    /// [`Self::reset_continue_flag`] will be done, which preserves the counters so that any
+    /// receipts that were counted while not consuming will still be processed.
+    ///
+    /// The other arguments are passed to `Self::consume_loop_with`.
+    ///
+    /// # Panics
+    /// If `reinstall`, same as `Self::install_all_handlers`.
+    #[must_use]
+    #[inline]
+    fn restart_consume_loop_with(
+        do_mask: bool,
+        reinstall: bool,
+        prev: Self::Break,
+        restart: impl FnOnce(Self::Break) -> Self::Continue,
+        finish: Self::Break,
+    ) -> Self::Break {
+        if reinstall {
+            Self::install_all_handlers();
+        } else {
+            Self::reset_continue_flag();
+        }
+        Self::consume_loop_with(do_mask, restart(prev), finish)
+    }
+
     /// Finish all processing, by uninstalling all handlers and indicating to the consuming thread
     /// that it should finish.
     ///
@@ -120,25 +501,112 @@ pub trait Premade: Sealed {
         // Reset the dispositions and stop counting signal deliveries.
         Self::uninstall_all_handlers();
 
-        // Tell the consuming thread to finish.
+        // Tell the consuming thread to finish, and don't leave it paused for next time.
         Self::continue_flag().store(false, Relaxed);
+        Self::pause_flag().store(false, Relaxed);
 
         // Ensure the thread wakes to see the false continue-flag now.
-        if let Some(sem) = Self::semaphore().try_init(10_000) {
-            // Our change to the flag will be visible, as happens-before, to the thread that
-            // wakes.
-            let r = sem.post();
-            if r.is_err() {
-                #[allow(clippy::unreachable)]
-                assert_errno_is_overflow(|| {
-                    unreachable!(); // Impossible - `sem_safe` ensures the semaphores are valid.
-                });
-            }
-        } else {
-            // Our semaphore wasn't already initialized and couldn't be quickly.  This is very
-            // unlikely, and there's nothing we can do, but at least we did change the
-            // continue-flag.
+        wake::<Self>();
+    }
+
+    /// Like [`Self::finish`], but, after stopping, this does one final pass of calling all the
+    /// delegates, on the calling thread with the given `state`, so that the receipts that were
+    /// already counted are processed instead of lost.  E.g. so that the last few `SIGCHLD`s are
+    /// reaped before exiting.
+    ///
+    /// The ordering is:
+    /// 1. All handlers are uninstalled, and so no more deliveries are counted.
+    /// 2. The consuming thread (if any) is told to finish, like `Self::finish`, and then
+    ///    `join_consumer` is called, which must wait for that thread to have returned (e.g. by
+    ///    joining it), or do nothing if there isn't one.
+    /// 3. Like [`Self::consume_once`], the delegates take and process every count that the
+    ///    handlers incremented before step 1 and that the consuming thread didn't already.
+    ///
+    /// Because of step 2, the delegates are never called by both threads at the same time, and
+    /// so the single-consumer assumption still holds.  If a delegate returns `Break` in step 3,
+    /// that is returned, which leaves the counts of the remaining delegates unprocessed.  When
+    /// the consuming thread must be the one to process them, with its state, use
+    /// [`Self::quiesce`] instead.
+    ///
+    /// # Panics
+    /// Same as [`Self::uninstall_all_handlers`], or if `join_consumer` does.
+    #[inline]
+    fn drain_and_finish(
+        state: Self::Continue,
+        join_consumer: impl FnOnce(),
+    ) -> ControlFlow<Self::Break, Self::Continue> {
+        Self::finish();
+        join_consumer();
+        Self::consume_once(state)
+    }
+
+    /// Like [`Self::finish`], but without uninstalling the handlers, and so deliveries continue
+    /// to be counted.  E.g. to replace the consuming thread with a fresh one (after
+    /// [`Self::reset_continue_flag`]) that will process the backlog that accumulated meanwhile.
+    #[inline]
+    fn stop_consuming() {
+        Self::continue_flag().store(false, Relaxed);
+        Self::pause_flag().store(false, Relaxed);
+        wake::<Self>();
+    }
+
+    /// Whether the consuming thread should continue, i.e. whether neither [`Self::finish`] nor
+    /// [`Self::stop_consuming`] (et al) was done since our continue-flag was last reset.
+    #[must_use]
+    #[inline]
+    fn is_consuming() -> bool { Self::continue_flag().load(Relaxed) }
+
+    /// Like [`Self::finish`], but the consuming thread first processes, through the delegates,
+    /// everything that was already counted, before it finishes.  E.g. for rolling restarts, to
+    /// stop handling new signals while not leaving any receipts unprocessed.
+    ///
+    /// The ordering is:
+    /// 1. All handlers are uninstalled, and so no more deliveries are counted.
+    /// 2. The consuming thread is woken and, after it completes its current iteration (if any),
+    ///    it does one more complete iteration of calling all the delegates, which takes every
+    ///    count that the handlers incremented before step 1.
+    /// 3. Only then, the consuming thread assigns `false` to the continue-flag and so its loop
+    ///    returns.
+    ///
+    /// Unlike `Self::finish`, which can cause the loop to return before calling the remaining
+    /// delegates of an iteration, this guarantees that no counted-but-unprocessed receipts remain
+    /// once the loop has returned.  This doesn't wait for that, and the caller may join the
+    /// consuming thread for that.
+    ///
+    /// This also does [`Self::resume`], if paused, because the final iteration must call the
+    /// delegates.
+    ///
+    /// # Panics
+    /// Same as [`Self::uninstall_all_handlers`].
+    #[inline]
+    fn quiesce() {
+        // Stop counting signal deliveries, so that what remains to be processed is bounded.
+        Self::uninstall_all_handlers();
+
+        // Request the final complete iteration.  The consuming thread will see this after the
+        // above, as happens-before.
+        Self::quiesce_flag().store(__internal::QUIESCE_REQUESTED, Release);
+        Self::pause_flag().store(false, Relaxed);
+
+        wake::<Self>();
+    }
+}
+
+
+/// Wake the consuming thread of `P`, to see a change to the continue-flag or the quiesce-flag.
+fn wake<P: Premade + ?Sized>() {
+    if let Some(sem) = P::semaphore().try_init(10_000) {
+        // Our change to the flag will be visible, as happens-before, to the thread that wakes.
+        let r = sem.post();
+        if r.is_err() {
+            #[allow(clippy::unreachable)]
+            assert_errno_is_overflow(|| {
+                unreachable!(); // Impossible - `sem_safe` ensures the semaphores are valid.
+            });
         }
+    } else {
+        // Our semaphore wasn't already initialized and couldn't be quickly.  This is very
+        // unlikely, and there's nothing we can do, but at least we did change the flag.
     }
 }
 
@@ -152,20 +620,136 @@ pub trait Premade: Sealed {
 ///
 /// The name of the module defaults to `signals_receipts_premade` when not given.
 ///
+/// The module also defines an enum named `Signal`, with a variant for each declared signal
+/// number, named the same, that converts to and from `SignalNumber`.
+///
 /// The `Continue` and `Break` types default to `()` when not given.
+///
+/// The optional `{wake_every} => N;` sets [`SignalReceipt::WAKE_EVERY`] for all of the declared
+/// signal numbers.  Similarly, the optional `{clock} => Boottime;` sets [`SignalReceipt::CLOCK`]
+/// to the named [`ClockSource`](crate::util::ClockSource) variant.
+///
+/// The optional `{callback} => |state| ...;` is called first in each iteration of the consuming
+/// loop, before all the delegates, and the optional `{epilogue} => |state| ...;` is called last,
+/// after all the delegates, e.g. to begin and commit a transaction once per batch.  Both are
+/// given and return the state like a [`Consumer`](crate::Consumer), and so either can break the
+/// loop.
+///
+/// The counter of each declared signal number is an `AtomicU64` by default, which can be
+/// overridden per signal by declaring it like `SIGUSR1: AtomicU8 => ...;`, e.g. to save memory
+/// when only a few deliveries are expected between consumings.  The type can be any that
+/// implements [`AtomicUInt`](crate::AtomicUInt) and has a `const fn new(0)`, and the
+/// `cur_count` of the [`Receipt`]s given to that signal's delegate is of the corresponding
+/// integer type.  The `core::sync::atomic` types can be given by their bare names, but others
+/// must be given by a path that resolves from within the expanded module, e.g. `crate::MyAtomic`.
+///
+/// A real-time signal is declared like `SIGRT_JOB = rt(2) => ...;` (or `SIGRT_JOB = rt(2):
+/// AtomicU8 => ...;`), for `SIGRTMIN + 2`, where the name is any of your choosing, because each
+/// declared signal needs a name.  The `sig_num` of the [`Receipt`]s given to that signal's
+/// delegate is the real number.  See [`rt`](crate::rt).
+///
+/// A signal declared like `SIGUSR1 {always} => ...;` (or `SIGUSR1: AtomicU8 {always} => ...;`)
+/// has its delegate called every iteration of the consuming loop, even when its count is zero,
+/// via [`consume_count_then_delegate_always`] instead of [`consume_count_then_delegate`].
+///
+/// Similarly, a signal declared like `SIGUSR1 {retaining} => ...;` has its count only cleared
+/// after its delegate returns, via [`consume_count_then_delegate_retaining`], so that a panicking
+/// delegate leaves the count intact.
+///
+/// A real-time signal declared like `SIGRT_MSG = rt(1) {values} => ...;` has each value that was
+/// sent with it via `sigqueue()` queued, by installing [`handler_with_value`](
+/// crate::handler_with_value) for it, and its delegate is given an [`RtReceipt`](
+/// crate::RtReceipt) per value, in the order delivered, via [`consume_values_then_delegate`](
+/// crate::consume_values_then_delegate), instead of a [`Receipt`] of the coalesced count.  The
+/// generated `SignalsReceipts` also implements [`SignalReceiptWithValue`](
+/// crate::SignalReceiptWithValue) for it, e.g. for [`SigValueQueue::take_dropped`](
+/// crate::SigValueQueue::take_dropped).  Only on Linux and Android, like `rt`.  (Deliveries that
+/// are counted other than via the handler, e.g. via [`Premade::count_delivery`], have no value.)
+///
+/// A group of signals can share the same delegate, by declaring them like `[SIGINT, SIGTERM,
+/// SIGQUIT] => ...;`, which is the same as declaring each with a copy of the delegate expression
+/// (which is why that must be a path or a non-capturing closure, like any delegate).  Each still
+/// has its own counter, `SignalReceipt` `impl`, and consumer, and the `sig_num` of the
+/// [`Receipt`]s differs per signal, so the delegate can branch on that.
+///
+/// Declaring a signal that can't be caught (`SIGKILL`, `SIGSTOP`), or that must not be blocked
+/// because a "computational exception" generates it (`SIGFPE`, `SIGILL`, `SIGSEGV`, `SIGBUS`), is
+/// a compile error, instead of a failure to install at run time.
 #[macro_export]
 macro_rules! premade {
+    { @wake_every } => { 1 };
+    { @wake_every $wake_every:expr } => { $wake_every };
+    { @clock } => { $crate::util::ClockSource::Monotonic };
+    { @clock $clock:ident } => { $crate::util::ClockSource::$clock };
+    { @atomic_uint } => { core::sync::atomic::AtomicU64 };
+    { @atomic_uint $uint:ty } => { $uint };
+    { @signum $signum:ident } => { $crate::__internal::signals_names::$signum };
+    { @signum $signum:ident $rt:expr } => { $crate::rt($rt) };
+    { @consume [$( $generic:tt )*] ($( $arg:expr ),*) } => {
+        $crate::consume_count_then_delegate::<$( $generic )*>($( $arg ),*)
+    };
+    { @consume always [$( $generic:tt )*] ($( $arg:expr ),*) } => {
+        $crate::consume_count_then_delegate_always::<$( $generic )*>($( $arg ),*)
+    };
+    { @consume retaining [$( $generic:tt )*] ($( $arg:expr ),*) } => {
+        $crate::consume_count_then_delegate_retaining::<$( $generic )*>($( $arg ),*)
+    };
+    { @consume values [$( $generic:tt )*] ($( $arg:expr ),*) } => {
+        $crate::consume_values_then_delegate::<$( $generic )*>($( $arg ),*)
+    };
+    { @try_install (values) $template:ident $signum:ident } => {
+        $template.try_install_with_value::<{$signum}, SignalsReceipts>()
+    };
+    { @try_install ($( $mode:ident )?) $template:ident $signum:ident } => {
+        $template.try_install::<{$signum}, SignalsReceipts>()
+    };
+    { @is_installed (values) $signum:ident } => {
+        $crate::is_handler_with_value_installed::<{$signum}, SignalsReceipts>()
+    };
+    { @is_installed ($( $mode:ident )?) $signum:ident } => {
+        $crate::is_handler_installed::<{$signum}, SignalsReceipts>()
+    };
+    { @value_queue (values) $signum:ident } => {
+        impl $crate::SignalReceiptWithValue<{$signum}> for SignalsReceipts {
+            fn value_queue() -> &'static $crate::SigValueQueue {
+                static QUEUE: $crate::SigValueQueue = $crate::SigValueQueue::new();
+                &QUEUE
+            }
+        }
+    };
+    { @value_queue ($( $mode:ident )?) $signum:ident } => {};
+    { @delegate (values) $signum:ident } => {
+        fn(&mut $crate::RtReceipt<
+                  <super::super::SignalsReceipts as $crate::Premade>::Break,
+                  <super::super::SignalsReceipts as $crate::Premade>::Continue>)
+    };
+    { @delegate ($( $mode:ident )?) $signum:ident } => {
+        fn(&mut $crate::Receipt<
+                  <<super::super::SignalsReceipts
+                    as $crate::SignalReceipt<{super::super::$signum}>>
+                      ::AtomicUInt as $crate::AtomicUInt>::UInt,
+                  <super::super::SignalsReceipts as $crate::Premade>::Break,
+                  <super::super::SignalsReceipts as $crate::Premade>::Continue>)
+    };
+
     {
         $( ( $( $item:item )* ) )?
+        $( {wake_every} => $wake_every:expr; )?
+        $( {clock} => $clock:ident; )?
         $( {callback} => $callback:expr; )?
-        $( $signum:ident => $delegate:expr; )+
+        $( {epilogue} => $epilogue:expr; )?
+        $( $signum:ident $( = rt($rt:expr) )? $( : $uint:ty )? $( {$always:ident} )?
+           => $delegate:expr; )+
     } => {
         $crate::premade! {
             $( ( $( $item )* ) )?
             type Continue = ();
             type Break = ();
+            $( {wake_every} => $wake_every; )?
+            $( {clock} => $clock; )?
             $( {callback} => $callback; )?
-            $( $signum => $delegate; )+
+            $( {epilogue} => $epilogue; )?
+            $( $signum $( = rt($rt) )? $( : $uint )? $( {$always} )? => $delegate; )+
         }
     };
 
@@ -173,16 +757,23 @@ macro_rules! premade {
         $( ( $( $item:item )* ) )?
         type Continue = $cont:ty;
         type Break = $break:ty;
+        $( {wake_every} => $wake_every:expr; )?
+        $( {clock} => $clock:ident; )?
         $( {callback} => $callback:expr; )?
-        $( $signum:ident => $delegate:expr; )+
+        $( {epilogue} => $epilogue:expr; )?
+        $( $signum:ident $( = rt($rt:expr) )? $( : $uint:ty )? $( {$always:ident} )?
+           => $delegate:expr; )+
     } => {
         $crate::premade! {
             mod signals_receipts_premade {
                 $( ( $( $item )* ) )?
                 type Continue = $cont;
                 type Break = $break;
+                $( {wake_every} => $wake_every; )?
+                $( {clock} => $clock; )?
                 $( {callback} => $callback; )?
-                $( $signum => $delegate; )+
+                $( {epilogue} => $epilogue; )?
+                $( $signum $( = rt($rt) )? $( : $uint )? $( {$always} )? => $delegate; )+
             }
         }
     };
@@ -190,8 +781,12 @@ macro_rules! premade {
     {
         $visib:vis mod $name:ident {
             $( ( $( $item:item )* ) )?
+            $( {wake_every} => $wake_every:expr; )?
+            $( {clock} => $clock:ident; )?
             $( {callback} => $callback:expr; )?
-            $( $signum:ident => $delegate:expr; )+
+            $( {epilogue} => $epilogue:expr; )?
+            $( $signum:ident $( = rt($rt:expr) )? $( : $uint:ty )? $( {$always:ident} )?
+               => $delegate:expr; )+
         }
     } => {
         $crate::premade! {
@@ -199,8 +794,11 @@ macro_rules! premade {
                 $( ( $( $item )* ) )?
                 type Continue = ();
                 type Break = ();
+                $( {wake_every} => $wake_every; )?
+                $( {clock} => $clock; )?
                 $( {callback} => $callback; )?
-                $( $signum => $delegate; )+
+                $( {epilogue} => $epilogue; )?
+                $( $signum $( = rt($rt) )? $( : $uint )? $( {$always} )? => $delegate; )+
             }
         }
     };
@@ -210,16 +808,32 @@ macro_rules! premade {
             $( ( $( $item:item )* ) )?
             type Continue = $cont:ty;
             type Break = $break:ty;
+            $( {wake_every} => $wake_every:expr; )?
+            $( {clock} => $clock:ident; )?
             $( {callback} => $callback:expr; )?
-            $( $signum:ident => $delegate:expr; )+
+            $( {epilogue} => $epilogue:expr; )?
+            $( $signum:ident $( = rt($rt:expr) )? $( : $uint:ty )? $( {$always:ident} )?
+               => $delegate:expr; )+
         }
     } => {
         $visib mod $name {
             use $crate::{consume_count_then_delegate, install_handler, uninstall_handler,
-                         reset_counter, __internal::{signals_names, Sealed},
-                         Consumer, Premade, SignalReceipt,
+                         reset_counter, resolve_signum,
+                         util::{mask_signals_of_current_thread_saving, SigSet},
+                         InstallOneError, PartialInstallError, VerifyError,
+                         __internal::{count_delivery, dirty_bit_position, dirty_words,
+                                      Sealed, HandlerTemplate, is_undeclarable, peek_count_pair,
+                                      quiesce_check, take_count_pair, QUIESCE_NONE,
+                                      RESET_SEM_LIMIT},
+                         Consumer, Premade, SignalNumber, SignalReceipt,
                          Semaphore, SemaphoreMethods as _, SemaphoreRef};
-            use core::{pin::Pin, sync::atomic::{AtomicBool, AtomicU64}};
+            use core::{ops::ControlFlow,
+                       pin::Pin,
+                       sync::atomic::{AtomicBool, AtomicU8, AtomicU64}};
+            // So that the types which implement `AtomicUInt` can be given by name as the
+            // `$uint` of a declared signal.
+            #[allow(unused_imports)]
+            use core::sync::atomic::{AtomicU16, AtomicU32, AtomicUsize};
 
             /// The type that [`SignalReceipt`] and [`Premade`] are `impl`emented for.
             ///
@@ -230,22 +844,154 @@ macro_rules! premade {
             pub(crate) struct SignalsReceipts;
 
             $(
-                impl SignalReceipt<{signals_names::$signum}> for SignalsReceipts {
-                    type AtomicUInt = AtomicU64;
+                /// The signal number as declared, which is a placeholder if it's real-time.
+                const $signum: SignalNumber = $crate::premade!(@signum $signum $( $rt )?);
+            )+
+
+            // Reject, at compile time, declared signals that can't be handled.
+            $(
+                const _: () = assert!(
+                    !is_undeclarable($signum),
+                    concat!("`", stringify!($signum), "` can't be caught or must not be blocked")
+                );
+            )+
+
+            /// The declared signals, as a type, e.g. for keying a `Continue` state on, or for
+            /// the `N` of a channel of the notifications of `channel_notify_facility`.  Declaring
+            /// the same signal more than once is a compile error.
+            #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+            #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+            pub(crate) enum Signal {
+                $(
+                    #[doc = concat!("`", stringify!($signum), "`")]
+                    $signum
+                ),+
+            }
+
+            impl Signal {
+                /// The real signal number.  (This isn't `const`, because real-time signal
+                /// numbers are only known at run time.)
+                #[must_use]
+                pub(crate) fn signum(self) -> SignalNumber {
+                    match self { $( Self::$signum => resolve_signum($signum) ),+ }
+                }
+            }
+
+            impl From<Signal> for SignalNumber {
+                fn from(signal: Signal) -> Self { signal.signum() }
+            }
+
+            impl TryFrom<SignalNumber> for Signal {
+                /// The given signal number, which isn't one of the declared.
+                type Error = SignalNumber;
+
+                fn try_from(sig_num: SignalNumber) -> Result<Self, Self::Error> {
+                    $(
+                        if sig_num == resolve_signum($signum) {
+                            return Ok(Self::$signum);
+                        }
+                    )+
+                    Err(sig_num)
+                }
+            }
+
+            /// As declared, or else the default.
+            const DECLARED_WAKE_EVERY: u8 = $crate::premade!(@wake_every $( $wake_every )?);
+            /// As declared, or else the default.
+            const DECLARED_CLOCK: $crate::util::ClockSource =
+                $crate::premade!(@clock $( $clock )?);
+
+            /// The declared signal numbers, in order, which determines their dirty bits.
+            const SIGNUMS: &[SignalNumber] = &[ $( $signum ),+ ];
+
+            /// The words of the dirty bitmask, enough for all of the declared signal numbers.
+            static DIRTY: [AtomicU64; dirty_words(SIGNUMS.len())] = {
+                #[allow(clippy::declare_interior_mutable_const)]
+                const ZERO: AtomicU64 = AtomicU64::new(0);
+                [ZERO; dirty_words(SIGNUMS.len())]
+            };
+
+            $(
+                impl SignalReceipt<{$signum}> for SignalsReceipts {
+                    type AtomicUInt = $crate::premade!(@atomic_uint $( $uint )?);
+
+                    const WAKE_EVERY: u8 = DECLARED_WAKE_EVERY;
+
+                    const CLOCK: $crate::util::ClockSource = DECLARED_CLOCK;
 
                     fn counter() -> &'static Self::AtomicUInt {
-                        static COUNTER: AtomicU64 = AtomicU64::new(0);
+                        static COUNTER: $crate::premade!(@atomic_uint $( $uint )?) =
+                            <$crate::premade!(@atomic_uint $( $uint )?)>::new(0);
                         &COUNTER
                     }
 
                     fn semaphore() -> Option<SemaphoreRef<'static>> {
                         <Self as Premade>::semaphore().sem_ref().ok()
                     }
+
+                    fn dirty_bit() -> Option<(&'static AtomicU64, u32)> {
+                        const POSITION: (usize, u32) =
+                            dirty_bit_position(SIGNUMS, $signum);
+                        DIRTY.get(POSITION.0).map(|word| (word, POSITION.1))
+                    }
+
+                    fn coalescing_flag() -> Option<&'static AtomicBool> {
+                        static COALESCED: AtomicBool = AtomicBool::new(false);
+                        Some(&COALESCED)
+                    }
+
+                    fn overflow_flag() -> Option<&'static AtomicBool> {
+                        static OVERFLOWED: AtomicBool = AtomicBool::new(false);
+                        Some(&OVERFLOWED)
+                    }
+
+                    fn timestamp_slot() -> Option<&'static AtomicU64> {
+                        static RECEIVED_AT: AtomicU64 = AtomicU64::new(0);
+                        Some(&RECEIVED_AT)
+                    }
+
+                    fn generation_slot() -> Option<&'static AtomicU64> {
+                        static GENERATION: AtomicU64 = AtomicU64::new(0);
+                        Some(&GENERATION)
+                    }
                 }
             )+
 
+            $( $crate::premade!(@value_queue ($( $always )?) $signum); )+
+
             impl Sealed for SignalsReceipts {}
 
+            /// Enough for at least a second.
+            const TRY_INIT_LIMIT: u64 = 200_000_000;
+
+            // This just enables our `$( ... $callback ...)?` (and `$epilogue`) to work where
+            // `$callback` actually isn't used in that.
+            #[allow(unused_macros)]
+            macro_rules! repeat_for { ($metavar:tt: $second:expr) => { $second } }
+
+            /// The array of all the consumers, for the consuming loops, which must be expanded
+            /// within the `impl Premade for SignalsReceipts`.
+            macro_rules! consumers { () => { [ $(
+                &mut repeat_for!($callback: delegates::callback::__FUNC)
+                    as &mut Consumer<Self::Break, Self::Continue>,
+            )? $(
+                &mut (|state| $crate::premade!(@consume $( $always )?
+                      [{$signum}, Self, _, Self::Break, Self::Continue]
+                      (state, delegates::$signum::__FUNC)))
+                    as &mut Consumer<Self::Break, Self::Continue>
+            ),+ , $(
+                &mut repeat_for!($epilogue: delegates::epilogue::__FUNC)
+                    as &mut Consumer<Self::Break, Self::Continue>,
+            )?
+                // Last, after all the delegates, so that `Self::quiesce` can know when a complete
+                // iteration was done.
+                &mut (|state| {
+                    quiesce_check::<Self>();
+                    ControlFlow::Continue(state)
+                })
+                    as &mut Consumer<Self::Break, Self::Continue>
+            ] }; }
+
             impl Premade for SignalsReceipts {
                 type Continue = $cont;
                 type Break = $break;
@@ -255,7 +1001,12 @@ macro_rules! premade {
                     Pin::static_ref(&SEMAPHORE)
                 }
 
-                fn install_all_handlers_with(mask: bool, restart: bool) {
+                fn try_install_all_handlers_with(
+                    mask: bool,
+                    restart: bool,
+                    reset_sem: bool
+                ) -> Result<(), PartialInstallError>
+                {
                     // Make the counters start fresh if our handling is being re-installed.  Must
                     // be done before installing the handlers next.
                     Self::reset_all_counters();
@@ -265,45 +1016,158 @@ macro_rules! premade {
                     // installing the handlers next.
                     Self::reset_continue_flag();
 
-                    // We don't reset our semaphore here, in case its value is high, because that
-                    // would require looping (to decrement its value via `.try_wait()`) an
-                    // unbounded amount of times which could cause a significant delay.  Resetting
-                    // the semaphore is unnecessary because `$crate::consume_loop` still works
-                    // when it's not reset.
+                    // We only reset our semaphore here when requested, because, in case its
+                    // value is high, that requires looping (to decrement its value via
+                    // `.try_wait()`) many times which could cause a delay, and so that's bounded.
+                    // Resetting the semaphore is unnecessary because `$crate::consume_loop` still
+                    // works when it's not reset.
+                    if reset_sem {
+                        let _drained = Self::reset_semaphore(RESET_SEM_LIMIT);
+                    }
+
+                    // Block all of ours for this thread while installing, so that none of them
+                    // can be delivered to it while only some of our handlers are installed
+                    // (which would do the default action, e.g. terminating, for those not yet).
+                    // Any that become pending meanwhile are delivered when this guard restores
+                    // the mask.
+                    let _blocked =
+                        mask_signals_of_current_thread_saving(&Self::declared_signals());
 
-                    $( install_handler::<{signals_names::$signum}, Self>(mask, restart); )+
+                    // Build the mask and flags only once, for all the handlers.
+                    let template = HandlerTemplate::new(mask, restart);
+                    let mut installed = SigSet::empty();
+                    let roll_back = |these: &SigSet| {
+                        $(
+                            if these.contains(resolve_signum($signum)) {
+                                uninstall_handler::<{$signum}>();
+                            }
+                        )+
+                    };
+                    $(
+                        if let Err(failed) =
+                            $crate::premade!(@try_install ($( $always )?) template $signum)
+                        {
+                            roll_back(&installed);
+                            return Err(PartialInstallError { failed, rolled_back: installed });
+                        }
+                        installed.insert(resolve_signum($signum)).ok();
+                    )+
+                    Ok(())
+                }
+
+                fn verify_all_handlers() -> Result<(), VerifyError> {
+                    let mut unverified = SigSet::empty();
+                    $(
+                        if !$crate::premade!(@is_installed ($( $always )?) $signum) {
+                            unverified.insert(resolve_signum($signum)).ok();
+                        }
+                    )+
+                    if unverified == SigSet::empty() {
+                        Ok(())
+                    } else {
+                        Err(VerifyError { unverified })
+                    }
                 }
 
                 fn uninstall_all_handlers() {
-                    $( uninstall_handler::<{signals_names::$signum}>(); )+
+                    $( uninstall_handler::<{$signum}>(); )+
+                }
+
+                fn install_one(sig_num: SignalNumber, mask: bool, restart: bool)
+                -> Result<(), InstallOneError>
+                {
+                    let template = HandlerTemplate::new(mask, restart);
+                    $(
+                        if sig_num == resolve_signum($signum) {
+                            reset_counter::<{$signum}, Self>();
+                            return $crate::premade!(@try_install ($( $always )?) template $signum)
+                                       .map_err(InstallOneError::Install);
+                        }
+                    )+
+                    Err(InstallOneError::Undeclared(sig_num))
+                }
+
+                fn uninstall_one(sig_num: SignalNumber) -> Result<(), ()> {
+                    $(
+                        if sig_num == resolve_signum($signum) {
+                            uninstall_handler::<{$signum}>();
+                            return Ok(());
+                        }
+                    )+
+                    Err(())
                 }
 
                 fn reset_all_counters() {
-                    $( reset_counter::<{signals_names::$signum}, Self>(); )+
+                    $( reset_counter::<{$signum}, Self>(); )+
+                }
+
+                fn declared_signals() -> SigSet {
+                    let mut set = SigSet::empty();
+                    $( set.insert(resolve_signum($signum)).ok(); )+
+                    set
+                }
+
+                fn count_delivery(sig_num: SignalNumber) -> bool {
+                    $(
+                        if sig_num == resolve_signum($signum) {
+                            count_delivery::<{$signum}, Self>();
+                            return true;
+                        }
+                    )+
+                    false
                 }
 
-                fn consume_loop_with(
+                fn consume_once(
+                    mut state: Self::Continue
+                ) -> ControlFlow<Self::Break, Self::Continue>
+                {
+                    for consume in consumers!() {
+                        state = consume(state)?;
+                    }
+                    ControlFlow::Continue(state)
+                }
+
+                fn drain_all() -> impl Iterator<Item = (SignalNumber, u64)> {
+                    // All taken now, not as iterated.
+                    let counts = [ $( take_count_pair::<{$signum}, Self>() ),+ ];
+                    IntoIterator::into_iter(counts).filter(|&(_, count)| count != 0)
+                }
+
+                fn pending() -> impl Iterator<Item = (SignalNumber, u64)> {
+                    // All loaded now, not as iterated.
+                    let counts = [ $( peek_count_pair::<{$signum}, Self>() ),+ ];
+                    IntoIterator::into_iter(counts).filter(|&(_, count)| count != 0)
+                }
+
+                fn coalescing_observed_for(sig_num: SignalNumber) -> bool {
+                    $(
+                        if sig_num == resolve_signum($signum) {
+                            return <Self as SignalReceipt<{$signum}>>
+                                       ::coalescing_observed();
+                        }
+                    )+
+                    false
+                }
+
+                fn take_overflowed_for(sig_num: SignalNumber) -> bool {
+                    $(
+                        if sig_num == resolve_signum($signum) {
+                            return <Self as SignalReceipt<{$signum}>>
+                                       ::take_overflowed();
+                        }
+                    )+
+                    false
+                }
+
+                fn try_consume_loop_with_start(
                     do_mask: bool,
                     state: Self::Continue,
+                    on_start: impl FnOnce(&mut Self::Continue),
                     finish: Self::Break
-                ) -> Self::Break
+                ) -> Result<Self::Break, $crate::ConsumeLoopError>
                 {
-                    // This just enables our `$( ... $callback ...)?` to work where `$callback`
-                    // actually isn't used in that.
-                    #[allow(unused_macros)]
-                    macro_rules! repeat_for { ($metavar:tt: $second:expr) => { $second } }
-
                     let sem = <Self as Premade>::semaphore();
-                    let try_init_limit = 200_000_000; // Enough for at least a second.
-                    let mut consumers = [ $(
-                        &mut repeat_for!($callback: delegates::callback::__FUNC)
-                            as &mut Consumer<Self::Break, Self::Continue>,
-                    )? $(
-                        &mut (|state| consume_count_then_delegate::<
-                              {signals_names::$signum}, Self, _, Self::Break, Self::Continue>(
-                                  state, delegates::$signum::__FUNC))
-                            as &mut Consumer<Self::Break, Self::Continue>
-                    ),+ ];
+                    let mut consumers = consumers!();
                     let continue_flag = <Self as Premade>::continue_flag();
 
                     // (Must not try here to make our semaphore start fresh if our handling is
@@ -314,14 +1178,45 @@ macro_rules! premade {
                     // only cause `consume_loop` to loop that many extra times checking the
                     // receipt counters pointlessly and harmlessly.)
 
-                    $crate::consume_loop(do_mask, sem, try_init_limit, state, &mut consumers,
-                                         continue_flag, finish)
+                    let pause_flag = <Self as Premade>::pause_flag();
+                    $crate::__internal::try_consume_loop_pausable_with_start(
+                        do_mask, sem, TRY_INIT_LIMIT, state, &mut consumers, continue_flag,
+                        pause_flag, on_start, finish)
+                }
+
+                #[cfg(not(target_os = "macos"))]
+                fn consume_loop_with_timeout(
+                    do_mask: bool,
+                    timeout: core::time::Duration,
+                    clock: $crate::util::WaitClock,
+                    state: Self::Continue,
+                    finish: Self::Break
+                ) -> Self::Break
+                {
+                    let sem = <Self as Premade>::semaphore();
+                    let mut consumers = consumers!();
+                    let continue_flag = <Self as Premade>::continue_flag();
+                    let pause_flag = <Self as Premade>::pause_flag();
+                    // (Same as above about not making our semaphore start fresh.)
+                    $crate::__internal::consume_loop_timed_pausable(
+                        do_mask, sem, TRY_INIT_LIMIT, timeout, clock, state, &mut consumers,
+                        continue_flag, pause_flag, finish)
                 }
 
                 fn continue_flag() -> &'static AtomicBool {
                     static CONTINUE_FLAG: AtomicBool = AtomicBool::new(true);
                     &CONTINUE_FLAG
                 }
+
+                fn quiesce_flag() -> &'static AtomicU8 {
+                    static QUIESCE_FLAG: AtomicU8 = AtomicU8::new(QUIESCE_NONE);
+                    &QUIESCE_FLAG
+                }
+
+                fn pause_flag() -> &'static AtomicBool {
+                    static PAUSE_FLAG: AtomicBool = AtomicBool::new(false);
+                    &PAUSE_FLAG
+                }
             }
 
             /// Places the `$delegate` expressions in (nearly) clean scopes, so they cannot
@@ -341,20 +1236,177 @@ macro_rules! premade {
                           = $callback;
                     }
                 )?
+                $(
+                    pub(super) mod epilogue {
+                        use super::*; // Import any items given above.
+
+                        pub(in super::super) const __FUNC:
+                          fn(<super::super::SignalsReceipts as $crate::Premade>::Continue)
+                            -> core::ops::ControlFlow<
+                                 <super::super::SignalsReceipts as $crate::Premade>::Break,
+                                 <super::super::SignalsReceipts as $crate::Premade>::Continue>
+                          = $epilogue;
+                    }
+                )?
                 $(
                     pub(super) mod $signum {
                         use super::*; // Import any items given above.
 
                         pub(in super::super) const __FUNC:
-                          fn(&mut $crate::Receipt<u64,
-                                    <super::super::SignalsReceipts as $crate::Premade>::Break,
-                                    <super::super::SignalsReceipts as $crate::Premade>::Continue>)
+                          $crate::premade!(@delegate ($( $always )?) $signum)
                           = $delegate;
                     }
                 )+
             }
         }
     };
+
+    // Expand each group of signals, like `[SIGINT, SIGTERM] => ...;`, into an entry per signal
+    // that shares the delegate expression, one entry at a time, and then re-expand as if they'd
+    // been declared individually.  `$found` is whether any group was expanded, because otherwise
+    // the input was invalid for all of the above rules and re-expanding it would loop.
+    { @groups $found:ident $head:tt [$( $done:tt )*]
+      [ $( $group:ident ),+ $(,)? ] => $delegate:expr; $( $rest:tt )* } => {
+        $crate::premade! { @groups true $head [$( $done )* $( $group => $delegate; )+]
+                           $( $rest )* }
+    };
+    { @groups $found:ident $head:tt [$( $done:tt )*]
+      $signum:ident $( = rt($rt:expr) )? $( : $uint:ty )? $( {$always:ident} )?
+      => $delegate:expr; $( $rest:tt )* } => {
+        $crate::premade! {
+            @groups $found $head
+            [$( $done )* $signum $( = rt($rt) )? $( : $uint )? $( {$always} )? => $delegate;]
+            $( $rest )*
+        }
+    };
+    { @groups $found:ident $head:tt [$( $done:tt )*] $first:tt $( $rest:tt )* } => {
+        $crate::premade! { @groups $found $head [$( $done )* $first] $( $rest )* }
+    };
+    { @groups true () [$( $done:tt )*] } => {
+        $crate::premade! { $( $done )* }
+    };
+    { @groups true ($( $head:tt )+) [$( $done:tt )*] } => {
+        $crate::premade! { $( $head )+ { $( $done )* } }
+    };
+    { @groups false $head:tt [$( $done:tt )*] } => {
+        core::compile_error!("invalid syntax for `premade!`, see its documentation");
+    };
+
+    {
+        $visib:vis mod $name:ident { $( $body:tt )* }
+    } => {
+        $crate::premade! { @groups false ($visib mod $name) [] $( $body )* }
+    };
+
+    { $( $body:tt )* } => {
+        $crate::premade! { @groups false () [] $( $body )* }
+    };
+}
+
+
+/// Guard returned by [`Premade::install_all_handlers_guarded`] that uninstalls all the handlers
+/// of `P` when dropped.
+///
+/// This is `!Send`, because the dispositions of signals are process-wide, and so the guard should
+/// be dropped by the same thread that did the installing, as part of the scope of whatever needed
+/// the handlers, so that it's clear when they're uninstalled.
+#[must_use = "dropping this immediately uninstalls the handlers"]
+pub struct InstalledHandlers<P: Premade> {
+    finish:   bool,
+    /// The pointer makes this `!Send`.
+    _premade: PhantomData<*const P>,
+}
+
+impl<P: Premade> InstalledHandlers<P> {
+    /// Do [`Premade::finish`] when dropped, instead of only uninstalling, so that the consuming
+    /// thread is also told to finish.
+    #[inline]
+    pub fn finishing(mut self) -> Self {
+        self.finish = true;
+        self
+    }
+}
+
+impl<P: Premade> Drop for InstalledHandlers<P> {
+    #[inline]
+    fn drop(&mut self) {
+        if self.finish {
+            P::finish();
+        } else {
+            P::uninstall_all_handlers();
+        }
+    }
+}
+
+impl<P: Premade> Debug for InstalledHandlers<P> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstalledHandlers").field("finish", &self.finish).finish_non_exhaustive()
+    }
+}
+
+
+/// Error from [`Premade::verify_all_handlers`] (or the like), when our handler isn't the one
+/// that is installed for some of the declared signal numbers.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[allow(clippy::exhaustive_structs)] // Must be constructible by the `premade` macro.
+pub struct VerifyError {
+    /// The signal numbers whose handlers didn't verify.
+    pub unverified: SigSet,
+}
+
+impl Display for VerifyError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "handlers not installed for signal numbers:")?;
+        for signum in self.unverified.iter() {
+            write!(f, " {signum}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error from [`Premade::try_install_all_handlers_with`], when installing a handler failed, after
+/// which the handlers that had been installed before that were uninstalled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[allow(clippy::exhaustive_structs)] // Must be constructible by the `premade` macro.
+pub struct PartialInstallError {
+    /// The failure of installing, with its signal number.
+    pub failed:      InstallHandlerError,
+    /// The signal numbers whose handlers had been installed before the failure, which were
+    /// uninstalled.
+    pub rolled_back: SigSet,
+}
+
+impl Display for PartialInstallError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, and so uninstalled for signal numbers:", self.failed)?;
+        for signum in self.rolled_back.iter() {
+            write!(f, " {signum}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error from [`Premade::install_one`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum InstallOneError {
+    /// The signal number isn't among the declared ones.
+    Undeclared(SignalNumber),
+    /// Installing the handler failed.
+    Install(InstallHandlerError),
+}
+
+impl Display for InstallOneError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Undeclared(signum) => write!(f, "signal number {signum} is not declared"),
+            Self::Install(e) => Display::fmt(e, f),
+        }
+    }
 }
 
 
@@ -374,7 +1426,7 @@ where
     T: SignalReceipt<SIGNUM>,
     F: FnMut(&mut Receipt<<<T as SignalReceipt<SIGNUM>>::AtomicUInt as AtomicUInt>::UInt, B, C>),
 {
-    let cur_count = <T as SignalReceipt<SIGNUM>>::take_count();
+    let cur_count = <T as SignalReceipt<SIGNUM>>::take_dirty_count();
     let flow = ControlFlow::Continue(state);
     if cur_count == 0.into() {
         // Do not call the delegate, when the count is zero.
@@ -382,8 +1434,140 @@ where
     } else {
         // Passing-in this kind of argument enables a delegate to be simpler in which aspects it
         // wants to deal with or not.
-        let mut receipt = Receipt { sig_num: SIGNUM, cur_count, flow };
+        let received_at = <T as SignalReceipt<SIGNUM>>::last_received_nanos();
+        let sig_num = resolve_signum(SIGNUM);
+        let mut receipt = Receipt { sig_num, cur_count, received_at, flow };
         delegate(&mut receipt);
         receipt.flow // The delegate can choose whether or not to change this.
     }
 }
+
+/// Like [`consume_count_then_delegate`], but the `delegate` is always called, even when the
+/// current count is zero, so that it runs every iteration of the consuming loop (which is woken
+/// by the delivery of any signal of the loop, not only of `SIGNUM`).  E.g. for decaying a rate
+/// estimate.  The delegate can branch on `receipt.cur_count == 0`.
+#[inline]
+pub fn consume_count_then_delegate_always<const SIGNUM: SignalNumber, T, F, B, C>(
+    state: C,
+    mut delegate: F,
+) -> ControlFlow<B, C>
+where
+    T: SignalReceipt<SIGNUM>,
+    F: FnMut(&mut Receipt<<<T as SignalReceipt<SIGNUM>>::AtomicUInt as AtomicUInt>::UInt, B, C>),
+{
+    let cur_count = <T as SignalReceipt<SIGNUM>>::take_dirty_count();
+    let received_at = <T as SignalReceipt<SIGNUM>>::last_received_nanos();
+    let sig_num = resolve_signum(SIGNUM);
+    let flow = ControlFlow::Continue(state);
+    let mut receipt = Receipt { sig_num, cur_count, received_at, flow };
+    delegate(&mut receipt);
+    receipt.flow
+}
+
+/// Like [`consume_count_then_delegate`], but the count is only loaded, not taken, before calling
+/// the `delegate`, and is only subtracted from the counter after the `delegate` returns.  So if
+/// the `delegate` panics (and that's caught, e.g. by `std::panic::catch_unwind` around the
+/// consuming), the count remains intact to be processed again the next time, which is needed for
+/// idempotent-retry semantics.
+///
+/// Deliveries that are counted while the `delegate` is running aren't lost, because only the
+/// amount that was given to it is subtracted (atomically, via
+/// [`AtomicUInt::saturating_sub`]), and so those remain counted for the next time.
+///
+/// Like `consume_count_then_delegate`, if [`SignalReceipt::dirty_bit()`] is used, the counter is
+/// only checked when the bit is set.  The bit is only cleared once the retained count was
+/// handled, and is left set if deliveries remain counted, so that later iterations don't revisit
+/// the signal when there's nothing new.
+#[inline]
+pub fn consume_count_then_delegate_retaining<const SIGNUM: SignalNumber, T, F, B, C>(
+    state: C,
+    mut delegate: F,
+) -> ControlFlow<B, C>
+where
+    T: SignalReceipt<SIGNUM>,
+    F: FnMut(&mut Receipt<<<T as SignalReceipt<SIGNUM>>::AtomicUInt as AtomicUInt>::UInt, B, C>),
+{
+    let dirty = <T as SignalReceipt<SIGNUM>>::dirty_bit()
+        .map(|(word, bit)| (word, 1_u64.checked_shl(bit).unwrap_or(0)));
+    if let Some((word, mask)) = dirty {
+        // Synchronizes with the setting in `handler`, like in `take_dirty_count`.
+        if word.load(Acquire) & mask == 0 {
+            return ControlFlow::Continue(state);
+        }
+    }
+    let cur_count = <T as SignalReceipt<SIGNUM>>::peek_count();
+    let flow = if cur_count == 0.into() {
+        ControlFlow::Continue(state)
+    } else {
+        let received_at = <T as SignalReceipt<SIGNUM>>::last_received_nanos();
+        let sig_num = resolve_signum(SIGNUM);
+        let flow = ControlFlow::Continue(state);
+        let mut receipt = Receipt { sig_num, cur_count, received_at, flow };
+        delegate(&mut receipt);
+        receipt.flow
+    };
+    // Only now that the delegate succeeded.  The bit is cleared before subtracting, and set again
+    // if any remain counted, so that a delivery that was counted meanwhile, whose own setting of
+    // the bit might have preceded our clearing, isn't missed.  (Acquire, so that such a
+    // delivery's increment is visible to our subtracting.)
+    if let Some((word, mask)) = dirty {
+        word.fetch_and(!mask, Acquire);
+    }
+    let remaining = <T as SignalReceipt<SIGNUM>>::counter().saturating_sub(cur_count);
+    if remaining != 0.into() {
+        if let Some((word, mask)) = dirty {
+            word.fetch_or(mask, Relaxed);
+        }
+    }
+    flow
+}
+
+/// Like [`consume_count_then_delegate`], but the given `action` is only given the current count
+/// and the state, and returns the [`ControlFlow`] directly.  For the common pattern of doing an
+/// action once (e.g. reloading a config) but knowing how many receipts it was done for (e.g. to
+/// log that), without needing a whole [`Receipt`].
+///
+/// The `action` is called at most once per call of this, with the coalesced count.  When the
+/// count is zero, the `action` is skipped entirely and `Continue(state)` is returned.
+#[inline]
+pub fn consume_once_with_count<const SIGNUM: SignalNumber, T, F, B, C>(
+    state: C,
+    action: F,
+) -> ControlFlow<B, C>
+where
+    T: SignalReceipt<SIGNUM>,
+    F: FnOnce(
+        <<T as SignalReceipt<SIGNUM>>::AtomicUInt as AtomicUInt>::UInt,
+        C,
+    ) -> ControlFlow<B, C>,
+{
+    let cur_count = <T as SignalReceipt<SIGNUM>>::take_dirty_count();
+    if cur_count == 0.into() { ControlFlow::Continue(state) } else { action(cur_count, state) }
+}
+
+
+/// Make a delegate that, upon the first [`Receipt`], calls the given `cleanup` and then
+/// terminates the process via [`util::terminate_by_default`](crate::util::terminate_by_default),
+/// so that the exit status indicates termination by the signal as if it weren't handled.  E.g.
+/// for `SIGTERM`, to clean-up and then "die like normal".
+///
+/// Intended to be given to [`consume_count_then_delegate`], like
+/// [`util::enqueue_on_receipt`](crate::util::enqueue_on_receipt).  The `cleanup` is called by
+/// the consuming thread in a normal context (not in a signal handler), and so it can do
+/// whatever.  The terminating is done by that same thread, which is why that unblocks the signal
+/// for itself.
+///
+/// Only intended for signals whose default action is to terminate.  For others, after the
+/// cleanup, this just returns (and the disposition will have been reset to the default).
+#[inline]
+pub fn cleanup_then_default<U, B, C>(
+    cleanup: impl FnOnce(),
+) -> impl FnMut(&mut Receipt<U, B, C>) {
+    let mut cleanup = Some(cleanup);
+    move |receipt| {
+        if let Some(cleanup) = cleanup.take() {
+            cleanup();
+        }
+        crate::util::terminate_by_default(receipt.sig_num);
+    }
+}
@@ -4,5 +4,233 @@ pub use libc as signals_names;
 
 pub trait Sealed {}
 
+/// The mask and flags, for installing the handlers of the `premade` macro, built only once and
+/// reused for all its signal numbers.
+#[derive(Debug)]
+pub struct HandlerTemplate(crate::util::SigAction);
+
+impl HandlerTemplate {
+    /// Like the `mask` and `restart` arguments of [`crate::install_handler`].
+    #[must_use]
+    #[inline]
+    pub fn new(mask: bool, restart: bool) -> Self { Self(crate::handler_template(mask, restart)) }
+
+    /// Like [`crate::try_install_handler`], but reusing the mask and flags of `self`.
+    ///
+    /// # Errors
+    /// If installing the handler fails.  Only possible if an invalid signal number was given.
+    #[inline]
+    pub fn try_install<const SIGNUM: crate::SignalNumber, T: crate::SignalReceipt<SIGNUM>>(
+        &self,
+    ) -> Result<(), crate::InstallHandlerError> {
+        crate::install_handler_from_template::<SIGNUM, T>(&self.0)
+    }
+
+    /// Like [`Self::try_install`], but installs [`crate::handler_with_value`] instead, for the
+    /// signals declared with `{values}`.
+    ///
+    /// # Errors
+    /// Same as [`Self::try_install`].
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[inline]
+    pub fn try_install_with_value<
+        const SIGNUM: crate::SignalNumber,
+        T: crate::SignalReceiptWithValue<SIGNUM>,
+    >(
+        &self,
+    ) -> Result<(), crate::InstallHandlerError> {
+        crate::install_handler_with_value_from_template::<SIGNUM, T>(&self.0)
+    }
+}
+
+/// Count one delivery of `SIGNUM` that was received other than via our handler, e.g. read from
+/// a `signalfd`, for [`crate::Premade::count_delivery`].
+#[inline]
+pub fn count_delivery<const SIGNUM: crate::SignalNumber, T: crate::SignalReceipt<SIGNUM>>() {
+    let _is_wake = crate::count_delivery::<SIGNUM, T>();
+}
+
+/// Take the count of `SIGNUM`, paired with its (resolved) signal number, for
+/// [`crate::Premade::drain_all`].  The count is saturated if the counter is wider than 64 bits.
+#[must_use]
+#[inline]
+pub fn take_count_pair<const SIGNUM: crate::SignalNumber, T: crate::SignalReceipt<SIGNUM>>(
+) -> (crate::SignalNumber, u64)
+where
+    u64: TryFrom<<T::AtomicUInt as crate::AtomicUInt>::UInt>,
+{
+    let count = <T as crate::SignalReceipt<SIGNUM>>::take_dirty_count();
+    (crate::resolve_signum(SIGNUM), u64::try_from(count).unwrap_or(u64::MAX))
+}
+
+/// Like [`take_count_pair`], but only loads the count, without resetting it, for
+/// [`crate::Premade::pending`].
+#[must_use]
+#[inline]
+pub fn peek_count_pair<const SIGNUM: crate::SignalNumber, T: crate::SignalReceipt<SIGNUM>>(
+) -> (crate::SignalNumber, u64)
+where
+    u64: TryFrom<<T::AtomicUInt as crate::AtomicUInt>::UInt>,
+{
+    let count = <T as crate::SignalReceipt<SIGNUM>>::peek_count();
+    (crate::resolve_signum(SIGNUM), u64::try_from(count).unwrap_or(u64::MAX))
+}
+
+/// Whether `signum` is one that the `premade` macro rejects at compile time: those that can't be
+/// caught (`SIGKILL`, `SIGSTOP`), and those that must not be blocked because a "computational
+/// exception" generates them (`SIGFPE`, `SIGILL`, `SIGSEGV`, `SIGBUS`).
+#[must_use]
+#[inline]
+pub const fn is_undeclarable(signum: crate::SignalNumber) -> bool {
+    matches!(
+        signum,
+        libc::SIGKILL | libc::SIGSTOP | libc::SIGFPE | libc::SIGILL | libc::SIGSEGV | libc::SIGBUS
+    )
+}
+
+/// How many words the dirty bitmask of the `premade` macro needs for the given amount of
+/// declared signal numbers.
+#[must_use]
+#[inline]
+pub const fn dirty_words(signums_len: usize) -> usize { signums_len.div_ceil(64) }
+
+/// The word index and bit index, of the dirty bitmask of the `premade` macro, for the given
+/// signal number, according to its position among the given declared signal numbers.
+///
+/// # Panics
+/// If `signum` isn't among `signums`.  Only done at compile time by the macro.
+#[must_use]
+#[inline]
+pub const fn dirty_bit_position(
+    signums: &[crate::SignalNumber],
+    signum: crate::SignalNumber,
+) -> (usize, u32) {
+    let mut rest = signums;
+    let (mut word, mut bit): (usize, u32) = (0, 0);
+    while let Some((first, tail)) = rest.split_first() {
+        if *first == signum {
+            return (word, bit);
+        }
+        rest = tail;
+        bit = bit.saturating_add(1);
+        if bit == 64 {
+            word = word.saturating_add(1);
+            bit = 0;
+        }
+    }
+    #[allow(clippy::panic)] // Only at compile time, and impossible when used by the macro.
+    {
+        panic!("signal number must be declared");
+    }
+}
+
+/// How many times, at most, [`crate::Premade::install_all_handlers_with`] decrements the
+/// semaphore when `reset_sem`.  Enough for a value that's left over from many deliveries, but
+/// bounded so that re-installing can't be delayed much.
+pub const RESET_SEM_LIMIT: u64 = 1_000_000;
+
+/// Values of [`super::Premade::quiesce_flag`].  Not requested.
+pub const QUIESCE_NONE: u8 = 0;
+/// [`super::Premade::quiesce`] was requested, but a complete iteration since then hasn't started.
+pub const QUIESCE_REQUESTED: u8 = 1;
+/// A complete iteration, that started after quiescing was requested, is being done.
+pub const QUIESCE_ARMED: u8 = 2;
+
+/// Called by the `premade` macro's consuming loop after all the delegates of each iteration, to
+/// progress with [`super::Premade::quiesce`].
+#[inline]
+pub fn quiesce_check<P: super::Premade>() {
+    use core::sync::atomic::Ordering::{Acquire, Relaxed};
+
+    let flag = P::quiesce_flag();
+    match flag.load(Acquire) {
+        // The iteration that just completed might have started before the request, and so
+        // another complete one is needed.  Ensure we wake for it.
+        QUIESCE_REQUESTED => {
+            let is_armed =
+                flag.compare_exchange(QUIESCE_REQUESTED, QUIESCE_ARMED, Relaxed, Relaxed).is_ok();
+            if is_armed {
+                super::wake::<P>();
+            }
+        },
+        // The iteration that just completed started after the request, and so it took all the
+        // counts that were incremented before the handlers were uninstalled.
+        QUIESCE_ARMED => P::continue_flag().store(false, Relaxed),
+        _ => {},
+    }
+}
+
+/// Like [`crate::consume_loop_pausable`], but with the timed waiting of
+/// [`crate::consume_loop_timed`], for the `premade` macro's [`super::Premade::pause`] to also
+/// work with [`super::Premade::consume_loop_with_timeout`].
+///
+/// # Panics
+/// Same as `crate::consume_loop`.
+#[cfg(not(target_os = "macos"))]
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+#[inline]
+pub fn consume_loop_timed_pausable<B, C, W: crate::TimedWaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    timeout: core::time::Duration,
+    clock: crate::util::WaitClock,
+    state: C,
+    consumers: &mut [&mut crate::Consumer<B, C>],
+    continue_flag: &core::sync::atomic::AtomicBool,
+    pause_flag: &core::sync::atomic::AtomicBool,
+    finish: B,
+) -> B {
+    crate::consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        Some(pause_flag),
+        finish,
+        |_| {},
+        |sem| sem.timed_wait(timeout, clock),
+    )
+}
+
+/// Like [`crate::consume_loop_pausable`], but with the `on_start` of
+/// [`crate::consume_loop_with_start`], and returning the error of
+/// [`crate::consume_loop_fallible`], for the `premade` macro's
+/// [`super::Premade::try_consume_loop_with_start`] to also work with [`super::Premade::pause`].
+///
+/// # Errors
+/// Same as `crate::consume_loop_fallible`.
+///
+/// # Panics
+/// Same as `crate::consume_loop_fallible`.
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+#[inline]
+pub fn try_consume_loop_pausable_with_start<B, C, W: crate::WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    state: C,
+    consumers: &mut [&mut crate::Consumer<B, C>],
+    continue_flag: &core::sync::atomic::AtomicBool,
+    pause_flag: &core::sync::atomic::AtomicBool,
+    on_start: impl FnOnce(&mut C),
+    finish: B,
+) -> Result<B, crate::ConsumeLoopError> {
+    crate::try_consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        consumers,
+        continue_flag,
+        Some(pause_flag),
+        finish,
+        on_start,
+        crate::WaitSource::wait,
+    )
+}
+
 #[cfg(feature = "channel_notify_facility")]
 pub mod channel_notify_facility;
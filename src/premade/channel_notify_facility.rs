@@ -2,19 +2,25 @@
 //! installing, uninstalling, and internal consuming thread.
 
 
-pub use state::{FinishError, InstallError, UninstallError};
+pub use state::{CoalescePolicy, FacilityStateKind, FinishError, InstallConfig, InstallError,
+                UninstallError};
 pub(super) mod state;
 
 pub(super) mod receipts_thread;
 
-pub use signals_channel::{encapsulated::Receiver, SendError, Sender};
+pub use signals_channel::{broadcast::Broadcast, encapsulated::Receiver, socket::SocketSender,
+                          SendError, Sender};
 mod signals_channel;
 
 
 use super::__internal::Sealed;
 use crate::SignalNumber;
-use core::fmt::Debug;
+use core::{fmt::Debug, time::Duration};
 use receipts_thread::DelegatesState;
+use signals_channel::FnSender;
+extern crate std;
+/// The errors of [`Receiver`]'s methods.
+pub use std::sync::mpsc::{RecvError, RecvTimeoutError, TryRecvError};
 
 
 /// Functions for using a `SignalsChannel` type to manage the signal handling as generated by the
@@ -24,10 +30,10 @@ use receipts_thread::DelegatesState;
 /// `SignalsChannel` type) is accessed thru a mutex, and so all these methods might block waiting
 /// to acquire it if there is concurrent use of these methods of the same `SignalsChannel` type.
 /// Such blocking is only very brief, regardless of the possible states of the state, for almost
-/// all the methods, except for `finish` and `finish_with_outside_channel` which wait for the
-/// "signals-receipt" thread to finish while holding the mutex which might take somewhat longer,
-/// and in the supposed-to-never-occur chance that the thread is permanently stuck then those
-/// methods would never return and so never release the mutex.
+/// all the methods, except for `finish`, `finish_with_outside_channel`, and `finish_drained`
+/// which wait for the "signals-receipt" thread to finish while holding the mutex which might take
+/// somewhat longer, and in the supposed-to-never-occur chance that the thread is permanently
+/// stuck then those methods would never return and so never release the mutex.
 ///
 /// This trait is sealed to only be implemented automatically by the `channel_notify_facility`
 /// macro.  This trait only exists so that macro can provide these functions.
@@ -74,6 +80,23 @@ pub trait SignalsChannel: Sealed + Sized {
         SignalNumber: TryInto<N>,
         N: Send + 'static;
 
+    /// Like [`Self::install`], but with more options, e.g. the stack size and name of the
+    /// internal "signals-receipt" thread.  See [`InstallConfig`].
+    ///
+    /// # Errors
+    /// - Same as [`Self::install`].
+    /// - If the thread can't be created, e.g. because the stack size is too large.
+    /// - If the thread name is invalid.
+    ///
+    /// # Panics
+    /// Same as [`Self::install`].
+    fn install_with_config<N>(
+        config: InstallConfig,
+    ) -> Result<Receiver<N, Self>, InstallError<()>>
+    where
+        SignalNumber: TryInto<N>,
+        N: Send + 'static;
+
     /// Install global signal handlers, with notifications sent to the given preexisting `notify`
     /// channel that is outside our control.
     ///
@@ -88,6 +111,93 @@ pub trait SignalsChannel: Sealed + Sized {
     /// Same as [`Self::install`].
     fn install_with_outside_channel<T: Sender>(notify: T) -> Result<(), InstallError<T>>;
 
+    /// Like [`Self::install_with_outside_channel`], with a newly-created bounded `tokio` channel,
+    /// whose receiving end is returned, so that an async task can `.recv().await` the
+    /// notifications without tying up a blocking thread.  Available when the `tokio` package
+    /// feature is enabled.
+    ///
+    /// The internal "signals-receipt" thread blocks when the channel is full, like with
+    /// [`Self::install`] with a bound, and so `buffer` must be positive.
+    ///
+    /// Because this uses an outside channel, [`Self::uninstall_with_outside_channel`] or
+    /// [`Self::finish_with_outside_channel`] must be used, and the returned receiver must be
+    /// dropped before finishing, as those describe.
+    ///
+    /// # Errors
+    /// Same as [`Self::install`].
+    ///
+    /// # Panics
+    /// - Same as [`Self::install`].
+    /// - If `buffer` is zero.
+    #[cfg(feature = "tokio")]
+    #[inline]
+    fn install_tokio<N>(buffer: usize) -> Result<tokio::sync::mpsc::Receiver<N>, InstallError<()>>
+    where
+        SignalNumber: TryInto<N>,
+        N: Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::mpsc::channel(buffer);
+        Self::install_with_outside_channel(sender).map(|()| receiver).map_err(InstallError::from)
+    }
+
+    /// Like [`Self::install_with_outside_channel`], but without a channel: `f` is called, on the
+    /// internal "signals-receipt" thread, instead of sending each notification.  E.g. to just run
+    /// a bit of code per signal, without needing to define a [`Sender`] type.
+    ///
+    /// `f` is called in a normal context (not in a signal handler), and so it can do whatever,
+    /// but blocking in it delays the processing of further signals.  Its result is treated the
+    /// same as that of [`Sender::send`].
+    ///
+    /// [`Self::uninstall_with_outside_channel`] or [`Self::finish_with_outside_channel`] must be
+    /// used, and `f` must not block forever, since there's no channel for those to disconnect.
+    ///
+    /// # Errors
+    /// Same as [`Self::install`].
+    ///
+    /// # Panics
+    /// Same as [`Self::install`].
+    #[inline]
+    fn install_with_fn<F>(f: F) -> Result<(), InstallError<()>>
+    where
+        F: FnMut(SignalNumber) -> Result<(), SendError> + Send + 'static,
+    {
+        Self::install_with_outside_channel(FnSender::new(f)).map_err(InstallError::from)
+    }
+
+    /// Like [`Self::install_with_outside_channel`], with notifications fanned-out to any number
+    /// of receivers, which are created by [`Broadcast::subscribe`], at any time.  E.g. so that
+    /// several subsystems can independently react to `SIGHUP`.  Each receiver has its own
+    /// channel, bounded by `channel_bound` like with [`Self::install`].
+    ///
+    /// When a receiver is dropped, its channel is pruned from the set, without affecting the
+    /// others.  The internal "signals-receipt" thread sends to each receiver in turn, and so a
+    /// full bounded one delays the others, and also delays [`Broadcast::subscribe`] meanwhile.
+    ///
+    /// Because the receivers are outside the control of the state,
+    /// [`Self::uninstall_with_outside_channel`] or [`Self::finish_with_outside_channel`] must be
+    /// used, and the receivers must be dropped, as those describe.  The receivers' channels are
+    /// disconnected once both the `Broadcast` (and all its clones) are dropped and our handling
+    /// is uninstalled.
+    ///
+    /// # Errors
+    /// Same as [`Self::install`].
+    ///
+    /// # Panics
+    /// Same as [`Self::install`].
+    #[inline]
+    fn install_broadcast<N>(
+        channel_bound: Option<usize>,
+    ) -> Result<Broadcast<N, Self>, InstallError<()>>
+    where
+        SignalNumber: TryInto<N>,
+        N: Send + 'static,
+    {
+        let broadcast = Broadcast::new(channel_bound);
+        Self::install_with_outside_channel(broadcast.sender())
+            .map(|()| broadcast)
+            .map_err(InstallError::from)
+    }
+
     /// Uninstall global signal handlers (fully, at the OS-process level), if [`Self::install`]
     /// was used to install.
     ///
@@ -165,6 +275,49 @@ pub trait SignalsChannel: Sealed + Sized {
     /// # Panics
     /// Same as [`Self::uninstall_with_outside_channel`].
     fn finish_with_outside_channel() -> Result<(), FinishError>;
+
+    /// Like [`Self::finish`], but without losing any notifications that were still queued in the
+    /// channel or that were still pending for receipts counted before our handling was
+    /// uninstalled.
+    ///
+    /// Our signal handling is uninstalled first, to stop counting, then the "signals-receipt"
+    /// thread sends notifications of the remaining receipts and disconnects the channel, and then
+    /// that thread is finished.  Meanwhile, this keeps reading the `receiver` until it's
+    /// disconnected, and gives each notification read to `sink`, in order.  The caller processes
+    /// the final notifications in `sink`, instead of needing to keep reading the `receiver` on
+    /// another thread, which also ensures that the thread can't block forever on sending on a
+    /// full bounded channel.
+    ///
+    /// `sink` is called while the mutex of the state is held, and so it must not call any of the
+    /// methods of `Self`, or else it will deadlock.
+    ///
+    /// If our signal handling is already uninstalled, this is the same as [`Self::finish`].
+    ///
+    /// # Errors
+    /// - Same as [`Self::finish`].
+    /// - If `timeout` elapsed before the channel was disconnected.  The handling is still
+    ///   finished in that case, but notifications not yet read are lost.
+    ///
+    /// # Panics
+    /// Same as [`Self::finish`].
+    fn finish_drained<N>(
+        receiver: Receiver<N, Self>,
+        timeout: Duration,
+        sink: impl FnMut(N),
+    ) -> Result<(), FinishError>;
+
+    /// Set the function that is called whenever `Self` transitions between the states (as
+    /// indicated by [`Self::is_installed`] et al), with the new state.  E.g. for updating a
+    /// status display or a readiness probe.  Replaces any previous observer.  By default, there
+    /// is no observer.
+    ///
+    /// The observer is called once per operation that changes the state, i.e. finishing from
+    /// installed is observed as one transition to [`FacilityStateKind::Nothing`].
+    ///
+    /// The observer is called in a normal context, by the thread that called the state-changing
+    /// method, while the mutex of the state is held.  And so the observer must not call any of
+    /// the methods of `Self`, or else it will deadlock.
+    fn set_state_observer(observer: impl Fn(FacilityStateKind) + Send + Sync + 'static);
 }
 
 
@@ -211,9 +364,11 @@ macro_rules! channel_notify_facility {
                                  SignalsReceipts as SignalsReceiptsTrait},
                              Sealed},
                          channel_notify_facility::{
-                             Receiver, Sender, InstallError, UninstallError, FinishError,
+                             Receiver, Sender, InstallConfig, InstallError, UninstallError,
+                             FinishError, FacilityStateKind,
                              SignalsChannel as SignalsChannelTrait},
                          SignalNumber};
+            use ::core::time::Duration;
             use self::signals_receipts_premade::SignalsReceipts;
 
             $crate::premade! {
@@ -258,6 +413,16 @@ macro_rules! channel_notify_facility {
                     STATE.install(channel_bound)
                 }
 
+                fn install_with_config<N>(
+                    config: InstallConfig
+                ) -> Result<Receiver<N, Self>, InstallError<()>>
+                where
+                    SignalNumber: TryInto<N>,
+                    N: Send + 'static
+                {
+                    STATE.install_with_config(config)
+                }
+
                 fn install_with_outside_channel<T: Sender>(
                     notify: T
                 ) -> Result<(), InstallError<T>> {
@@ -279,6 +444,20 @@ macro_rules! channel_notify_facility {
                 fn finish_with_outside_channel() -> Result<(), FinishError> {
                     STATE.finish_with_outside_channel()
                 }
+
+                fn finish_drained<N>(
+                    receiver: Receiver<N, Self>,
+                    timeout: Duration,
+                    sink: impl FnMut(N),
+                ) -> Result<(), FinishError> {
+                    STATE.finish_drained(receiver, timeout, sink)
+                }
+
+                fn set_state_observer(
+                    observer: impl Fn(FacilityStateKind) + Send + Sync + 'static
+                ) {
+                    STATE.set_observer(observer);
+                }
             }
         }
     };
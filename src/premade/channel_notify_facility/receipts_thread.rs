@@ -1,11 +1,14 @@
-use super::{signals_channel, SignalsChannel, SignalsReceipts};
-use crate::{help::assert_errno_is_overflow, Receipt, SemaphoreMethods as _, SemaphoreRef};
+use super::{signals_channel, CoalescePolicy, InstallConfig, SignalsChannel, SignalsReceipts};
+use crate::{help::assert_errno_is_overflow, Receipt, SemaphoreMethods as _, SemaphoreRef,
+            SignalNumber};
 use core::{fmt::{self, Display, Formatter},
            marker::PhantomData,
            mem::size_of,
            ops::ControlFlow};
+extern crate alloc;
+use alloc::{borrow::ToOwned as _, boxed::Box};
 extern crate std;
-use std::{error::Error, io, prelude::rust_2021::*, sync::mpsc, thread};
+use std::{error::Error, io, sync::mpsc, thread};
 
 
 /// Internal thread that processes updates to signal-receipt counters and that sends, over a
@@ -21,16 +24,28 @@ pub struct ReceiptsThread<C, R> {
 }
 
 
+/// Callback for when sending a notification fails.
+type OnSendError = fn(SignalNumber, signals_channel::SendError);
+
+
 /// Tells the thread what to do when the user is installing or uninstalling our handling.
 enum Control {
     /// The user has invoked installing our signal handling and has provided a channel to send
     /// notifications on.
     Installed {
         /// The channel to send notifications of signals received.
-        notify: Box<dyn signals_channel::Sender>,
+        notify:        Box<dyn signals_channel::Sender>,
+        /// How many notifications to send per receipt.
+        policy:        CoalescePolicy,
+        /// Called when sending a notification fails.
+        on_send_error: Option<OnSendError>,
     },
     /// The user has invoked uninstalling our signal handling.
     Uninstalled,
+    /// The user has invoked uninstalling our signal handling and wants the receipts, that were
+    /// counted before that, to still be sent as final notifications before the notifications
+    /// channel is disconnected.
+    Drain,
 }
 
 
@@ -49,9 +64,25 @@ pub enum DelegatesState {
     /// The thread's state when installed.
     Active {
         /// The channel to send notifications of signals received.
-        notify:     Box<dyn signals_channel::Sender>,
+        notify:        Box<dyn signals_channel::Sender>,
+        /// How many notifications to send per receipt.
+        policy:        CoalescePolicy,
+        /// Called when sending a notification fails.
+        on_send_error: Option<OnSendError>,
         /// The internal channel to control the thread.  Same channel as when `Dormant`.
-        controller: mpsc::Receiver<Control>,
+        controller:    mpsc::Receiver<Control>,
+    },
+    /// The thread's state for the single final iteration, after being told to drain, that sends
+    /// the notifications of the remaining receipts.  Becomes `Dormant` after that.
+    Draining {
+        /// The channel to send the final notifications on.  Dropped after those are sent.
+        notify:        Box<dyn signals_channel::Sender>,
+        /// How many notifications to send per receipt.  Same as when `Active`.
+        policy:        CoalescePolicy,
+        /// Called when sending a notification fails.  Same as when `Active`.
+        on_send_error: Option<OnSendError>,
+        /// The internal channel to control the thread.  Same channel as when `Dormant`.
+        controller:    mpsc::Receiver<Control>,
     },
 }
 
@@ -59,12 +90,7 @@ pub enum DelegatesState {
 impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
     const NAME: &'static str = {
         let name = "signals-receipt";
-        if name.len() < {
-            // Linux's limit of 16 (including a nul) is the smallest among OSs.
-            const LINUX_LIMIT: usize = 16;
-            const SMALLEST_LIMIT_AMONG: usize = LINUX_LIMIT;
-            SMALLEST_LIMIT_AMONG
-        } {
+        if is_valid_name(name) {
             name
         } else {
             panic!("limited by `pthread_setname_np` or the OS");
@@ -85,8 +111,10 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
         4 * needed
     };
 
+    /// The stack size and name of `config` override [`Self::STACK_SIZE`] and [`Self::NAME`], if
+    /// given.  The name must already be validated.
     #[allow(clippy::unwrap_in_result)]
-    pub(super) fn new() -> Result<Self, NewError> {
+    pub(super) fn new(config: InstallConfig) -> Result<Self, NewError> {
         // The internal channel to control the thread.  It's unbounded, so that sending on it will
         // never block, but its amount should stay very small when the user is not pathological.
         let (controller_sender, controller_receiver) = mpsc::channel();
@@ -114,8 +142,8 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
         };
 
         let join_handle = thread::Builder::new()
-            .name(Self::NAME.to_owned())
-            .stack_size(Self::STACK_SIZE)
+            .name(config.get_thread_name().unwrap_or(Self::NAME).to_owned())
+            .stack_size(config.get_stack_size().unwrap_or(Self::STACK_SIZE))
             .spawn(Self::main(controller_receiver))
             // Only an OS-level failure to create a thread could cause this to fail.
             .map_err(NewError::ThreadCreateFailed)?;
@@ -134,10 +162,12 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
         || {
             // Initially, wait until told to proceed, to ensure that the operation that created
             // this thread has completed its resetting of the global state of `R`.
-            let notify = match controller.recv() {
-                Ok(Control::Installed { notify }) => notify,
+            let (notify, policy, on_send_error) = match controller.recv() {
+                Ok(Control::Installed { notify, policy, on_send_error }) =>
+                    (notify, policy, on_send_error),
                 #[allow(clippy::unreachable)] // It's impossible for this to panic.
-                Ok(Control::Uninstalled) | Err(mpsc::RecvError) => unreachable!(),
+                Ok(Control::Uninstalled | Control::Drain) | Err(mpsc::RecvError) =>
+                    unreachable!(),
             };
 
             let () = R::consume_loop_with(
@@ -153,7 +183,7 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
                 false,
                 // Pass the channels to the loop to pass to our `Self::control` callback and our
                 // `Self::handler` delegates.
-                DelegatesState::Active { notify, controller },
+                DelegatesState::Active { notify, policy, on_send_error, controller },
                 (),
             );
         }
@@ -171,15 +201,31 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
             .expect("controller channel is always connected");
     }
 
-    pub(super) fn installed(&self, notify: Box<dyn signals_channel::Sender>) {
-        self.send(Control::Installed { notify });
+    pub(super) fn installed(
+        &self,
+        notify: Box<dyn signals_channel::Sender>,
+        policy: CoalescePolicy,
+        on_send_error: Option<OnSendError>,
+    ) {
+        self.send(Control::Installed { notify, policy, on_send_error });
     }
 
     pub(super) fn uninstalled(&self) {
         self.send(Control::Uninstalled);
         // Ensure that the "signals-receipt" thread wakes to see our `Uninstalled` message, in
         // case that thread is blocked waiting on the semaphore (which is the most likely case).
-        let r = self.semaphore.post();
+        Self::wake(self.semaphore);
+    }
+
+    /// Like [`Self::uninstalled`], but the thread first sends notifications of any remaining
+    /// receipts, and then it disconnects the notifications channel by dropping its sender.
+    pub(super) fn drain(&self) {
+        self.send(Control::Drain);
+        Self::wake(self.semaphore);
+    }
+
+    fn wake(semaphore: SemaphoreRef<'_>) {
+        let r = semaphore.post();
         // This `.post()` can only fail if the semaphore's value is maxed, in which case the
         // thread is already being woken.
         if r.is_err() {
@@ -198,9 +244,13 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
         // Disconnect the controller channel, to ensure the thread wakes (because it could be
         // blocked on this channel now), to see that it must finish.
         drop(self.controller);
-        // Wait for the thread to finish, only after having dropped our controller.  (It's
-        // unnecessary, here, to deal with the possibility that the thread panicked.)
-        self.join_handle.join().ok();
+        // Wait for the thread to finish, only after having dropped our controller.  It could only
+        // have panicked if the user's `Sender` or `on_send_error` did, in which case that was
+        // already reported, and so its payload is just discarded, instead of propagating it here
+        // which would poison our caller's state.
+        if let Err(payload) = self.join_handle.join() {
+            drop(payload);
+        }
     }
 
     /// Send, on the `notify` channel, notification of receipt of a signal.  This is the delegate
@@ -216,8 +266,10 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
     pub fn handler(receipt: &mut Receipt<u64, (), DelegatesState>) {
         let receipt = &*receipt; // As immutable.
 
-        let notify = match receipt.get_state_ref() {
-            DelegatesState::Active { notify, .. } => notify,
+        let (notify, policy, on_send_error) = match receipt.get_state_ref() {
+            DelegatesState::Active { notify, policy, on_send_error, .. }
+            | DelegatesState::Draining { notify, policy, on_send_error, .. } =>
+                (notify, *policy, *on_send_error),
             // Our `Self::control` callback blocks our "signals-receipt" thread until a
             // notifications channel has been provided, before that thread can call us, and so
             // it's impossible for this to panic.
@@ -225,10 +277,11 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
             DelegatesState::Dormant { .. } => unreachable!(),
         };
 
-        // If `receipt.cur_count >= 2`, we don't send more than one notification on the channel.
-        // This coalesces multiple of the same that were received within the short time span of a
-        // single iteration of the consuming loop.  This is deemed acceptable because the OS might
-        // already be doing its own coalescing and so you can't rely on that to not happen anyway.
+        // If `receipt.cur_count >= 2`, by default we don't send more than one notification on the
+        // channel.  This coalesces multiple of the same that were received within the short time
+        // span of a single iteration of the consuming loop.  This is deemed acceptable because
+        // the OS might already be doing its own coalescing and so you can't rely on that
+        // to not happen anyway.  But the user can choose a policy that sends more.
 
         // It's ok if this blocks waiting to send on the channel.  This honors the capacity of the
         // channel that the user chose to install.  It's the "signals-receipt" thread that might
@@ -238,9 +291,18 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
         // `crate::handler` will still run when a signal is delivered and will still increment
         // their counters), and so the processing of further signals will still be done after we
         // wake up when the channel is ready.
-        notify.send(receipt.sig_num).ok();
-        // If the send fails (because the channel either: is disconnected, is full and chooses to
-        // not block, or chooses to ignore this signal number), we just ignore that.
+        for _ in 0 .. policy.amount(receipt.cur_count) {
+            // If the send fails (because the channel either: is disconnected, is full and chooses
+            // to not block, or chooses to ignore this signal number), we just ignore that, unless
+            // the user wants to know, but we don't try sending the rest, since those would fail
+            // the same.
+            if let Err(error) = notify.send(receipt.sig_num) {
+                if let Some(on_send_error) = on_send_error {
+                    on_send_error(receipt.sig_num, error);
+                }
+                break;
+            }
+        }
     }
 
     /// Assist with transitioning between installed and uninstalled states.  This is the callback
@@ -254,8 +316,8 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
     /// interrupted.
     #[allow(clippy::missing_inline_in_public_items, clippy::must_use_candidate)]
     pub fn control(state: DelegatesState) -> ControlFlow<(), DelegatesState> {
-        use self::{Control::{Installed, Uninstalled},
-                   DelegatesState::{Active, Dormant}};
+        use self::{Control::{Drain, Installed, Uninstalled},
+                   DelegatesState::{Active, Dormant, Draining}};
         use mpsc::{RecvError,
                    TryRecvError::{Disconnected, Empty}};
         use ControlFlow::{Break, Continue};
@@ -263,9 +325,9 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
         match state {
             // Check if there's a new message telling us what to do.  This is the thread's state
             // when installed.
-            Active { notify, controller } => match controller.try_recv() {
+            Active { notify, policy, on_send_error, controller } => match controller.try_recv() {
                 // There is not any new message.  No change.  This is the most frequent case.
-                Err(Empty) => Continue(Active { notify, controller }),
+                Err(Empty) => Continue(Active { notify, policy, on_send_error, controller }),
                 // We're being told to go dormant - uninstalling was done.
                 Ok(Uninstalled) => {
                     // Disconnect the notifications channel.
@@ -273,26 +335,57 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
                     // Recur to block until re-installed.
                     Self::control(Dormant { controller })
                 },
+                // We're being told to drain - uninstalling was done but the receipts counted
+                // before that must still be notified.  Continue so that `Self::handler` is
+                // called, this iteration, for each signal that has a remaining receipt.
+                Ok(Drain) => {
+                    // Ensure we wake for the next iteration, which will disconnect the
+                    // notifications channel once the final notifications have been sent.
+                    // (`Self::new` ensured the semaphore is initialized.)
+                    if let Ok(semaphore) = R::semaphore().sem_ref() {
+                        Self::wake(semaphore);
+                    }
+                    Continue(Draining { notify, policy, on_send_error, controller })
+                },
                 // Installation of a different notifications channel, to replace the current one.
                 // This message while we're in this state, does not occur actually.
-                Ok(Installed { notify: new_notify }) => {
+                Ok(Installed {
+                    notify: new_notify,
+                    policy: new_policy,
+                    on_send_error: new_on,
+                }) => {
                     debug_assert!(false, "doesn't occur with current design");
-                    Continue(Active { notify: new_notify, controller })
+                    Continue(Active {
+                        notify: new_notify,
+                        policy: new_policy,
+                        on_send_error: new_on,
+                        controller,
+                    })
                 },
                 // If the controller channel is ever disconnected, that means to finish the
                 // thread.
                 Err(Disconnected) => Break(()),
             },
 
+            // The final notifications were sent in the previous iteration.
+            Draining { notify, controller, .. } => {
+                // Disconnect the notifications channel, which tells the receiver that there will
+                // be no more.
+                drop(notify);
+                // Recur to block until re-installed (or finished).
+                Self::control(Dormant { controller })
+            },
+
             // Block our "signals-receipt" thread until told what to do.  This is the thread's
             // state when our signal handling is uninstalled.
             Dormant { controller } => match controller.recv() {
                 // Activation with the channel for sending notifications of signals received.
                 // This occurs when re-installed.
-                Ok(Installed { notify }) => Continue(Active { notify, controller }),
-                // It's already dormant.  No change.  Recur to keep blocking.  This message while
-                // we're in this state, does not occur actually.
-                Ok(Uninstalled) => {
+                Ok(Installed { notify, policy, on_send_error }) =>
+                    Continue(Active { notify, policy, on_send_error, controller }),
+                // It's already dormant.  No change.  Recur to keep blocking.  These messages
+                // while we're in this state, do not occur actually.
+                Ok(Uninstalled | Drain) => {
                     debug_assert!(false, "doesn't occur with current design");
                     Self::control(Dormant { controller })
                 },
@@ -305,6 +398,28 @@ impl<C: SignalsChannel, R: SignalsReceipts> ReceiptsThread<C, R> {
 }
 
 
+/// Whether `name` is acceptable for naming a thread on all OSs: short enough and without a nul.
+pub(super) const fn is_valid_name(name: &str) -> bool {
+    // Linux's limit of 16 (including a nul) is the smallest among OSs.
+    const LINUX_LIMIT: usize = 16;
+    const SMALLEST_LIMIT_AMONG: usize = LINUX_LIMIT;
+
+    let bytes = name.as_bytes();
+    if bytes.len() >= SMALLEST_LIMIT_AMONG {
+        return false;
+    }
+    let mut i = 0;
+    while i < bytes.len() {
+        #[allow(clippy::indexing_slicing)] // `i` is always in bounds.
+        if bytes[i] == 0 {
+            return false;
+        }
+        i = i.saturating_add(1);
+    }
+    true
+}
+
+
 #[derive(Debug)]
 pub(super) enum NewError {
     SemaphoreInitFailed(io::Error),
@@ -1,4 +1,6 @@
+pub(super) mod broadcast;
 pub(super) mod encapsulated;
+pub(super) mod socket;
 
 
 #[cfg(doc)]
@@ -6,7 +8,8 @@ use super::SignalsChannel;
 use crate::SignalNumber;
 use core::fmt::{self, Debug, Display, Formatter};
 extern crate std;
-use std::{error::Error, sync::mpsc};
+use std::{error::Error,
+          sync::{mpsc, Mutex, PoisonError}};
 
 
 /// A sender side of a channel.  The channel may be either bounded or unbounded.
@@ -87,29 +90,147 @@ where
     }
 }
 
+/// Provided for this `crossbeam-channel` type, when the `crossbeam` package feature is enabled,
+/// for both bounded and unbounded channels.  Sending never blocks for this type: if a bounded
+/// channel is full, it won't be sent and the `SendError::Full` error will be returned.
+///
+/// If a `sig_num` value cannot be converted to the chosen `N` type, it won't be sent and the
+/// `SendError::Ignored` error will be returned.
+#[cfg(feature = "crossbeam")]
+impl<N> Sender for crossbeam_channel::Sender<N>
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    #[inline]
+    fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
+        use crossbeam_channel::TrySendError;
+
+        if let Ok(repr) = sig_num.try_into() {
+            self.try_send(repr).map_err(|e| match e {
+                TrySendError::Full(_) => SendError::Full,
+                TrySendError::Disconnected(_) => SendError::Disconnected,
+            })
+        } else {
+            Err(SendError::Ignored)
+        }
+    }
+}
+
+
+/// Provided for this `tokio` channel type, when the `tokio` package feature is enabled, such
+/// that sending blocks (our internal thread, not any async task), to not miss any signals, if the
+/// channel is full.  See [`SignalsChannel::install_tokio`].
+///
+/// If a `sig_num` value cannot be converted to the chosen `N` type, it won't be sent and the
+/// `SendError::Ignored` error will be returned.
+#[cfg(feature = "tokio")]
+impl<N> Sender for tokio::sync::mpsc::Sender<N>
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    #[inline]
+    fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
+        if let Ok(repr) = sig_num.try_into() {
+            // Our internal thread isn't in an async runtime, and so this won't panic.
+            self.blocking_send(repr).or(Err(SendError::Disconnected))
+        } else {
+            Err(SendError::Ignored)
+        }
+    }
+}
+
+/// Provided for this `tokio` channel type, when the `tokio` package feature is enabled.  Sending
+/// never blocks for this type.
+///
+/// If a `sig_num` value cannot be converted to the chosen `N` type, it won't be sent and the
+/// `SendError::Ignored` error will be returned.
+#[cfg(feature = "tokio")]
+impl<N> Sender for tokio::sync::mpsc::UnboundedSender<N>
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    #[inline]
+    fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
+        if let Ok(repr) = sig_num.try_into() {
+            tokio::sync::mpsc::UnboundedSender::send(self, repr).or(Err(SendError::Disconnected))
+        } else {
+            Err(SendError::Ignored)
+        }
+    }
+}
+
+
+/// Adapts a closure to be a [`Sender`].  Only used internally, by
+/// [`SignalsChannel::install_with_fn`].
+pub(super) struct FnSender<F>(Mutex<F>);
+
+impl<F> FnSender<F> {
+    pub(super) fn new(f: F) -> Self { Self(Mutex::new(f)) }
+}
+
+impl<F> Sender for FnSender<F>
+where
+    F: FnMut(SignalNumber) -> Result<(), SendError> + Send + 'static,
+{
+    fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
+        // Our internal thread is the only caller, and so this is never contended.  If the
+        // closure panicked before, that thread is already gone.
+        let mut f = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        f(sig_num)
+    }
+}
+
+/// Want `Debug` for this but without `F: Debug`.
+impl<F> Debug for FnSender<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnSender").finish_non_exhaustive()
+    }
+}
+
 
 /// Error returned by [`Sender::send`] that indicates the way in which the implementer chose to
 /// have that operation fail.  With any of these variants, the notification of the signal was not
 /// sent.
-#[derive(Copy, Clone, Debug)]
-#[allow(clippy::exhaustive_enums)]
+///
+/// More variants might be added in the future, e.g. to give more context about a failure.
+#[non_exhaustive]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SendError {
     /// The channel is disconnected.
     Disconnected,
     /// The channel was full and sending would've blocked.
     Full,
+    /// The channel was still full after the implementer retried sending the given amount of
+    /// times, e.g. with some backoff between, and it gave up instead of blocking.
+    FullAfterRetries(u32),
     /// The implementer chose to not send for whatever reason.
     Ignored,
 }
 
+impl SendError {
+    /// Whether this is [`Self::Full`] or [`Self::FullAfterRetries`].
+    #[must_use]
+    #[inline]
+    pub fn is_full(&self) -> bool { matches!(self, Self::Full | Self::FullAfterRetries(_)) }
+}
+
 impl Display for SendError {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", match self {
-            SendError::Disconnected => "signals-notifications channel is disconnected",
-            SendError::Full => "signals-notifications channel was full, sending would've blocked",
-            SendError::Ignored => "signal wasn't sent, due to implementer choice",
-        })
+        match self {
+            SendError::Disconnected => write!(f, "signals-notifications channel is disconnected"),
+            SendError::Full =>
+                write!(f, "signals-notifications channel was full, sending would've blocked"),
+            SendError::FullAfterRetries(retries) => write!(
+                f,
+                "signals-notifications channel was still full after {retries} retries, sending \
+                 would've blocked"
+            ),
+            SendError::Ignored => write!(f, "signal wasn't sent, due to implementer choice"),
+        }
     }
 }
 
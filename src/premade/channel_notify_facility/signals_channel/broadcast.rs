@@ -0,0 +1,132 @@
+use super::{super::SignalsChannel,
+            encapsulated::{self, Receiver},
+            SendError};
+use crate::SignalNumber;
+use core::{fmt::{self, Debug, Formatter},
+           marker::PhantomData};
+extern crate alloc;
+use alloc::{sync::Arc, vec::Vec};
+extern crate std;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+
+/// Fans-out the signals notifications to any number of receivers, which can be subscribed at any
+/// time, so that multiple subsystems can independently react to the same signals.  This is
+/// returned by [`SignalsChannel::install_broadcast`].
+///
+/// Cloning this gives another handle to the same set of subscribers.
+pub struct Broadcast<N, C> {
+    subscribers: Arc<Subscribers<N>>,
+    _creator:    PhantomData<C>,
+}
+
+struct Subscribers<N> {
+    bound:   Option<usize>,
+    senders: Mutex<Vec<encapsulated::Sender<N>>>,
+}
+
+impl<N> Subscribers<N> {
+    fn acquire(&self) -> MutexGuard<'_, Vec<encapsulated::Sender<N>>> {
+        // The set remains consistent even if another thread panicked while holding the lock,
+        // because it's only modified by pushing and retaining.
+        self.senders.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<N, C: SignalsChannel> Broadcast<N, C>
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    pub(in super::super) fn new(channel_bound: Option<usize>) -> Self {
+        Self {
+            subscribers: Arc::new(Subscribers {
+                bound:   channel_bound,
+                senders: Mutex::new(Vec::new()),
+            }),
+            _creator:    PhantomData,
+        }
+    }
+
+    /// The internal sender that sends to all the subscribers.
+    pub(in super::super) fn sender(&self) -> Sender<N> { Sender(Arc::clone(&self.subscribers)) }
+
+    /// Create a new channel that is sent all the notifications from now on, and return its
+    /// receiving end.  The channel has the bound that was given to
+    /// [`SignalsChannel::install_broadcast`].
+    ///
+    /// Dropping the returned `Receiver` unsubscribes it (once the next notification is
+    /// attempted), without affecting the other subscribers.
+    #[must_use]
+    #[inline]
+    pub fn subscribe(&self) -> Receiver<N, C> {
+        let (sender, receiver) = if let Some(bound) = self.subscribers.bound {
+            encapsulated::bounded(bound)
+        } else {
+            encapsulated::unbounded()
+        };
+        self.subscribers.acquire().push(sender);
+        receiver
+    }
+}
+
+impl<N, C> Clone for Broadcast<N, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { subscribers: Arc::clone(&self.subscribers), _creator: PhantomData }
+    }
+}
+
+/// Want `Debug` for this but without `N: Debug`.
+impl<N, C> Debug for Broadcast<N, C> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broadcast")
+            .field("subscribers", &self.subscribers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Want `Debug` for this but without `N: Debug`.
+impl<N> Debug for Subscribers<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscribers")
+            .field("bound", &self.bound)
+            .field("len", &self.acquire().len())
+            .finish_non_exhaustive()
+    }
+}
+
+
+/// The corresponding other end of the channels of a [`Broadcast`].  Only used internally to send
+/// signals notifications when our handling was installed with
+/// [`SignalsChannel::install_broadcast`].
+pub(in super::super) struct Sender<N>(Arc<Subscribers<N>>);
+
+impl<N> super::Sender for Sender<N>
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    /// Sends to each subscriber in turn, which blocks while any bounded one is full, and prunes
+    /// those that are disconnected.  Only returns [`SendError::Disconnected`] if there are no
+    /// subscribers left, and otherwise succeeds if sent to any.
+    fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
+        let mut result = Err(SendError::Disconnected);
+        self.0.acquire().retain(|subscriber| {
+            let sent = super::Sender::send(subscriber, sig_num);
+            if result.is_err() && sent != Err(SendError::Disconnected) {
+                result = sent;
+            }
+            sent != Err(SendError::Disconnected)
+        });
+        result
+    }
+}
+
+/// Want `Debug` for this but without `N: Debug`.
+impl<N> Debug for Sender<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Sender").field(&self.0).finish()
+    }
+}
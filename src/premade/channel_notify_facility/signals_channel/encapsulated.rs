@@ -1,22 +1,151 @@
 use super::{super::SignalsChannel, SendError};
 use crate::SignalNumber;
-use core::{fmt::{self, Debug, Formatter},
-           marker::PhantomData};
+use core::{cell::Cell,
+           fmt::{self, Debug, Formatter},
+           iter,
+           marker::PhantomData,
+           sync::atomic::{AtomicU64, Ordering::Relaxed},
+           time::Duration};
+extern crate alloc;
+use alloc::sync::Arc;
 extern crate std;
 use std::sync::mpsc;
 
 
 /// The receiving end of a premade signals-notifications channel that knows its creator.  This is
-/// returned by [`SignalsChannel::install`].
+/// returned by [`SignalsChannel::install`] and [`SignalsChannel::install_with_config`].
 ///
 /// This cannot be cloned, and so is single-owner, as needed to ensure disconnection when
 /// giving-up ownership to [`SignalsChannel::uninstall`] or [`SignalsChannel::finish`].
 pub struct Receiver<N, C> {
-    inner:    mpsc::Receiver<N>,
-    _creator: PhantomData<C>,
+    inner:     mpsc::Receiver<N>,
+    /// Holds a notification that [`Self::has_pending`] received but that hasn't been consumed
+    /// yet.
+    lookahead: Cell<Option<N>>,
+    /// The channel back to the sender, when
+    /// [`InstallConfig::acked`](super::super::InstallConfig::acked) was used.
+    acks:      Option<mpsc::Sender<()>>,
+    /// Whether a notification was returned that [`Self::ack`] hasn't been called for yet.
+    unacked:   Cell<bool>,
+    /// Shared with the sender, when
+    /// [`InstallConfig::bounded_lossy`](super::super::InstallConfig::bounded_lossy) was used.
+    dropped:   Option<Arc<AtomicU64>>,
+    _creator:  PhantomData<C>,
+}
+
+impl<N, C> Receiver<N, C> {
+    fn new(
+        inner: mpsc::Receiver<N>,
+        acks: Option<mpsc::Sender<()>>,
+        dropped: Option<Arc<AtomicU64>>,
+    ) -> Self {
+        Self {
+            inner,
+            lookahead: Cell::new(None),
+            acks,
+            unacked: Cell::new(false),
+            dropped,
+            _creator: PhantomData,
+        }
+    }
+
+    /// Note when a notification is returned to the user, so that it may be acknowledged.
+    fn returned<E>(&self, result: Result<N, E>) -> Result<N, E> {
+        if result.is_ok() && self.acks.is_some() {
+            self.unacked.set(true);
+        }
+        result
+    }
+
+    /// Acknowledge that the last notification returned by `self` has been fully handled, which
+    /// allows the next notification to be sent.  Only relevant when
+    /// [`InstallConfig::acked`](super::super::InstallConfig::acked) was used, and otherwise does
+    /// nothing.
+    ///
+    /// Calling this again, without another notification having been returned in between, does
+    /// nothing, and so extra calls can't let multiple notifications be in-flight.
+    #[inline]
+    pub fn ack(&self) {
+        if let Some(acks) = &self.acks {
+            if self.unacked.replace(false) {
+                // If the sender is already gone, there's nothing to acknowledge to.
+                #[allow(clippy::let_underscore_must_use, clippy::let_underscore_untyped)]
+                let _ = acks.send(());
+            }
+        }
+    }
+
+    /// How many notifications were dropped, instead of sent, because the channel was full.  Only
+    /// relevant when
+    /// [`InstallConfig::bounded_lossy`](super::super::InstallConfig::bounded_lossy)
+    /// was used, and otherwise always zero.
+    #[must_use]
+    #[inline]
+    pub fn dropped(&self) -> u64 { self.dropped.as_ref().map_or(0, |d| d.load(Relaxed)) }
+
+    /// Returns whether a notification is pending, without consuming it.  Never blocks.
+    ///
+    /// Because the underlying channel can't peek, this has a one-item lookahead: a pending
+    /// notification is received from the channel and held in `self`, and then it's returned by
+    /// the next [`Self::recv`] or [`Self::try_recv`].  This preserves the order of the
+    /// notifications.  But receiving directly via [`Self::as_ref`] bypasses the held
+    /// notification, and so that shouldn't be mixed with using this.
+    #[must_use]
+    #[inline]
+    pub fn has_pending(&self) -> bool {
+        let lookahead = self.lookahead.take().or_else(|| self.inner.try_recv().ok());
+        let has = lookahead.is_some();
+        self.lookahead.set(lookahead);
+        has
+    }
+
+    /// Like [`mpsc::Receiver::recv`], but first returns the notification held by
+    /// [`Self::has_pending`] if there is one.
+    ///
+    /// # Errors
+    /// If the channel is disconnected and empty.
+    #[inline]
+    pub fn recv(&self) -> Result<N, mpsc::RecvError> {
+        self.returned(self.lookahead.take().map_or_else(|| self.inner.recv(), Ok))
+    }
+
+    /// Like [`mpsc::Receiver::try_recv`], but first returns the notification held by
+    /// [`Self::has_pending`] if there is one.
+    ///
+    /// # Errors
+    /// If the channel is empty or is disconnected and empty.
+    #[inline]
+    pub fn try_recv(&self) -> Result<N, mpsc::TryRecvError> {
+        self.returned(self.lookahead.take().map_or_else(|| self.inner.try_recv(), Ok))
+    }
+
+    /// Like [`mpsc::Receiver::recv_timeout`], but first returns the notification held by
+    /// [`Self::has_pending`] if there is one.
+    ///
+    /// # Errors
+    /// If the channel is disconnected and empty, or if `timeout` elapsed while it was empty.
+    #[inline]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<N, mpsc::RecvTimeoutError> {
+        self.returned(self.lookahead.take().map_or_else(|| self.inner.recv_timeout(timeout), Ok))
+    }
+
+    /// Like [`mpsc::Receiver::iter`], i.e. repeated [`Self::recv`] until the channel is
+    /// disconnected.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = N> + '_ { iter::from_fn(|| self.recv().ok()) }
+
+    /// Like [`mpsc::Receiver::try_iter`], i.e. repeated [`Self::try_recv`] until there are no
+    /// more pending notifications.  Never blocks.
+    #[inline]
+    pub fn try_iter(&self) -> impl Iterator<Item = N> + '_ {
+        iter::from_fn(|| self.try_recv().ok())
+    }
 }
 
 /// Enables users to use `Self` as a receiver.
+///
+/// Note that this bypasses the notification, if any, held by [`Receiver::has_pending`], and that
+/// notifications received via this can't be acknowledged by [`Receiver::ack`].
 impl<N, C> AsRef<mpsc::Receiver<N>> for Receiver<N, C> {
     #[inline]
     fn as_ref(&self) -> &mpsc::Receiver<N> { &self.inner }
@@ -26,7 +155,7 @@ impl<N, C> AsRef<mpsc::Receiver<N>> for Receiver<N, C> {
 impl<N, C> Debug for Receiver<N, C> {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Receiver").field("inner", &self.inner).finish()
+        f.debug_struct("Receiver").field("inner", &self.inner).finish_non_exhaustive()
     }
 }
 
@@ -36,6 +165,16 @@ impl<N, C> Debug for Receiver<N, C> {
 pub(in super::super) enum Sender<N> {
     Bounded(mpsc::SyncSender<N>),
     Unbounded(mpsc::Sender<N>),
+    /// Bounded to 1, and each send waits for the acknowledgment of it on `acks`.
+    Acked {
+        sender: mpsc::SyncSender<N>,
+        acks:   mpsc::Receiver<()>,
+    },
+    /// Bounded, but, instead of blocking when full, the new notification is dropped and counted.
+    Lossy {
+        sender:  mpsc::SyncSender<N>,
+        dropped: Arc<AtomicU64>,
+    },
 }
 
 impl<N> super::Sender for Sender<N>
@@ -46,10 +185,23 @@ where
     fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
         if let Ok(repr) = sig_num.try_into() {
             match self {
-                Sender::Bounded(s) => s.send(repr),
-                Sender::Unbounded(s) => s.send(repr),
+                Sender::Bounded(s) => s.send(repr).or(Err(SendError::Disconnected)),
+                Sender::Unbounded(s) => s.send(repr).or(Err(SendError::Disconnected)),
+                // After sending, block until the receiver has fully handled this one.  The
+                // receiver being dropped wakes this, the same as for it being disconnected.
+                Sender::Acked { sender, acks } => sender
+                    .send(repr)
+                    .map_err(drop)
+                    .and_then(|()| acks.recv().map_err(drop))
+                    .or(Err(SendError::Disconnected)),
+                Sender::Lossy { sender, dropped } => sender.try_send(repr).map_err(|e| match e {
+                    mpsc::TrySendError::Full(_) => {
+                        dropped.fetch_add(1, Relaxed);
+                        SendError::Full
+                    },
+                    mpsc::TrySendError::Disconnected(_) => SendError::Disconnected,
+                }),
             }
-            .or(Err(SendError::Disconnected))
         } else {
             Err(SendError::Ignored)
         }
@@ -62,6 +214,8 @@ impl<N> Debug for Sender<N> {
         let (name, field): (_, &dyn Debug) = match self {
             Sender::Bounded(s) => ("Bounded", s),
             Sender::Unbounded(s) => ("Unbounded", s),
+            Sender::Acked { sender, .. } => ("Acked", sender),
+            Sender::Lossy { sender, .. } => ("Lossy", sender),
         };
         f.debug_tuple(name).field(field).finish()
     }
@@ -75,7 +229,7 @@ where
     N: Send + 'static,
 {
     let (sender, receiver) = mpsc::sync_channel(bound);
-    (Sender::Bounded(sender), Receiver { inner: receiver, _creator: PhantomData })
+    (Sender::Bounded(sender), Receiver::new(receiver, None, None))
 }
 
 /// Creates a new premade signals-notifications channel that is unbounded.
@@ -85,5 +239,30 @@ where
     N: Send + 'static,
 {
     let (sender, receiver) = mpsc::channel();
-    (Sender::Unbounded(sender), Receiver { inner: receiver, _creator: PhantomData })
+    (Sender::Unbounded(sender), Receiver::new(receiver, None, None))
+}
+
+/// Creates a new premade signals-notifications channel that is bounded to 1 and where each
+/// notification must be acknowledged by [`Receiver::ack`] before the next is sent.
+pub(in super::super) fn acked<N, C: SignalsChannel>() -> (Sender<N>, Receiver<N, C>)
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let (acks_sender, acks) = mpsc::channel();
+    (Sender::Acked { sender, acks }, Receiver::new(receiver, Some(acks_sender), None))
+}
+
+/// Creates a new premade signals-notifications channel that is bounded and where, when it's full,
+/// a notification is dropped, and counted for [`Receiver::dropped`], instead of blocking.
+pub(in super::super) fn lossy<N, C: SignalsChannel>(bound: usize) -> (Sender<N>, Receiver<N, C>)
+where
+    SignalNumber: TryInto<N>,
+    N: Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(bound);
+    let dropped = Arc::new(AtomicU64::new(0));
+    let receiver = Receiver::new(receiver, None, Some(Arc::clone(&dropped)));
+    (Sender::Lossy { sender, dropped }, receiver)
 }
@@ -0,0 +1,49 @@
+use super::{SendError, Sender};
+use crate::SignalNumber;
+extern crate std;
+use std::{io::ErrorKind, os::unix::net::UnixDatagram};
+
+
+/// Sends each signal's number over a Unix datagram socket, e.g. to a supervisor process written
+/// in another language, which can be given to [`SignalsChannel::install_with_outside_channel`].
+///
+/// Each notification is a single datagram of the signal number as a 4-byte little-endian signed
+/// integer.
+///
+/// Sending blocks if the socket is blocking and its buffer is full.  If it's been set as
+/// non-blocking, [`SendError::Full`] is returned instead.
+///
+/// [`SignalsChannel::install_with_outside_channel`]:
+/// super::super::SignalsChannel::install_with_outside_channel
+#[derive(Debug)]
+pub struct SocketSender(UnixDatagram);
+
+impl SocketSender {
+    /// `socket` must already be connected to its peer.
+    #[must_use]
+    #[inline]
+    pub fn new(socket: UnixDatagram) -> Self { Self(socket) }
+
+    /// The socket that is sent on.
+    #[must_use]
+    #[inline]
+    pub fn socket(&self) -> &UnixDatagram { &self.0 }
+}
+
+impl Sender for SocketSender {
+    #[inline]
+    #[allow(clippy::little_endian_bytes)] // The wire format is defined as little-endian.
+    fn send(&self, sig_num: SignalNumber) -> Result<(), SendError> {
+        let datagram = sig_num.to_le_bytes();
+        loop {
+            match self.0.send(&datagram) {
+                Ok(_) => break Ok(()),
+                // Delivery of a signal to our thread interrupted the sending.  Retry.
+                Err(e) if e.kind() == ErrorKind::Interrupted => {},
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Err(SendError::Full),
+                // E.g. broken pipe, connection reset or refused, or not connected.
+                Err(_) => break Err(SendError::Disconnected),
+            }
+        }
+    }
+}
@@ -1,14 +1,26 @@
 use self::Inner::{Dormant, Installed, Nothing};
 use super::{receipts_thread::{self, ReceiptsThread},
-            signals_channel::{self, encapsulated::Receiver},
+            signals_channel::{self, encapsulated::Receiver, SendError},
             SignalsChannel, SignalsReceipts};
 use crate::SignalNumber;
 use core::{fmt::{self, Debug, Display, Formatter},
-           mem};
+           mem,
+           time::Duration};
 extern crate std;
 use std::{error::Error,
           prelude::rust_2021::*,
-          sync::{Mutex, MutexGuard}};
+          sync::mpsc::RecvTimeoutError,
+          time::Instant};
+
+
+// The type of mutex is chosen by the package feature, and the rest is the same either way.
+cfg_if::cfg_if! { if #[cfg(feature = "parking_lot")] {
+    type Mutex<T> = parking_lot::Mutex<T>;
+    type MutexGuard<'l, T> = parking_lot::MutexGuard<'l, T>;
+} else {
+    type Mutex<T> = std::sync::Mutex<T>;
+    type MutexGuard<'l, T> = std::sync::MutexGuard<'l, T>;
+} }
 
 
 /// The global state of the facility's signal handling.  Manages the installing, uninstalling, and
@@ -16,8 +28,29 @@ use std::{error::Error,
 ///
 /// Only intended to be used by the [`channel_notify_facility`](crate::channel_notify_facility!)
 /// macro.
-#[derive(Debug)]
-pub struct State<C, R>(Mutex<Inner<C, R>>);
+pub struct State<C, R> {
+    inner:    Mutex<Inner<C, R>>,
+    /// Called whenever the state changes.  Only accessed while `inner` is locked, or by
+    /// [`State::set_observer`].
+    observer: Mutex<Option<Observer>>,
+}
+
+type Observer = Box<dyn Fn(FacilityStateKind) + Send + Sync>;
+
+/// Which state the facility is in.  Given to the observer set by
+/// [`SignalsChannel::set_state_observer`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum FacilityStateKind {
+    /// Our handling is not installed at all, and the "signals-receipt" thread doesn't exist.
+    /// I.e. "finished" (or never installed).
+    Nothing,
+    /// Our handling is currently installed.
+    Installed,
+    /// Our handling was uninstalled after having been installed, and the "signals-receipt"
+    /// thread is kept for re-installing.
+    Dormant,
+}
 
 /// Whether our handling is or was installed.
 #[derive(Default, Debug)]
@@ -42,18 +75,31 @@ enum Inner<C, R> {
 
 
 impl<C: SignalsChannel, R: SignalsReceipts> Inner<C, R> {
+    fn kind(&self) -> FacilityStateKind {
+        match self {
+            Nothing => FacilityStateKind::Nothing,
+            Installed { .. } => FacilityStateKind::Installed,
+            Dormant { .. } => FacilityStateKind::Dormant,
+        }
+    }
+
     fn do_install<T: signals_channel::Sender>(
         &mut self,
         notify: T,
         is_encapsulated: bool,
+        config: InstallConfig,
     ) -> Result<(), InstallError<T>> {
+        if !config.thread_name.map_or(true, receipts_thread::is_valid_name) {
+            return Err(InstallError::InvalidThreadName { unused_notify: notify });
+        }
+
         // Need a thread to run the processing of the receipts of signals, so that the delegating,
         // to our `ReceiptsThread::handler`, is run in a normal context where it can do whatever
         // (not in the interrupt context of a signal handler which would be extremely limited by
         // async-signal-safety).
         let next = match mem::take(self) {
             // Fresh installing.
-            Nothing => Ok(ReceiptsThread::new()?),
+            Nothing => Ok(ReceiptsThread::new(config)?),
 
             // Re-installing.
             Dormant { receipts_thread } => {
@@ -65,7 +111,8 @@ impl<C: SignalsChannel, R: SignalsReceipts> Inner<C, R> {
                     // ever happen, but, if this ever does, to be more resilient, we'll create a
                     // new one.
                     drop(receipts_thread);
-                    ReceiptsThread::new()? // If early error return, `self` is left as `Nothing`.
+                    // If early error return, `self` is left as `Nothing`.
+                    ReceiptsThread::new(config)?
                 })
             },
 
@@ -87,7 +134,11 @@ impl<C: SignalsChannel, R: SignalsReceipts> Inner<C, R> {
                 // callback to pass to `ReceiptsThread::handler`, only after the counters were
                 // reset (so that the thread won't access them until then).  This makes the thread
                 // ready and start its processing.
-                receipts_thread.installed(Box::new(notify));
+                receipts_thread.installed(
+                    Box::new(notify),
+                    config.coalesce,
+                    config.on_send_error,
+                );
 
                 (Installed { receipts_thread, is_encapsulated }, Ok(()))
             },
@@ -105,24 +156,22 @@ impl<C: SignalsChannel, R: SignalsReceipts> Inner<C, R> {
         &mut self,
         notify: T,
     ) -> Result<(), InstallError<T>> {
-        self.do_install(notify, false)
+        self.do_install(notify, false, InstallConfig::default())
     }
 
-    fn install<N>(
-        &mut self,
-        channel_bound: Option<usize>,
-    ) -> Result<Receiver<N, C>, InstallError<()>>
+    fn install<N>(&mut self, config: InstallConfig) -> Result<Receiver<N, C>, InstallError<()>>
     where
         SignalNumber: TryInto<N>,
         N: Send + 'static,
     {
-        let (sender, receiver) = if let Some(bound) = channel_bound {
-            signals_channel::encapsulated::bounded(bound)
-        } else {
-            signals_channel::encapsulated::unbounded()
+        let (sender, receiver) = match config.channel {
+            ChannelKind::Unbounded => signals_channel::encapsulated::unbounded(),
+            ChannelKind::Bounded(bound) => signals_channel::encapsulated::bounded(bound),
+            ChannelKind::BoundedLossy(bound) => signals_channel::encapsulated::lossy(bound),
+            ChannelKind::Acked => signals_channel::encapsulated::acked(),
         };
 
-        Ok(self.do_install(sender, true).map(|()| receiver)?)
+        Ok(self.do_install(sender, true, config).map(|()| receiver)?)
     }
 
     fn do_uninstall(&mut self, expect_encapsulated: bool) -> Result<(), UninstallError> {
@@ -198,6 +247,66 @@ impl<C: SignalsChannel, R: SignalsReceipts> Inner<C, R> {
         self.uninstall(receiver).or_else(Result::from)?;
         self.do_finish()
     }
+
+    fn finish_drained<N>(
+        &mut self,
+        receiver: Receiver<N, C>,
+        timeout: Duration,
+        mut sink: impl FnMut(N),
+    ) -> Result<(), FinishError> {
+        match mem::take(self) {
+            Installed { receipts_thread, is_encapsulated: true } => {
+                // Stop counting signal deliveries, so that the remaining receipts are bounded.
+                R::uninstall_all_handlers();
+
+                // Tell the "signals-receipt" thread to send the final notifications and to then
+                // disconnect the channel.  (If the thread somehow finished outside our control,
+                // its sender was dropped and so the channel is already disconnected.)
+                if receipts_thread.is_alive() {
+                    receipts_thread.drain();
+                }
+
+                // Give the caller everything that was and will be sent, until the thread
+                // disconnects the channel.  Done here, by us, instead of requiring the caller to
+                // do it on another thread, to avoid the thread blocking forever on sending on a
+                // full bounded channel.
+                let deadline = Instant::now().checked_add(timeout);
+                let timed_out = loop {
+                    let remaining =
+                        deadline.map_or(timeout, |d| d.saturating_duration_since(Instant::now()));
+                    match receiver.recv_timeout(remaining) {
+                        Ok(notification) => {
+                            sink(notification);
+                            // In case `InstallConfig::acked` was used.
+                            receiver.ack();
+                        },
+                        Err(RecvTimeoutError::Disconnected) => break false,
+                        Err(RecvTimeoutError::Timeout) => break true,
+                    }
+                };
+                // Disconnect the channel, which wakes the thread if it's still blocked on sending
+                // (only possible if timed-out), so that it can finish next.
+                drop(receiver);
+
+                *self = Dormant { receipts_thread };
+                self.do_finish()?;
+                if timed_out { Err(FinishError::DrainTimedOut) } else { Ok(()) }
+            },
+            // Let `Self::finish` handle these the same as it does.
+            other @ (Installed { is_encapsulated: false, .. } | Dormant { .. } | Nothing) => {
+                *self = other;
+                self.finish(receiver)
+            },
+        }
+    }
+}
+
+
+impl<C: Debug, R: Debug> Debug for State<C, R> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("State").field("inner", &self.inner).finish_non_exhaustive()
+    }
 }
 
 
@@ -206,17 +315,50 @@ impl<C: SignalsChannel, R: SignalsReceipts> State<C, R> {
     #[must_use]
     #[inline]
     #[allow(clippy::new_without_default)]
-    pub const fn new() -> Self { Self(Mutex::new(Nothing)) }
+    pub const fn new() -> Self { Self { inner: Mutex::new(Nothing), observer: Mutex::new(None) } }
+
+    #[cfg(feature = "parking_lot")]
+    fn acquire_inner(&self) -> MutexGuard<'_, Inner<C, R>> { self.inner.lock() }
 
+    #[cfg(not(feature = "parking_lot"))]
     fn acquire_inner(&self) -> MutexGuard<'_, Inner<C, R>> {
         #![allow(clippy::expect_used)]
-        self.0.lock()
+        self.inner.lock()
             // Only invalid signal numbers being given by the user, which would cause our other
             // methods to panic, could lead to our mutex becoming poisoned.  Those are given as
             // statically declared, and, once that's known to be correct, this will never panic.
             .expect("mutex should not become poisoned")
     }
 
+    #[cfg(feature = "parking_lot")]
+    fn acquire_observer(&self) -> MutexGuard<'_, Option<Observer>> { self.observer.lock() }
+
+    #[cfg(not(feature = "parking_lot"))]
+    fn acquire_observer(&self) -> MutexGuard<'_, Option<Observer>> {
+        // If an observer panicked, it's still alright to use or replace it.
+        self.observer.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Do the given mutation of the state, and call the observer if that changed the state.
+    fn mutate<T>(&self, mutation: impl FnOnce(&mut Inner<C, R>) -> T) -> T {
+        let mut inner = self.acquire_inner();
+        let before = inner.kind();
+        let result = mutation(&mut inner);
+        let after = inner.kind();
+        if after != before {
+            if let Some(observer) = &*self.acquire_observer() {
+                // Still holding the `inner` lock, so transitions are observed in order.
+                observer(after);
+            }
+        }
+        result
+    }
+
+    #[inline]
+    pub fn set_observer(&self, observer: impl Fn(FacilityStateKind) + Send + Sync + 'static) {
+        *self.acquire_observer() = Some(Box::new(observer));
+    }
+
     #[must_use]
     #[inline]
     pub fn is_installed(&self) -> bool { matches!(&*self.acquire_inner(), Installed { .. }) }
@@ -238,7 +380,22 @@ impl<C: SignalsChannel, R: SignalsReceipts> State<C, R> {
         SignalNumber: TryInto<N>,
         N: Send + 'static,
     {
-        self.acquire_inner().install(channel_bound)
+        let config = InstallConfig::default();
+        self.install_with_config(
+            channel_bound.map_or(config, |bound| config.channel_bound(bound)),
+        )
+    }
+
+    #[inline]
+    pub fn install_with_config<N>(
+        &self,
+        config: InstallConfig,
+    ) -> Result<Receiver<N, C>, InstallError<()>>
+    where
+        SignalNumber: TryInto<N>,
+        N: Send + 'static,
+    {
+        self.mutate(|inner| inner.install(config))
     }
 
     #[inline]
@@ -246,32 +403,194 @@ impl<C: SignalsChannel, R: SignalsReceipts> State<C, R> {
         &self,
         notify: T,
     ) -> Result<(), InstallError<T>> {
-        self.acquire_inner().install_with_outside_channel(notify)
+        self.mutate(|inner| inner.install_with_outside_channel(notify))
     }
 
     #[inline]
     pub fn uninstall<N>(&self, receiver: Receiver<N, C>) -> Result<(), UninstallError> {
-        self.acquire_inner().uninstall(receiver)
+        self.mutate(|inner| inner.uninstall(receiver))
     }
 
     #[inline]
     pub fn uninstall_with_outside_channel(&self) -> Result<(), UninstallError> {
-        self.acquire_inner().uninstall_with_outside_channel()
+        self.mutate(Inner::uninstall_with_outside_channel)
     }
 
     #[inline]
     pub fn finish<N>(&self, receiver: Receiver<N, C>) -> Result<(), FinishError> {
-        self.acquire_inner().finish(receiver)
+        self.mutate(|inner| inner.finish(receiver))
     }
 
     #[inline]
     pub fn finish_with_outside_channel(&self) -> Result<(), FinishError> {
-        self.acquire_inner().finish_with_outside_channel()
+        self.mutate(Inner::finish_with_outside_channel)
     }
+
+    #[inline]
+    pub fn finish_drained<N>(
+        &self,
+        receiver: Receiver<N, C>,
+        timeout: Duration,
+        sink: impl FnMut(N),
+    ) -> Result<(), FinishError> {
+        self.mutate(|inner| inner.finish_drained(receiver, timeout, sink))
+    }
+}
+
+
+/// Options for [`SignalsChannel::install_with_config`].  The default is the same as
+/// [`SignalsChannel::install`] with `None`.
+#[derive(Copy, Clone, Debug, Default)]
+#[must_use]
+pub struct InstallConfig {
+    channel:       ChannelKind,
+    stack_size:    Option<usize>,
+    thread_name:   Option<&'static str>,
+    coalesce:      CoalescePolicy,
+    on_send_error: Option<fn(SignalNumber, SendError)>,
 }
 
+impl InstallConfig {
+    /// Like the `channel_bound` argument of [`SignalsChannel::install`].  This replaces any
+    /// previous [`Self::bounded_lossy`] or [`Self::acked`], and vice versa.
+    #[inline]
+    pub fn channel_bound(mut self, bound: usize) -> Self {
+        self.channel = ChannelKind::Bounded(bound);
+        self
+    }
+
+    /// Like [`Self::channel_bound`], but the internal "signals-receipt" thread never blocks on
+    /// sending: when the channel is full, the new notification is dropped instead, and counted
+    /// for [`Receiver::dropped`].  E.g. for low-latency processes where a slow receiver must
+    /// never stall the processing of further signals.
+    ///
+    /// The receipts of signals are still counted, and so a dropped notification is only a missed
+    /// notification, not a lost delivery, but the receiver can't know which signal it was for.
+    /// (A [`SendError::Full`] is also given to [`Self::on_send_error`], if set.)  `bound` should
+    /// be positive, because a zero-bound channel would drop every notification that the receiver
+    /// isn't already blocked waiting for.
+    #[inline]
+    pub fn bounded_lossy(mut self, bound: usize) -> Self {
+        self.channel = ChannelKind::BoundedLossy(bound);
+        self
+    }
 
-/// Error returned by [`SignalsChannel::install`] and
+    /// The strictest backpressure: at most one notification is in-flight, and the next is not
+    /// sent until [`Receiver::ack`] is called for the previous, for when overlapping handling of
+    /// signals is unacceptable.
+    ///
+    /// This is distinct from the blocking-send of a bounded channel, which only waits for there
+    /// to be space in the channel, not for the receiver to have finished handling.  Receipts of
+    /// signals are still counted while waiting for the acknowledgment, and so none are lost, but
+    /// the notifications of those are delayed by however long the handling takes, and multiple
+    /// receipts of the same signal number meanwhile are coalesced into one notification (or as
+    /// [`Self::coalesce`] says, but still sent one at a time).
+    ///
+    /// Uninstalling or finishing, which disconnects the channel, also stops the waiting for an
+    /// acknowledgment.
+    #[inline]
+    pub fn acked(mut self) -> Self {
+        self.channel = ChannelKind::Acked;
+        self
+    }
+
+    /// The size, in bytes, of the stack of the internal "signals-receipt" thread, instead of the
+    /// small size that is otherwise used because it's all that the thread itself needs.  E.g. on
+    /// an OS without over-commit, where a custom [`Sender`](signals_channel::Sender) needs more.
+    ///
+    /// This only takes effect when the thread is created, i.e. not when re-installing after
+    /// uninstalling (which reuses the dormant thread), but again after finishing.
+    #[inline]
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// The name of the internal thread, instead of `"signals-receipt"`.  E.g. to distinguish the
+    /// threads of multiple facilities in a process, when debugging.
+    ///
+    /// It must be less than 16 bytes, which is the smallest limit among OSs (including a
+    /// terminating nul), and it must not contain a nul, else installing fails with
+    /// [`InstallError::InvalidThreadName`].  This takes effect like [`Self::stack_size`] does.
+    #[inline]
+    pub fn thread_name(mut self, name: &'static str) -> Self {
+        self.thread_name = Some(name);
+        self
+    }
+
+    /// How many notifications to send for a signal that was received multiple times between
+    /// iterations of the internal thread.  Unlike [`Self::stack_size`], this takes effect every
+    /// time that installing is done.
+    #[inline]
+    pub fn coalesce(mut self, policy: CoalescePolicy) -> Self {
+        self.coalesce = policy;
+        self
+    }
+
+    /// Called, on the internal "signals-receipt" thread, when sending a notification fails,
+    /// instead of that being silently ignored.  E.g. to count the notifications that were dropped
+    /// because a custom [`Sender`](signals_channel::Sender) returned [`SendError::Full`] (or
+    /// [`SendError::FullAfterRetries`], which also tells how hard it tried), to detect when the
+    /// bound of its channel is too small.
+    ///
+    /// This takes effect like [`Self::coalesce`] does.  Since it's called in a normal context
+    /// (not in a signal handler), it can do whatever, but blocking in it delays the processing
+    /// of further signals.
+    #[inline]
+    pub fn on_send_error(mut self, callback: fn(SignalNumber, SendError)) -> Self {
+        self.on_send_error = Some(callback);
+        self
+    }
+
+    pub(super) fn get_stack_size(&self) -> Option<usize> { self.stack_size }
+
+    pub(super) fn get_thread_name(&self) -> Option<&'static str> { self.thread_name }
+}
+
+
+/// Which kind of channel [`SignalsChannel::install_with_config`] creates.
+#[derive(Copy, Clone, Debug, Default)]
+enum ChannelKind {
+    #[default]
+    Unbounded,
+    Bounded(usize),
+    BoundedLossy(usize),
+    Acked,
+}
+
+
+/// How many notifications are sent for a signal whose receipt was counted multiple times when
+/// the internal "signals-receipt" thread processed it, i.e. when it was received multiple times
+/// within the short time span of a single iteration of that thread.  See
+/// [`InstallConfig::coalesce`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[non_exhaustive]
+pub enum CoalescePolicy {
+    /// Send only one notification, regardless of the count.  This is the default, because the OS
+    /// might already be doing its own coalescing and so you can't rely on that to not happen
+    /// anyway.
+    #[default]
+    One,
+    /// Send as many notifications as the count.  Note that this can block the thread longer,
+    /// when the channel is bounded, since it sends more.
+    PerCount,
+    /// Like `PerCount`, but send at most this many (and at least one).
+    UpTo(usize),
+}
+
+impl CoalescePolicy {
+    /// How many notifications to send for a receipt with `count`.
+    pub(super) fn amount(self, count: u64) -> u64 {
+        match self {
+            Self::One => 1,
+            Self::PerCount => count,
+            Self::UpTo(limit) => count.min(limit.try_into().unwrap_or(u64::MAX)).max(1),
+        }
+    }
+}
+
+
+/// Error returned by [`SignalsChannel::install`], [`SignalsChannel::install_with_config`], and
 /// [`SignalsChannel::install_with_outside_channel`].
 #[non_exhaustive]
 #[derive(Debug)]
@@ -286,6 +605,11 @@ pub enum InstallError<T> {
     /// possible causes of this are not guaranteed as stable, but the cause can still be accessed
     /// via [`Error::source`].
     ThreadCreateFailed(Box<dyn Error + Send + Sync>),
+    /// The name given by [`InstallConfig::thread_name`] is too long or contains a nul.
+    InvalidThreadName {
+        /// Same as for `AlreadyInstalled`.
+        unused_notify: T,
+    },
 }
 
 impl<T> From<receipts_thread::NewError> for InstallError<T> {
@@ -301,6 +625,8 @@ impl<T: signals_channel::Sender> From<InstallError<T>> for InstallError<()> {
         match value {
             InstallError::AlreadyInstalled { .. } => Self::AlreadyInstalled { unused_notify: () },
             InstallError::ThreadCreateFailed(e) => Self::ThreadCreateFailed(e),
+            InstallError::InvalidThreadName { .. } =>
+                Self::InvalidThreadName { unused_notify: () },
         }
     }
 }
@@ -311,6 +637,7 @@ impl<T> Display for InstallError<T> {
         write!(f, "{}", match self {
             Self::AlreadyInstalled { .. } => "already installed signal handling",
             Self::ThreadCreateFailed(_) => "failed to create internal thread",
+            Self::InvalidThreadName { .. } => "invalid name for internal thread",
         })
     }
 }
@@ -319,7 +646,7 @@ impl<T: Debug> Error for InstallError<T> {
     #[inline]
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            Self::AlreadyInstalled { .. } => None,
+            Self::AlreadyInstalled { .. } | Self::InvalidThreadName { .. } => None,
             Self::ThreadCreateFailed(e) => Some(&**e),
         }
     }
@@ -351,8 +678,8 @@ impl Display for UninstallError {
 impl Error for UninstallError {}
 
 
-/// Error returned by [`SignalsChannel::finish`] and
-/// [`SignalsChannel::finish_with_outside_channel`].
+/// Error returned by [`SignalsChannel::finish`], [`SignalsChannel::finish_with_outside_channel`],
+/// and [`SignalsChannel::finish_drained`].
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum FinishError {
@@ -361,6 +688,9 @@ pub enum FinishError {
     /// The called finishing method is incongruent with the method that was used for the
     /// installing.
     WrongMethod,
+    /// [`SignalsChannel::finish_drained`] finished, but its timeout elapsed before all the final
+    /// notifications were received, and so some might have been lost.
+    DrainTimedOut,
 }
 
 impl From<UninstallError> for Result<(), FinishError> {
@@ -379,6 +709,7 @@ impl Display for FinishError {
         write!(f, "{}", match self {
             Self::AlreadyFinished => "already finished signal handling",
             Self::WrongMethod => "finish method wrong for how installed",
+            Self::DrainTimedOut => "timed-out draining the final notifications",
         })
     }
 }
@@ -1,4 +1,4 @@
-use crate::SignalNumber;
+use crate::{util::signal_name, SignalNumber};
 use core::{cmp::Ordering, mem, ops::ControlFlow};
 
 
@@ -9,15 +9,36 @@ use core::{cmp::Ordering, mem, ops::ControlFlow};
 ///
 /// `B` is the type of the final value that the processing finishes with.  `C` is the type of the
 /// state value that is passed in and out of all delegates during processing.
+///
+/// With the crate feature `"serde"`, this implements `Serialize` and `Deserialize`, but only of
+/// `sig_num`, `cur_count`, and `received_at`, because `B` and `C` generally aren't serializable.
+/// Deserializing makes `flow` be `Continue(C::default())`.  E.g. for logging receipts to a
+/// structured sink, or forwarding them to another process.
 #[non_exhaustive]
 #[must_use]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Receipt<U, B = (), C = ()> {
     /// Signal number that was received.
-    pub sig_num:   SignalNumber,
+    pub sig_num:     SignalNumber,
     /// Current count of how many times the signal designated by `sig_num` was received since
     /// last time its consuming was run.
-    pub cur_count: U,
+    ///
+    /// This is the coalesced count: multiple deliveries that occurred between consumings are
+    /// represented by a single `Receipt` with this count, and so a delegate is called at most
+    /// once per consuming regardless.  It's never zero, except for a delegate that is called
+    /// every iteration, via [`consume_count_then_delegate_always`](
+    /// crate::consume_count_then_delegate_always) (i.e. a signal declared with `{always}`), for
+    /// which it's zero when there were no deliveries since the last iteration.
+    pub cur_count:   U,
+    /// The time, in nanoseconds, of the most recent delivery of the signal, as given by
+    /// [`SignalReceipt::last_received_nanos()`](crate::SignalReceipt).  It's of the clock that
+    /// [`SignalReceipt::CLOCK`](crate::SignalReceipt::CLOCK) chooses for the signal, which is
+    /// `CLOCK_MONOTONIC` by default (or as declared by `{clock}` with the `premade` macro).
+    ///
+    /// Only `Some` when the crate feature `"timestamps"` is enabled.  Comparing it to the
+    /// current time of the same [`ClockSource`](crate::util::ClockSource), via its `now_nanos`,
+    /// gives the latency of processing the receipt.
+    pub received_at: Option<u64>,
     /// Control whether the processing of subsequent receipts will continue or finish after the
     /// current delegate (which is processing this instance) returns.
     ///
@@ -27,13 +48,26 @@ pub struct Receipt<U, B = (), C = ()> {
     ///
     /// When `Continue`, the contained value can be used as a mutable state that is passed
     /// to and returned from all delegates during processing.
-    pub flow:      ControlFlow<B, C>,
+    pub flow:        ControlFlow<B, C>,
 }
 
 
 impl<U, B, C> Receipt<U, B, C> {
     const NOT_STATE_MSG: &'static str = "should be `ControlFlow::Continue` to get state";
 
+    /// Make a `Receipt` like the one that would be given to a delegate, with `flow` as
+    /// `ControlFlow::Continue(state)`.
+    ///
+    /// This is a testing aid, for unit-testing delegate functions directly, without delivering
+    /// real signals nor running a consuming loop.  (Otherwise, a `Receipt` is only ever made by
+    /// this crate.)  To be like a real one, `cur_count` should not be zero, unless the delegate
+    /// is for [`consume_count_then_delegate_always`](crate::consume_count_then_delegate_always),
+    /// to which zero is also given.  `received_at` is `None`.
+    #[inline]
+    pub fn new_for_test(sig_num: SignalNumber, cur_count: U, state: C) -> Self {
+        Self { sig_num, cur_count, received_at: None, flow: ControlFlow::Continue(state) }
+    }
+
     /// Cause the processing to finish.
     ///
     /// Assigns `self.flow = ControlFlow::Break(B::default())`.
@@ -51,6 +85,29 @@ impl<U, B, C> Receipt<U, B, C> {
     #[inline]
     pub fn break_loop_with(&mut self, val: B) { self.flow = ControlFlow::Break(val); }
 
+    /// Cause the processing to finish with the given value, only if `cond`.  Otherwise,
+    /// `self.flow` is left unchanged.
+    #[inline]
+    pub fn break_if(&mut self, cond: bool, val: B) {
+        if cond {
+            self.break_loop_with(val);
+        }
+    }
+
+    /// Like [`Self::break_if`], but the value is only made, by calling `f`, if `cond`.
+    #[inline]
+    pub fn break_if_with(&mut self, cond: bool, f: impl FnOnce() -> B) {
+        if cond {
+            self.break_loop_with(f());
+        }
+    }
+
+    /// The name of `sig_num`, e.g. for logging.  Same as
+    /// [`util::signal_name()`](crate::util::signal_name).
+    #[must_use]
+    #[inline]
+    pub const fn signal_name(&self) -> Option<&'static str> { signal_name(self.sig_num) }
+
     /// Return a reference to the state value (which is held in `self.flow`).
     ///
     /// # Panics
@@ -132,11 +189,14 @@ impl<U: Ord, B: Ord, C: Ord> Ord for Receipt<U, B, C> {
     fn cmp(&self, other: &Self) -> Ordering {
         match self.sig_num.cmp(&other.sig_num) {
             Ordering::Equal => match self.cur_count.cmp(&other.cur_count) {
-                Ordering::Equal => match (&self.flow, &other.flow) {
-                    (ControlFlow::Continue(c1), ControlFlow::Continue(c2)) => c1.cmp(c2),
-                    (ControlFlow::Continue(_), ControlFlow::Break(_)) => Ordering::Less,
-                    (ControlFlow::Break(_), ControlFlow::Continue(_)) => Ordering::Greater,
-                    (ControlFlow::Break(b1), ControlFlow::Break(b2)) => b1.cmp(b2),
+                Ordering::Equal => match self.received_at.cmp(&other.received_at) {
+                    Ordering::Equal => match (&self.flow, &other.flow) {
+                        (ControlFlow::Continue(c1), ControlFlow::Continue(c2)) => c1.cmp(c2),
+                        (ControlFlow::Continue(_), ControlFlow::Break(_)) => Ordering::Less,
+                        (ControlFlow::Break(_), ControlFlow::Continue(_)) => Ordering::Greater,
+                        (ControlFlow::Break(b1), ControlFlow::Break(b2)) => b1.cmp(b2),
+                    },
+                    ord @ (Ordering::Less | Ordering::Greater) => ord,
                 },
                 ord @ (Ordering::Less | Ordering::Greater) => ord,
             },
@@ -144,3 +204,39 @@ impl<U: Ord, B: Ord, C: Ord> Ord for Receipt<U, B, C> {
         }
     }
 }
+
+
+/// The fields of a [`Receipt`] that are serialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Receipt")]
+struct Repr<U> {
+    sig_num:     SignalNumber,
+    cur_count:   U,
+    received_at: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl<U: serde::Serialize, B, C> serde::Serialize for Receipt<U, B, C> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = Repr {
+            sig_num:     self.sig_num,
+            cur_count:   &self.cur_count,
+            received_at: self.received_at,
+        };
+        repr.serialize(serializer)
+    }
+}
+
+/// Makes `flow` be `Continue(C::default())`.
+#[cfg(feature = "serde")]
+impl<'de, U: serde::Deserialize<'de>, B, C: Default> serde::Deserialize<'de>
+    for Receipt<U, B, C>
+{
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Repr { sig_num, cur_count, received_at } = Repr::deserialize(deserializer)?;
+        Ok(Self { sig_num, cur_count, received_at, flow: ControlFlow::Continue(C::default()) })
+    }
+}
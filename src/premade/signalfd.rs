@@ -0,0 +1,130 @@
+//! An alternative to the consuming thread, for Linux, that receives the signals via a
+//! [`signalfd(2)`](https://man7.org/linux/man-pages/man2/signalfd.2.html), so that they can be
+//! processed from within an event loop (e.g. with `epoll` or `mio`).
+//!
+//! The signals must be blocked (masked) in all threads, or else they'll be delivered to a
+//! handler (or to their default action) instead of being readable from the file descriptor.
+//! This can be done via [`mask_all_signals_of_current_thread`] in the main thread before any
+//! other threads are spawned (which inherit the mask).  Installing our handlers isn't needed then
+//! (but it's harmless, because they aren't called while the signals are blocked).
+//!
+//! The same [`premade`](crate::premade!) declarations drive this: the file descriptor is for the
+//! declared signal numbers, and each signal read from it is counted the same as our handler
+//! would, and then the same delegates process the [`Receipt`](crate::Receipt)s.
+
+#![allow(unsafe_code)]
+
+#[cfg(doc)]
+use crate::util::mask_all_signals_of_current_thread;
+use crate::{util::SigSet, Premade, SignalNumber};
+use core::{ffi::{c_int, c_void},
+           mem::{size_of, MaybeUninit},
+           ops::ControlFlow};
+use errno::{errno, Errno};
+
+
+/// Owns a `signalfd` file descriptor, which is closed when this is dropped.
+///
+/// It's non-blocking, and so reading only reads what is already pending.  It's close-on-exec.
+#[derive(Debug)]
+pub struct SignalFd {
+    fd: c_int,
+}
+
+impl SignalFd {
+    /// Create a `signalfd` for the given set of signal numbers.
+    ///
+    /// # Errors
+    /// If `signalfd()` does.
+    #[inline]
+    pub fn new(signals: &SigSet) -> Result<Self, Errno> {
+        // SAFETY: The arguments are proper, because `signals` was initialized.
+        let fd = unsafe {
+            libc::signalfd(-1, signals.as_raw(), libc::SFD_NONBLOCK | libc::SFD_CLOEXEC)
+        };
+        if fd >= 0 { Ok(Self { fd }) } else { Err(errno()) }
+    }
+
+    /// Create a `signalfd` for all of the declared signal numbers of `P`.
+    ///
+    /// # Errors
+    /// If `signalfd()` does.
+    #[inline]
+    pub fn for_premade<P: Premade>() -> Result<Self, Errno> { Self::new(&P::declared_signals()) }
+
+    /// The file descriptor, e.g. for registering it to be polled for readability.  It remains
+    /// owned by `self`.
+    #[must_use]
+    #[inline]
+    pub fn as_raw_fd(&self) -> c_int { self.fd }
+
+    /// Read all the signals that are currently pending, and call `each` with the signal number
+    /// of each.
+    ///
+    /// # Errors
+    /// If `read()` fails other than by it being interrupted or by there being nothing more to
+    /// read.
+    #[inline]
+    pub fn read_pending(&self, mut each: impl FnMut(SignalNumber)) -> Result<(), Errno> {
+        const BATCH: usize = 16;
+        const SIZE: usize = size_of::<libc::signalfd_siginfo>();
+
+        let mut buf = MaybeUninit::<[libc::signalfd_siginfo; BATCH]>::uninit();
+        loop {
+            let dest = buf.as_mut_ptr().cast::<c_void>();
+            // SAFETY: The arguments are proper, because `dest` is valid for writing that size.
+            let r = unsafe { libc::read(self.fd, dest, SIZE * BATCH) };
+            let Ok(len) = usize::try_from(r) else {
+                let e = errno();
+                match e.0 {
+                    libc::EINTR => continue,
+                    libc::EAGAIN => break Ok(()),
+                    _ => break Err(e),
+                }
+            };
+            // A `signalfd` only ever gives whole structs.
+            #[allow(clippy::integer_division)]
+            let count = len / SIZE;
+            let infos = buf.as_ptr().cast::<libc::signalfd_siginfo>();
+            for i in 0 .. count {
+                // SAFETY: Within the bounds of `buf`.
+                let info = unsafe { infos.add(i) };
+                // SAFETY: This many were initialized by the `read()`.
+                let info = unsafe { &*info };
+                if let Ok(signum) = SignalNumber::try_from(info.ssi_signo) {
+                    each(signum);
+                }
+            }
+            if count < BATCH {
+                break Ok(()); // Nothing more is pending.
+            }
+        }
+    }
+
+    /// Read all the signals that are currently pending, count them for `P` (via
+    /// [`Premade::count_delivery`]), and then call all the delegates of `P` once (via
+    /// [`Premade::consume_once`]) to process them.  Intended to be called when the file
+    /// descriptor is readable.
+    ///
+    /// # Errors
+    /// Same as [`Self::read_pending`], in which case the delegates aren't called (but whatever
+    /// was read is still counted, and so it'll be processed by the next draining).
+    #[inline]
+    pub fn drain<P: Premade>(
+        &self,
+        state: P::Continue,
+    ) -> Result<ControlFlow<P::Break, P::Continue>, Errno> {
+        self.read_pending(|signum| {
+            let _is_declared = P::count_delivery(signum);
+        })?;
+        Ok(P::consume_once(state))
+    }
+}
+
+impl Drop for SignalFd {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: `self.fd` is owned by `self`, and so it's not closed elsewhere.
+        let _r = unsafe { libc::close(self.fd) };
+    }
+}
@@ -0,0 +1,116 @@
+//! An alternative to our asynchronous handlers, that receives the signals synchronously via
+//! [`sigwaitinfo(2)`](https://man7.org/linux/man-pages/man2/sigwaitinfo.2.html) in a thread
+//! dedicated to that, so that the delegates run in fully normal context and can also be given
+//! the `siginfo_t` of each delivery.
+//!
+//! The signals must be blocked (masked) in all threads, or else they'll be delivered to a
+//! handler (or to their default action) in some other thread instead of being waited for.  This
+//! can be done via [`mask_all_signals_of_current_thread`] in the main thread before any other
+//! threads are spawned (which inherit the mask).  [`sync_consume_loop`] also blocks them in its
+//! own thread.  Installing our handlers isn't needed then (but it's harmless, because they
+//! aren't called while the signals are blocked).
+//!
+//! The same [`premade`](crate::premade!) declarations drive this: the waiting is for the
+//! declared signal numbers, and each signal waited for is counted the same as our handler would,
+//! and then the same delegates process the [`Receipt`](crate::Receipt)s.
+
+#![allow(unsafe_code)]
+
+#[cfg(doc)]
+use crate::util::mask_all_signals_of_current_thread;
+use crate::{util::{SigInfo, SigSet},
+            Premade, SigInfoFields};
+use core::{mem::MaybeUninit, ops::ControlFlow, ptr, sync::atomic::Ordering::Relaxed,
+           time::Duration};
+use errno::errno;
+
+
+/// Like [`Premade::consume_loop_with`], but each iteration waits, via `sigwaitinfo()`, for one of
+/// the declared signals of `P` to be pending, instead of waiting for our handler to post the
+/// semaphore.  The waited-for signal is counted for `P` (via [`Premade::count_delivery`]) and
+/// then all the delegates of `P` are called once (via [`Premade::consume_once`]) to process it.
+///
+/// Before that, `on_info` is called with the info of the delivery, in the normal context of the
+/// current thread, e.g. to log who sent it.
+///
+/// The declared signals are blocked in the current thread (and left blocked after this returns),
+/// because that's required for waiting for them.
+///
+/// The loop finishes when a delegate breaks it, which is returned, or when the continue-flag of
+/// `P` is `false` (e.g. due to [`Premade::finish`]), in which case `finish` is returned.  Because
+/// the waiting isn't woken by our semaphore, the continue-flag is only checked when a declared
+/// signal is received or, if `timeout` is given, when that elapses without any.  (The
+/// continue-flag isn't reset by this, so [`Premade::reset_continue_flag`] must be done first if
+/// `P` was finished before.)
+///
+/// # Panics
+/// If `sigwaitinfo()` or `sigtimedwait()` fails other than by being interrupted or by timing
+/// out.
+#[inline]
+pub fn sync_consume_loop<P: Premade>(
+    timeout: Option<Duration>,
+    mut state: P::Continue,
+    mut on_info: impl FnMut(&SigInfoFields),
+    finish: P::Break,
+) -> P::Break {
+    let set = P::declared_signals();
+    block_signals_of_current_thread(&set);
+    loop {
+        if !P::continue_flag().load(Relaxed) {
+            break finish;
+        }
+        let Some(info) = wait(&set, timeout) else { continue };
+        on_info(&info);
+        let _is_declared = P::count_delivery(info.signo);
+        match P::consume_once(state) {
+            ControlFlow::Continue(s) => state = s,
+            ControlFlow::Break(b) => break b,
+        }
+    }
+}
+
+
+fn block_signals_of_current_thread(set: &SigSet) {
+    // SAFETY: The arguments are proper, because `set` is initialized and `how` is one of the
+    // allowed values.
+    let r = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, set.as_raw(), ptr::null_mut()) };
+    assert_eq!(0, r, "`pthread_sigmask()` will succeed");
+}
+
+/// Wait for one of `set` to be pending, and take it.  Returns `None` if interrupted or if
+/// `timeout` elapsed.
+fn wait(set: &SigSet, timeout: Option<Duration>) -> Option<SigInfoFields> {
+    let mut info = MaybeUninit::<SigInfo>::zeroed();
+    let r = if let Some(timeout) = timeout {
+        let ts = relative_timespec(timeout);
+        // SAFETY: The arguments are proper, and `info` is valid, aligned, and unaliased.
+        unsafe { libc::sigtimedwait(set.as_raw(), info.as_mut_ptr(), &ts) }
+    } else {
+        // SAFETY: The arguments are proper, and `info` is valid, aligned, and unaliased.
+        unsafe { libc::sigwaitinfo(set.as_raw(), info.as_mut_ptr()) }
+    };
+    if r >= 0 {
+        // SAFETY: It was zeroed, and the waiting initialized it.
+        let info = unsafe { info.assume_init_ref() };
+        Some(SigInfoFields::from_raw(info))
+    } else {
+        let e = errno();
+        match e.0 {
+            libc::EINTR | libc::EAGAIN => None,
+            #[allow(clippy::panic)]
+            _ => panic!("`sigwaitinfo()` or `sigtimedwait()` will succeed: {e}"),
+        }
+    }
+}
+
+/// Saturates at the greatest duration that can be represented.
+fn relative_timespec(timeout: Duration) -> libc::timespec {
+    // SAFETY: All-zeros is a valid `timespec`, i.e. zero, including any padding fields.
+    let mut ts = unsafe { MaybeUninit::<libc::timespec>::zeroed().assume_init() };
+    ts.tv_sec = libc::time_t::try_from(timeout.as_secs()).unwrap_or(libc::time_t::MAX);
+    #[allow(clippy::unnecessary_fallible_conversions)] // Not infallible on all platforms.
+    {
+        ts.tv_nsec = timeout.subsec_nanos().try_into().unwrap_or(0);
+    }
+    ts
+}
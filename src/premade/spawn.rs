@@ -0,0 +1,73 @@
+use crate::Premade;
+use core::{fmt::{self, Debug, Formatter},
+           marker::PhantomData};
+extern crate std;
+use std::{io,
+          thread::{self, JoinHandle}};
+
+
+/// The consuming thread of `P`, as spawned by [`Premade::install_and_spawn`].
+///
+/// Dropping this detaches the thread, which then continues consuming until [`Premade::finish`] is
+/// done elsewhere.
+#[must_use = "dropping this detaches the consuming thread"]
+pub struct ConsumeHandle<P: Premade> {
+    thread:   JoinHandle<P::Break>,
+    _premade: PhantomData<fn() -> P>,
+}
+
+impl<P: Premade> ConsumeHandle<P> {
+    /// The name of the spawned thread.
+    pub const THREAD_NAME: &'static str = "signals-consume";
+
+    pub(super) fn spawn() -> io::Result<Self>
+    where
+        P: 'static,
+        P::Continue: Default,
+        P::Break: Default + Send + 'static,
+    {
+        // Must be installed before the thread is spawned, so that no deliveries are missed
+        // between the thread starting to wait and the handlers being installed.
+        P::install_all_handlers();
+        match thread::Builder::new().name(Self::THREAD_NAME.into()).spawn(P::consume_loop) {
+            Ok(thread) => Ok(Self { thread, _premade: PhantomData }),
+            Err(e) => {
+                // Nothing would consume what the handlers count.
+                P::uninstall_all_handlers();
+                Err(e)
+            },
+        }
+    }
+
+    /// Wait for the consuming thread to return, e.g. after a delegate breaks its loop.
+    ///
+    /// # Errors
+    /// If the thread panicked, with the panic's payload.
+    #[inline]
+    pub fn join(self) -> thread::Result<P::Break> { self.thread.join() }
+
+    /// Do [`Premade::finish`], and then [`Self::join`].
+    ///
+    /// # Errors
+    /// Same as [`Self::join`].
+    ///
+    /// # Panics
+    /// Same as [`Premade::finish`].
+    #[inline]
+    pub fn finish(self) -> thread::Result<P::Break> {
+        P::finish();
+        self.join()
+    }
+
+    /// Whether the consuming thread has returned, and so [`Self::join`] won't block.
+    #[must_use]
+    #[inline]
+    pub fn is_finished(&self) -> bool { self.thread.is_finished() }
+}
+
+impl<P: Premade> Debug for ConsumeHandle<P> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsumeHandle").field("thread", &self.thread).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{consume_loop_impl, WaitSource};
+use core::{fmt::{self, Debug, Formatter},
+           ops::ControlFlow,
+           sync::atomic::{AtomicBool, Ordering::Relaxed}};
+extern crate alloc;
+use alloc::{boxed::Box, vec::Vec};
+extern crate std;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+
+/// Like [`Consumer`](crate::Consumer), but able to be given to a [`ConsumerRegistry`] from
+/// another thread.
+pub type SendConsumer<B = (), C = ()> = dyn FnMut(C) -> ControlFlow<B, C> + Send;
+
+/// Identifies a consumer that was registered with a [`ConsumerRegistry`], for unregistering it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[must_use]
+pub struct ConsumerHandle(u64);
+
+/// The consumers for [`consume_loop_dynamic()`], which other threads can register and unregister
+/// while that loop is running.  E.g. for a subsystem to add its processing once it's
+/// initialized.
+///
+/// Changes are picked up by the loop at the start of its next iteration, not in the middle of
+/// one, i.e. the next time it's woken, which is normally by a signal being received (or by
+/// whatever else posts its semaphore).  Until then, the loop continues calling the consumers
+/// that it had.
+///
+/// Consumers are called in the order they were registered.
+pub struct ConsumerRegistry<B = (), C = ()> {
+    inner: Mutex<Inner<B, C>>,
+}
+
+struct Inner<B, C> {
+    next_id: u64,
+    /// The IDs of the consumers that are currently registered.
+    live:    Vec<u64>,
+    /// Changes that the loop hasn't picked up yet, in the order they were made.
+    pending: Vec<Change<B, C>>,
+}
+
+enum Change<B, C> {
+    Register(u64, Box<SendConsumer<B, C>>),
+    Unregister(u64),
+}
+
+impl<B, C> ConsumerRegistry<B, C> {
+    /// Make an empty one.  Can be a `static` item.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self { inner: Mutex::new(Inner { next_id: 0, live: Vec::new(), pending: Vec::new() }) }
+    }
+
+    fn acquire(&self) -> MutexGuard<'_, Inner<B, C>> {
+        // The state remains consistent even if another thread panicked while holding the lock,
+        // because it's only modified by pushing and removing.
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Add `consumer`, to be called by the loop starting with its next iteration.
+    #[inline]
+    pub fn register(
+        &self,
+        consumer: impl FnMut(C) -> ControlFlow<B, C> + Send + 'static,
+    ) -> ConsumerHandle {
+        let mut inner = self.acquire();
+        let id = inner.next_id;
+        inner.next_id = id.saturating_add(1);
+        inner.live.push(id);
+        inner.pending.push(Change::Register(id, Box::new(consumer)));
+        ConsumerHandle(id)
+    }
+
+    /// Remove the consumer identified by `handle`, to not be called by the loop starting with
+    /// its next iteration.  Returns `false` if it already was unregistered.
+    #[inline]
+    pub fn unregister(&self, handle: ConsumerHandle) -> bool {
+        let mut inner = self.acquire();
+        let Some(index) = inner.live.iter().position(|&id| id == handle.0) else { return false };
+        let _id = inner.live.remove(index);
+        inner.pending.push(Change::Unregister(handle.0));
+        true
+    }
+
+    /// How many consumers are currently registered, including those that the loop hasn't picked
+    /// up yet.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize { self.acquire().live.len() }
+
+    /// Whether no consumers are currently registered.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Apply the pending changes to the loop's `active` consumers.
+    fn update(&self, active: &mut Vec<(u64, Box<SendConsumer<B, C>>)>) {
+        for change in self.acquire().pending.drain(..) {
+            match change {
+                Change::Register(id, consumer) => active.push((id, consumer)),
+                Change::Unregister(id) => active.retain(|&(active_id, _)| active_id != id),
+            }
+        }
+    }
+}
+
+impl<B, C> Default for ConsumerRegistry<B, C> {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl<B, C> Debug for ConsumerRegistry<B, C> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsumerRegistry").field("len", &self.len()).finish_non_exhaustive()
+    }
+}
+
+
+/// Like [`consume_loop()`](crate::consume_loop), but the consumers are those of `registry`,
+/// which can be changed while this is running.  See [`ConsumerRegistry`] for when changes are
+/// picked up.
+///
+/// # Panics
+/// Same as [`consume_loop()`](crate::consume_loop).
+#[allow(clippy::needless_pass_by_value)] // So that a `Pin<&Semaphore>` can be passed as is.
+#[inline]
+pub fn consume_loop_dynamic<B, C, W: WaitSource>(
+    do_mask: bool,
+    sem: W,
+    try_init_limit: u64,
+    state: C,
+    registry: &ConsumerRegistry<B, C>,
+    continue_flag: &AtomicBool,
+    finish: B,
+) -> B {
+    let mut active = Vec::new();
+    // One consumer, for the loop, that calls all of the registry's, so that they're only
+    // updated at the start of each iteration.
+    let mut each_iteration = |mut cur_state| {
+        registry.update(&mut active);
+        for (_, consume) in &mut active {
+            cur_state = consume(cur_state)?;
+            // Check after each, like the loop does, to not call any more once it's toggled.
+            if !continue_flag.load(Relaxed) {
+                break;
+            }
+        }
+        ControlFlow::Continue(cur_state)
+    };
+    consume_loop_impl(
+        do_mask,
+        sem,
+        try_init_limit,
+        state,
+        &mut [&mut each_iteration],
+        continue_flag,
+        None,
+        finish,
+        |_| {},
+        WaitSource::wait,
+    )
+}
@@ -0,0 +1,125 @@
+use crate::{util::SigInfo, SignalNumber};
+use core::{ffi::c_int,
+           hint,
+           sync::atomic::{fence, AtomicBool, AtomicI32, AtomicU32,
+                          Ordering::{Acquire, Relaxed, Release}}};
+
+
+/// The fields of a `siginfo_t` that [`handler_with_info`](crate::handler_with_info) captures.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[allow(clippy::exhaustive_structs)]
+pub struct SigInfoFields {
+    /// `si_signo`: The signal number.
+    pub signo: SignalNumber,
+    /// `si_code`: Why the signal was sent, e.g. `SI_USER` when by `kill()`.
+    pub code:  c_int,
+    /// `si_pid`: The process ID of the sender, when that's meaningful for `code`.
+    pub pid:   i32,
+    /// `si_uid`: The real user ID of the sender, when that's meaningful for `code`.
+    pub uid:   u32,
+}
+
+impl SigInfoFields {
+    /// Copy the fields out of the `siginfo_t` given to a signal handler.
+    ///
+    /// This is async-signal-safe.
+    pub(crate) fn from_raw(info: &SigInfo) -> Self {
+        #![allow(unsafe_code)]
+
+        Self {
+            signo: info.si_signo,
+            code:  info.si_code,
+            // SAFETY: These only read integers from the union within the `siginfo_t` that was
+            // given by the OS, which is always initialized.  The values are only meaningful for
+            // some `si_code`s, which is documented for the fields.
+            pid:   unsafe { info.si_pid() },
+            // SAFETY: Same as above.
+            uid:   unsafe { info.si_uid() },
+        }
+    }
+}
+
+
+/// A lock-free slot that holds the [`SigInfoFields`] of the latest delivery of a signal.  Written
+/// by [`handler_with_info`](crate::handler_with_info) and read by the consuming thread.
+///
+/// Writing is async-signal-safe, and never blocks: it's only atomic operations.  If another
+/// writer (a concurrent delivery of the same signal on another thread) is in the middle of
+/// writing, then the writing is skipped, and so the slot holds the info of one of the concurrent
+/// deliveries but not necessarily the very latest.
+///
+/// Reading is done via a sequence-lock, and so it retries if a write occurred concurrently, but
+/// it never sees a mix of fields from different deliveries.
+#[derive(Debug)]
+pub struct SigInfoSlot {
+    /// Even when not being written.  Zero when never written.
+    seq:     AtomicU32,
+    writing: AtomicBool,
+    signo:   AtomicI32,
+    code:    AtomicI32,
+    pid:     AtomicI32,
+    uid:     AtomicU32,
+}
+
+impl SigInfoSlot {
+    /// Make a new slot that hasn't been written to.
+    #[must_use]
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            seq:     AtomicU32::new(0),
+            writing: AtomicBool::new(false),
+            signo:   AtomicI32::new(0),
+            code:    AtomicI32::new(0),
+            pid:     AtomicI32::new(0),
+            uid:     AtomicU32::new(0),
+        }
+    }
+
+    /// Store the given fields, unless another write is in progress.
+    ///
+    /// This is async-signal-safe, and so it's safe for this to be called from a signal handler.
+    #[inline]
+    pub fn store(&self, fields: SigInfoFields) {
+        if self.writing.compare_exchange(false, true, Acquire, Relaxed).is_err() {
+            return;
+        }
+        // Odd while writing.
+        self.seq.fetch_add(1, Relaxed);
+        fence(Release);
+        self.signo.store(fields.signo, Relaxed);
+        self.code.store(fields.code, Relaxed);
+        self.pid.store(fields.pid, Relaxed);
+        self.uid.store(fields.uid, Relaxed);
+        // Even again.  Synchronizes with the loading in `Self::load`.
+        self.seq.fetch_add(1, Release);
+        self.writing.store(false, Release);
+    }
+
+    /// Get the fields that were last stored, or `None` if nothing was ever stored.
+    #[must_use]
+    #[inline]
+    pub fn load(&self) -> Option<SigInfoFields> {
+        loop {
+            let before = self.seq.load(Acquire);
+            if before == 0 {
+                break None;
+            }
+            if before % 2 == 0 {
+                let fields = SigInfoFields {
+                    signo: self.signo.load(Relaxed),
+                    code:  self.code.load(Relaxed),
+                    pid:   self.pid.load(Relaxed),
+                    uid:   self.uid.load(Relaxed),
+                };
+                fence(Acquire);
+                if self.seq.load(Relaxed) == before {
+                    break Some(fields);
+                }
+            }
+            // A write is in progress, which will complete very soon.
+            hint::spin_loop();
+        }
+    }
+}
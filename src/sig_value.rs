@@ -0,0 +1,224 @@
+use crate::{resolve_signum, util::SigInfo, SignalNumber, SignalReceiptWithValue};
+use core::{cmp::Ordering,
+           ffi::c_int,
+           ops::ControlFlow,
+           sync::atomic::{AtomicI32, AtomicU64,
+                          Ordering::{Acquire, Relaxed, Release}}};
+
+
+/// The value that was sent with a real-time signal via `sigqueue()`, as the `sival_int` of the
+/// `si_value` of the `siginfo_t` given to a signal handler.
+///
+/// This is async-signal-safe.
+pub(crate) fn sival_int(info: &SigInfo) -> c_int {
+    #![allow(unsafe_code)]
+
+    // SAFETY: This only reads from the union within the `siginfo_t` that was given by the OS,
+    // which is always initialized.  The value is only meaningful for `SI_QUEUE` (et al).
+    let value = unsafe { info.si_value() };
+    // `sigval` is a C `union` of `sival_int` and `sival_ptr`, which the `libc` crate only gives
+    // as the pointer.  Both start at the same address, and so this reads the `int` as C would,
+    // regardless of endianness.
+    let ptr: *const c_int = core::ptr::addr_of!(value).cast();
+    // SAFETY: `ptr` is valid, and aligned because a pointer is at least as aligned as an `int`.
+    unsafe { ptr.read() }
+}
+
+
+/// A lock-free bounded queue of the values of the deliveries of a real-time signal, written by
+/// [`handler_with_value`](crate::handler_with_value) and read by the consuming thread.
+///
+/// Unlike the counter, which coalesces deliveries, this keeps each value, in the order they were
+/// delivered, because the OS queues real-time signals with distinct values (up to a limit) and
+/// each must be processed.
+///
+/// Pushing is async-signal-safe, and never blocks: it's only atomic operations.  When the queue
+/// is full, the value is dropped instead, and counted for [`Self::take_dropped`].  Concurrent
+/// deliveries (on other threads) can push concurrently.
+///
+/// Popping must only be done by a single thread at a time, i.e. the consuming thread.
+#[derive(Debug)]
+pub struct SigValueQueue {
+    /// The position that the next push will claim.
+    tail:    AtomicU64,
+    /// The position that the next pop will read.  Only changed by the single consumer.
+    head:    AtomicU64,
+    slots:   [SigValueSlot; SigValueQueue::CAPACITY],
+    dropped: AtomicU64,
+}
+
+/// Each slot is used at positions `i`, `i + CAPACITY`, `i + 2*CAPACITY`, etc., i.e. once per lap.
+#[derive(Debug)]
+struct SigValueSlot {
+    /// `2 * lap` when empty and ready to be pushed into for that lap, and `2 * lap + 1` when full
+    /// with the value of that lap.
+    seq:   AtomicU64,
+    value: AtomicI32,
+}
+
+impl SigValueQueue {
+    /// How many values can be held at once, before further ones are dropped.  The consuming
+    /// thread empties the queue every iteration, and so this only needs to cover the deliveries
+    /// that occur between those.
+    pub const CAPACITY: usize = 64;
+
+    /// Make a new empty queue.
+    #[must_use]
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: SigValueSlot =
+            SigValueSlot { seq: AtomicU64::new(0), value: AtomicI32::new(0) };
+        Self {
+            tail:    AtomicU64::new(0),
+            head:    AtomicU64::new(0),
+            slots:   [EMPTY; SigValueQueue::CAPACITY],
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// The slot, and its lap, for the given position.
+    #[allow(clippy::as_conversions, clippy::cast_possible_truncation, clippy::indexing_slicing)]
+    fn slot(&self, pos: u64) -> (&SigValueSlot, u64) {
+        const CAP: u64 = SigValueQueue::CAPACITY as u64; // (Lossless.)
+        // The remainder is less than the length, and so the indexing can't panic.
+        #[allow(clippy::integer_division)] // The lap is intended to be the truncated quotient.
+        (&self.slots[(pos % CAP) as usize], pos / CAP)
+    }
+
+    /// Add the given value at the end, or, if full, drop it and count that.  Returns whether it
+    /// was added.
+    ///
+    /// This is async-signal-safe, and so it's safe for this to be called from a signal handler.
+    #[inline]
+    pub fn push(&self, value: c_int) -> bool {
+        let mut pos = self.tail.load(Relaxed);
+        loop {
+            let (slot, lap) = self.slot(pos);
+            let empty = lap.wrapping_mul(2);
+            match slot.seq.load(Acquire).cmp(&empty) {
+                Ordering::Equal => {
+                    match self.tail.compare_exchange_weak(
+                        pos,
+                        pos.wrapping_add(1),
+                        Relaxed,
+                        Relaxed,
+                    ) {
+                        Ok(_) => {
+                            slot.value.store(value, Relaxed);
+                            // Synchronizes with the loading in `Self::pop`.
+                            slot.seq.store(empty.wrapping_add(1), Release);
+                            break true;
+                        },
+                        Err(latest) => pos = latest,
+                    }
+                },
+                Ordering::Less => {
+                    // Still full from the previous lap, which means the whole queue is full.
+                    self.dropped.fetch_add(1, Relaxed);
+                    break false;
+                },
+                Ordering::Greater => {
+                    // Another push claimed this position meanwhile.
+                    pos = self.tail.load(Relaxed);
+                },
+            }
+        }
+    }
+
+    /// Remove and return the value at the front, or `None` if empty.  Also `None` if the push
+    /// of the front value is still in progress (by a concurrent delivery on another thread), in
+    /// which case that delivery will wake the consuming thread again after.
+    ///
+    /// Must only be called by a single thread at a time.
+    #[must_use]
+    #[inline]
+    pub fn pop(&self) -> Option<c_int> {
+        let pos = self.head.load(Relaxed);
+        let (slot, lap) = self.slot(pos);
+        let full = lap.wrapping_mul(2).wrapping_add(1);
+        (slot.seq.load(Acquire) == full).then(|| {
+            let value = slot.value.load(Relaxed);
+            // Make the slot empty for the next lap.  Synchronizes with the loading in
+            // `Self::push`.
+            slot.seq.store(full.wrapping_add(1), Release);
+            self.head.store(pos.wrapping_add(1), Relaxed);
+            value
+        })
+    }
+
+    /// How many values were dropped because the queue was full, since this was last called, and
+    /// reset that to zero.
+    #[must_use]
+    #[inline]
+    pub fn take_dropped(&self) -> u64 { self.dropped.swap(0, Relaxed) }
+}
+
+
+/// The representation of a single delivery of a real-time signal, with its value, that is given
+/// to a delegate by [`consume_values_then_delegate`].  Like [`Receipt`](crate::Receipt), but
+/// without coalescing.
+#[derive(Debug)]
+#[allow(clippy::exhaustive_structs)]
+pub struct RtReceipt<B, C> {
+    /// The signal number.
+    pub sig_num: SignalNumber,
+    /// The `sival_int` that was sent with it via `sigqueue()`.
+    pub value:   c_int,
+    /// The state, and whether the processing should continue or finish.
+    pub flow:    ControlFlow<B, C>,
+}
+
+impl<B, C> RtReceipt<B, C> {
+    /// Cause the processing to finish with the given value.  No further values are given to the
+    /// delegate then, and those remain queued.
+    #[inline]
+    pub fn break_loop_with(&mut self, val: B) { self.flow = ControlFlow::Break(val); }
+
+    /// Return a mutable reference to the state value (which is held in `self.flow`).
+    ///
+    /// # Panics
+    /// If `self.flow` is not `ControlFlow::Continue`.  When an `RtReceipt` is given to a
+    /// delegate, it is guaranteed to hold `Continue`, and so this won't panic.
+    #[inline]
+    pub fn get_state_mut(&mut self) -> &mut C {
+        #![allow(clippy::panic)]
+        match &mut self.flow {
+            ControlFlow::Continue(state) => state,
+            ControlFlow::Break(_) => panic!("must be `Continue`"),
+        }
+    }
+}
+
+
+/// Like [`consume_count_then_delegate`](crate::consume_count_then_delegate), but for a
+/// real-time signal whose handler is [`handler_with_value`](crate::handler_with_value): the
+/// `delegate` is called once per queued value, in the order they were delivered, instead of once
+/// for the coalesced count.
+///
+/// The counter is also taken, so that it doesn't accumulate, but its count isn't given to the
+/// `delegate`, because the values are what matter.  Values that were dropped because the queue
+/// was full can be known via [`SigValueQueue::take_dropped`].
+#[inline]
+pub fn consume_values_then_delegate<const SIGNUM: SignalNumber, T, F, B, C>(
+    state: C,
+    mut delegate: F,
+) -> ControlFlow<B, C>
+where
+    T: SignalReceiptWithValue<SIGNUM>,
+    F: FnMut(&mut RtReceipt<B, C>),
+{
+    let _count = T::take_dirty_count();
+    let sig_num = resolve_signum(SIGNUM);
+    let mut flow = ControlFlow::Continue(state);
+    while let ControlFlow::Continue(cur_state) = flow {
+        let Some(value) = T::value_queue().pop() else {
+            return ControlFlow::Continue(cur_state);
+        };
+        let mut receipt = RtReceipt { sig_num, value, flow: ControlFlow::Continue(cur_state) };
+        delegate(&mut receipt);
+        flow = receipt.flow;
+    }
+    flow
+}
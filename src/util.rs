@@ -3,7 +3,25 @@
 
 #![allow(unsafe_code, clippy::used_underscore_binding)]
 
-pub(crate) use sigaction::SigAction;
+pub use alt_stack::{install_alt_stack, AltStack};
+mod alt_stack;
+
+pub use catch_all::{install_catch_all, CatchAllHandle};
+mod catch_all;
+
+pub use crash_log::CrashLog;
+mod crash_log;
+
+#[cfg(feature = "premade")]
+pub use delegates::{enqueue_on_receipt, notify_watchdog};
+#[cfg(feature = "premade")]
+mod delegates;
+
+use crate::SignalNumber;
+use core::{fmt::{self, Debug, Formatter},
+           marker::PhantomData};
+use errno::Errno;
+pub(crate) use sigaction::{SigAction, SigInfo};
 
 
 macro_rules! except_signals {
@@ -18,7 +36,7 @@ macro_rules! except_signals {
 
 #[allow(unreachable_pub)] // These full-`pub`s are in case this becomes a separate library.
 mod sigaction {
-    use super::{sigset_all_usual, sigset_empty};
+    use super::{sigset_all_usual, sigset_empty, SigSet};
     use crate::SignalNumber;
     use core::{ffi::{c_int, c_void},
                mem::MaybeUninit,
@@ -48,16 +66,16 @@ mod sigaction {
     /// Pointer to a signal-catching function of the non-`SA_SIGINFO` type.
     pub type Handler = extern "C" fn(signo: SignalNumber);
 
-    // MAYBE: These could be exposed in the future.
     /// Pointer to a signal-catching function of the `SA_SIGINFO` type.
-    type HandlerWithInfo =
+    pub type HandlerWithInfo =
         extern "C" fn(signo: SignalNumber, info: *mut SigInfo, context: *mut c_void);
-    // This exists to avoid exposing in the API our use of the `libc` crate.
-    type SigInfo = libc::siginfo_t;
+    // This exists to avoid naming, throughout, our use of the `libc` crate.
+    /// The C `siginfo_t` that is given to a [`HandlerWithInfo`].
+    pub type SigInfo = libc::siginfo_t;
 
     /// A builder of a C `struct sigaction` that can only be used safely.
     #[must_use]
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     #[allow(missing_copy_implementations)]
     pub struct SigAction(MaybeUninit<libc::sigaction>);
 
@@ -92,7 +110,6 @@ mod sigaction {
             it
         }
 
-        #[cfg_attr(not(debug_assertions), allow(dead_code))]
         fn sa_handler_ptr(&self) -> *const libc::sighandler_t {
             let act = self.0.as_ptr();
             // SAFETY: The pointers to the field and the struct are in-bounds.
@@ -118,7 +135,6 @@ mod sigaction {
             unsafe { addr_of_mut!((*act).sa_mask) }
         }
 
-        #[cfg_attr(not(debug_assertions), allow(dead_code))]
         fn sa_flags_ptr(&self) -> *const c_int {
             let act = self.0.as_ptr();
             // SAFETY: The pointers to the field and the struct are in-bounds.
@@ -131,7 +147,6 @@ mod sigaction {
             unsafe { addr_of_mut!((*act).sa_flags) }
         }
 
-        #[cfg_attr(not(debug_assertions), allow(dead_code))]
         fn sa_sigaction_ptr(&self) -> *const libc::sighandler_t {
             let act = self.0.as_ptr();
             // SAFETY: The pointers to the field and the struct are in-bounds.
@@ -175,6 +190,7 @@ mod sigaction {
         }
 
         /// Set the `.sa_handler` field to `handler`.
+        #[allow(dead_code)]
         #[inline]
         pub fn handler(handler: Handler) -> Self {
             // SAFETY: The argument is the address of the function of type `Handler`.
@@ -184,10 +200,42 @@ mod sigaction {
             }
         }
 
-        // MAYBE: This could be exposed in the future.
-        #[allow(dead_code)]
-        /// Set the `.sa_sigaction` field to `handler`.
-        fn handler_with_info(handler: HandlerWithInfo) -> Self {
+        /// Replace the `.sa_handler` field with `handler`, keeping the other fields.  E.g. to
+        /// reuse the same mask and flags as a template for multiple handlers.
+        #[inline]
+        pub fn set_handler(&mut self, handler: Handler) {
+            let sa_handler = self.sa_handler_mut_ptr();
+            // SAFETY: `sa_handler` is valid, aligned, and unaliased.  The value is the address of
+            // the function of type `Handler`.  The `.sa_flags` field doesn't have `SA_SIGINFO`,
+            // because only `Self::handler_with_info` would set that.
+            unsafe {
+                #[allow(clippy::fn_to_numeric_cast_any, clippy::as_conversions)]
+                sa_handler.write(handler as usize);
+            }
+        }
+
+        /// Like [`Self::set_handler`], but replace the `.sa_sigaction` field with `handler`, and
+        /// set `SA_SIGINFO` in the `.sa_flags` field, keeping the other flags and the mask.
+        #[inline]
+        pub fn set_handler_with_info(&mut self, handler: HandlerWithInfo) {
+            let sa_flags = self.sa_flags_mut_ptr();
+            // SAFETY: `sa_flags` is valid, aligned, unaliased, and initialized.
+            unsafe {
+                *sa_flags |= libc::SA_SIGINFO;
+            }
+            let sa_sigaction = self.sa_sigaction_mut_ptr();
+            // SAFETY: `sa_sigaction` is valid, aligned, and unaliased.  The value is the address
+            // of the function of type `HandlerWithInfo`, as `SA_SIGINFO` requires.
+            unsafe {
+                #[allow(clippy::fn_to_numeric_cast_any, clippy::as_conversions)]
+                sa_sigaction.write(handler as usize);
+            }
+        }
+
+        /// Set the `.sa_sigaction` field to `handler`, and set `SA_SIGINFO` in the `.sa_flags`
+        /// field.
+        #[inline]
+        pub fn handler_with_info(handler: HandlerWithInfo) -> Self {
             // SAFETY: The argument is the address of the function of type `HandlerWithInfo`.
             unsafe {
                 #[allow(clippy::fn_to_numeric_cast_any, clippy::as_conversions)]
@@ -215,6 +263,24 @@ mod sigaction {
             self
         }
 
+        /// Mask the given signal numbers, in addition to any already masked, during execution of
+        /// the signal handler.  E.g. so that a `SIGTERM` can't interleave with a `SIGHUP`
+        /// handler, while still allowing other signals.  Invalid signal numbers are ignored.
+        ///
+        /// The exceptional signals are excluded, the same as for [`Self::mask_all`], i.e. those
+        /// among the given are not masked.
+        #[doc = except_signals!()]
+        #[inline]
+        pub fn mask_these(mut self, signums: &[SignalNumber]) -> Self {
+            let usual = SigSet::full_usual();
+            let sa_mask = self.sa_mask_mut_ptr();
+            for &signum in signums.iter().filter(|&&signum| usual.contains(signum)) {
+                // SAFETY: The arguments are proper, because `sa_mask` was initialized.
+                let _r = unsafe { libc::sigaddset(sa_mask, signum) };
+            }
+            self
+        }
+
         /// Set the `.sa_flags` field to include `SA_RESTART`.
         #[inline]
         pub fn restart_intr(mut self) -> Self {
@@ -226,6 +292,45 @@ mod sigaction {
             self
         }
 
+        /// Set the `.sa_flags` field to include `SA_NODEFER`, so that the signal isn't masked
+        /// during execution of its own handler, which can then be re-entered by another delivery
+        /// of it.
+        ///
+        /// The signal is still masked if it's in the mask, e.g. due to [`Self::mask_all`], and so
+        /// [`Self::unmask`] should also be done for it in that case.
+        #[inline]
+        pub fn no_defer(mut self) -> Self {
+            let sa_flags = self.sa_flags_mut_ptr();
+            // SAFETY: `sa_flags` is valid, aligned, unaliased, and initialized.
+            unsafe {
+                *sa_flags |= libc::SA_NODEFER;
+            }
+            self
+        }
+
+        /// Set the `.sa_flags` field to include `SA_ONSTACK`, so that the signal handler
+        /// executes on the alternate signal stack of the thread, if it has one, as registered by
+        /// `sigaltstack()` (e.g. via [`install_alt_stack`](super::install_alt_stack)).
+        #[inline]
+        pub fn on_stack(mut self) -> Self {
+            let sa_flags = self.sa_flags_mut_ptr();
+            // SAFETY: `sa_flags` is valid, aligned, unaliased, and initialized.
+            unsafe {
+                *sa_flags |= libc::SA_ONSTACK;
+            }
+            self
+        }
+
+        /// Remove the given signal number from the signals masked during execution of the
+        /// signal handler.  Does nothing if it's invalid.
+        #[inline]
+        pub fn unmask(mut self, signum: SignalNumber) -> Self {
+            let sa_mask = self.sa_mask_mut_ptr();
+            // SAFETY: The arguments are proper, because `sa_mask` was initialized.
+            let _r = unsafe { libc::sigdelset(sa_mask, signum) };
+            self
+        }
+
         /// Like [`sigaction`](
         /// https://pubs.opengroup.org/onlinepubs/9799919799/functions/sigaction.html).
         ///
@@ -237,6 +342,22 @@ mod sigaction {
         #[allow(clippy::result_unit_err)]
         #[inline]
         pub unsafe fn install(self, signum: SignalNumber) -> Result<(), ()> {
+            // SAFETY: The caller must uphold the same requirement.
+            unsafe { self.install_returning_old(signum) }.map(drop)
+        }
+
+        /// Like [`Self::install`], but also returns the action that was previously associated
+        /// with `signum`, which can be re-installed later to restore exactly that, including its
+        /// flags and mask.
+        ///
+        /// # Errors
+        /// If `sigaction()` does.  `errno` is set to indicate the error.
+        ///
+        /// # Safety
+        /// The creator of `self` must ensure that its handler is async-signal-safe.
+        #[allow(clippy::result_unit_err)]
+        #[inline]
+        pub unsafe fn install_returning_old(self, signum: SignalNumber) -> Result<Self, ()> {
             #[cfg(debug_assertions)]
             {
                 let sa_flags = self.sa_flags_ptr();
@@ -255,12 +376,74 @@ mod sigaction {
             // SAFETY: Each of the constructors of `Self` sufficiently initializes by itself and
             // further builder methods ensure the initialization remains proper.
             let act = unsafe { self.0.assume_init() };
-            // SAFETY: The arguments are proper, because `act` was initialized.
-            let r = unsafe { libc::sigaction(signum, &act, ptr::null_mut()) };
-            if r == 0 { Ok(()) } else { Err(()) }
-            // MAYBE: In the future, the previously-associated action could be returned as a new
-            // `Self` which would already be initialized (via the 3rd arg of the `sigaction()`
-            // above).
+            let mut old = MaybeUninit::<libc::sigaction>::zeroed();
+            // SAFETY: The arguments are proper, because `act` was initialized.  The `old` is
+            // fully initialized by a successful call.
+            let r = unsafe { libc::sigaction(signum, &act, old.as_mut_ptr()) };
+            if r == 0 { Ok(Self(old)) } else { Err(()) }
+        }
+
+        /// Get the action that is currently associated with `signum`, without changing it.
+        ///
+        /// # Errors
+        /// If `sigaction()` does.  `errno` is set to indicate the error.
+        #[allow(clippy::result_unit_err)]
+        #[inline]
+        pub fn current(signum: SignalNumber) -> Result<Self, ()> {
+            let mut old = MaybeUninit::<libc::sigaction>::zeroed();
+            // SAFETY: The arguments are proper, and a null `act` only queries.
+            let r = unsafe { libc::sigaction(signum, ptr::null(), old.as_mut_ptr()) };
+            if r == 0 { Ok(Self(old)) } else { Err(()) }
+        }
+
+        /// Whether the `.sa_handler` field is `handler`, of the non-`SA_SIGINFO` type.
+        #[must_use]
+        #[inline]
+        pub fn is_handler(&self, handler: Handler) -> bool {
+            let sa_flags = self.sa_flags_ptr();
+            // SAFETY: `sa_flags` is valid, aligned, initialized, and `Copy`.
+            let flags = unsafe { *sa_flags };
+            let sa_handler = self.sa_handler_ptr();
+            // SAFETY: `sa_handler` is valid, aligned, initialized, and `Copy`.
+            let addr = unsafe { *sa_handler };
+            #[allow(clippy::fn_to_numeric_cast_any, clippy::as_conversions)]
+            let handler = handler as usize;
+            flags & libc::SA_SIGINFO == 0 && addr == handler
+        }
+
+        /// Whether the `.sa_sigaction` field is `handler`, of the `SA_SIGINFO` type.
+        #[must_use]
+        #[inline]
+        pub fn is_handler_with_info(&self, handler: HandlerWithInfo) -> bool {
+            let sa_flags = self.sa_flags_ptr();
+            // SAFETY: `sa_flags` is valid, aligned, initialized, and `Copy`.
+            let flags = unsafe { *sa_flags };
+            let sa_sigaction = self.sa_sigaction_ptr();
+            // SAFETY: `sa_sigaction` is valid, aligned, initialized, and `Copy`.
+            let addr = unsafe { *sa_sigaction };
+            #[allow(clippy::fn_to_numeric_cast_any, clippy::as_conversions)]
+            let handler = handler as usize;
+            flags & libc::SA_SIGINFO != 0 && addr == handler
+        }
+
+        /// Which kind of disposition this is.
+        #[inline]
+        pub fn disposition(&self) -> super::Disposition {
+            let sa_flags = self.sa_flags_ptr();
+            // SAFETY: `sa_flags` is valid, aligned, initialized, and `Copy`.
+            let flags = unsafe { *sa_flags };
+            let sa_handler = self.sa_handler_ptr();
+            // SAFETY: `sa_handler` is valid, aligned, initialized, and `Copy`.
+            let addr = unsafe { *sa_handler };
+            if flags & libc::SA_SIGINFO != 0 {
+                super::Disposition::Custom
+            } else if addr == libc::SIG_DFL {
+                super::Disposition::Default
+            } else if addr == libc::SIG_IGN {
+                super::Disposition::Ignore
+            } else {
+                super::Disposition::Custom
+            }
         }
     }
 }
@@ -354,6 +537,9 @@ unsafe fn sigset_empty(set: *mut libc::sigset_t) {
     debug_abort_assert_eq!(0, _r, b"`sigemptyset()` never errors");
 }
 
+pub use sig_set::SigSet;
+mod sig_set;
+
 
 /// Changes the calling thread's signal mask to "block" (prevent from being delivered) almost all
 /// signals.
@@ -364,7 +550,64 @@ unsafe fn sigset_empty(set: *mut libc::sigset_t) {
 #[doc = async_signal_and_fork_safe!()]
 #[inline]
 pub fn mask_all_signals_of_current_thread() {
-    change_signal_mask_of_current_thread(sigset_all_usual, libc::SIG_BLOCK);
+    let _old = change_signal_mask_of_current_thread(MaskOp::Block, &SigSet::full_usual());
+}
+
+/// Like [`mask_all_signals_of_current_thread`], but returns a guard that restores the calling
+/// thread's previous mask when dropped.  E.g. for a library that temporarily blocks signals
+/// around a critical section, without disturbing the application's long-term mask.
+#[doc = async_signal_and_fork_safe!()]
+#[inline]
+pub fn mask_all_signals_of_current_thread_saving() -> MaskGuard {
+    mask_signals_of_current_thread_saving(&SigSet::full_usual())
+}
+
+/// Like [`mask_all_signals_of_current_thread_saving`], but only "blocks" the given signals (in
+/// addition to those already blocked).  E.g. to block the signals whose handlers are being
+/// installed, so that none of them can be delivered to the calling thread while only some of
+/// their handlers are installed.
+///
+/// Unlike `mask_all_signals_of_current_thread_saving`, this doesn't except any signals, and so
+/// it's the caller's responsibility to not give those that shouldn't be blocked.
+#[doc = async_signal_and_fork_safe!()]
+#[inline]
+pub fn mask_signals_of_current_thread_saving(set: &SigSet) -> MaskGuard {
+    let saved = change_signal_mask_of_current_thread(MaskOp::Block, set);
+    MaskGuard { saved, _not_send: PhantomData }
+}
+
+/// Guard returned by [`mask_all_signals_of_current_thread_saving`] (or
+/// [`mask_signals_of_current_thread_saving`]) that restores the saved signal mask of the thread
+/// when dropped.
+///
+/// This is `!Send`, because a signal mask is per-thread, and so it must be dropped by the same
+/// thread.
+#[must_use = "dropping this immediately restores the mask"]
+pub struct MaskGuard {
+    saved:     SigSet,
+    /// The pointer makes this `!Send`.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl MaskGuard {
+    /// The mask that the thread had before, which will be restored.
+    #[must_use]
+    #[inline]
+    pub fn saved(&self) -> &SigSet { &self.saved }
+}
+
+impl Drop for MaskGuard {
+    #[inline]
+    fn drop(&mut self) {
+        let _masked = change_signal_mask_of_current_thread(MaskOp::SetMask, &self.saved);
+    }
+}
+
+impl Debug for MaskGuard {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaskGuard").field("saved", &self.saved).finish_non_exhaustive()
+    }
 }
 
 /// Changes the calling thread's signal mask to not "block" (to allow to be delivered) all
@@ -372,37 +615,335 @@ pub fn mask_all_signals_of_current_thread() {
 #[doc = async_signal_and_fork_safe!()]
 #[inline]
 pub fn unmask_all_signals_of_current_thread() {
-    change_signal_mask_of_current_thread(sigset_empty, libc::SIG_SETMASK);
+    let _old = change_signal_mask_of_current_thread(MaskOp::SetMask, &SigSet::empty());
+}
+
+/// Changes the calling thread's signal mask to not "block" (to allow to be delivered) the given
+/// signals.  Other signals remain as they were.
+#[doc = async_signal_and_fork_safe!()]
+#[inline]
+pub fn unmask_signals_of_current_thread(set: &SigSet) {
+    let _old = change_signal_mask_of_current_thread(MaskOp::Unblock, set);
 }
 
-/// This is async-signal-safe if `sigset_func` is.
-fn change_signal_mask_of_current_thread(
-    sigset_func: unsafe fn(set: *mut libc::sigset_t),
-    how: core::ffi::c_int,
-) {
-    use core::{mem::MaybeUninit, ptr};
+/// How [`set_thread_signal_mask`] changes the calling thread's signal mask.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[non_exhaustive]
+pub enum MaskOp {
+    /// `SIG_BLOCK`: Add the given signals to the mask, to "block" them.
+    Block,
+    /// `SIG_UNBLOCK`: Remove the given signals from the mask, to allow them to be delivered.
+    Unblock,
+    /// `SIG_SETMASK`: Replace the mask with exactly the given signals.
+    SetMask,
+}
 
-    let set = {
-        let mut set = MaybeUninit::<libc::sigset_t>::zeroed();
-        // SAFETY: The argument is valid, aligned, and unaliased. It's allowed to be
-        // uninitialized.  `sigset_func` is only one of our two helper functions.
-        unsafe {
-            sigset_func(set.as_mut_ptr());
+impl MaskOp {
+    const fn how(self) -> core::ffi::c_int {
+        match self {
+            Self::Block => libc::SIG_BLOCK,
+            Self::Unblock => libc::SIG_UNBLOCK,
+            Self::SetMask => libc::SIG_SETMASK,
         }
-        // SAFETY: We just initialized it.
-        unsafe { set.assume_init() }
-    };
+    }
+}
 
-    // SAFETY: The arguments are proper, because `set` was initialized and `how` is only one of
-    // the allowed values.
-    let _r = unsafe { libc::pthread_sigmask(how, &set, ptr::null_mut()) };
+/// Changes the calling thread's signal mask, per `how`, for only the given signals.  E.g. for a
+/// dedicated thread to unblock only the signals that it's responsible for, while the others
+/// remain blocked.
+///
+/// Unlike [`mask_all_signals_of_current_thread`], this doesn't except any signals, and so it's
+/// the caller's responsibility to not block those that shouldn't be.
+#[doc = async_signal_and_fork_safe!()]
+///
+/// # Errors
+/// If any of `signums` isn't a valid signal number, in which case the mask isn't changed.
+#[inline]
+pub fn set_thread_signal_mask(how: MaskOp, signums: &[SignalNumber]) -> Result<(), Errno> {
+    let set = SigSet::from_signals(signums)?;
+    let _old = change_signal_mask_of_current_thread(how, &set);
+    Ok(())
+}
+
+/// Terminate the process as if `signum` were delivered without being handled, so that the exit
+/// status of the process indicates termination by that signal (which e.g. a shell shows as
+/// `128 + signum`).  E.g. to "die like normal" after doing some cleanup when handling `SIGTERM`.
+///
+/// This resets the disposition of `signum` to its default, unblocks it for the calling thread
+/// (which is needed when the calling thread masks it, as the consuming thread usually does),
+/// and raises it for the calling thread.
+///
+/// This only returns if the default action of `signum` isn't to terminate (e.g. `SIGCHLD`), or
+/// if it's an invalid signal number.
+#[doc = async_signal_and_fork_safe!()]
+#[inline]
+pub fn terminate_by_default(signum: SignalNumber) {
+    let action = SigAction::default();
+    // SAFETY: `SIG_DFL` handling is async-signal-safe, because no user function is called.
+    let Ok(()) = (unsafe { action.install(signum) }) else { return };
+
+    if let Ok(set) = SigSet::from_signals(&[signum]) {
+        unmask_signals_of_current_thread(&set);
+    }
+
+    // SAFETY: The argument is proper.
+    let _r = unsafe { libc::raise(signum) };
+}
+
+/// Get which kind of disposition is currently associated with `signum`, without changing it.
+/// E.g. to detect that the application already handles a signal, to warn instead of replacing
+/// its handler.
+///
+/// # Errors
+/// If `sigaction()` does, e.g. for an invalid signal number.  `errno` is set to indicate the
+/// error.
+#[doc = async_signal_and_fork_safe!()]
+#[allow(clippy::result_unit_err)]
+#[inline]
+pub fn current_disposition(signum: SignalNumber) -> Result<Disposition, ()> {
+    SigAction::current(signum).map(|action| action.disposition())
+}
+
+/// The kinds of disposition that a signal can have, as given by [`current_disposition`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Disposition {
+    /// `SIG_DFL`, the default action of the signal.
+    Default,
+    /// `SIG_IGN`, ignoring the signal.
+    Ignore,
+    /// A handler function, of either type, e.g. as installed by this crate.
+    Custom,
+}
+
+/// The name of `signum`, e.g. `"SIGTERM"`, for logging.  Only the common signals, as numbered
+/// on the current OS, are known, and `None` is returned for others, including the real-time
+/// signals.  Aliases (e.g. `SIGIOT` of `SIGABRT`) are given as the primary name.
+#[doc = async_signal_and_fork_safe!()]
+#[must_use]
+#[inline]
+pub const fn signal_name(signum: SignalNumber) -> Option<&'static str> {
+    Some(match signum {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGUSR1 => "SIGUSR1",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGUSR2 => "SIGUSR2",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGCHLD => "SIGCHLD",
+        libc::SIGCONT => "SIGCONT",
+        libc::SIGSTOP => "SIGSTOP",
+        libc::SIGTSTP => "SIGTSTP",
+        libc::SIGTTIN => "SIGTTIN",
+        libc::SIGTTOU => "SIGTTOU",
+        libc::SIGURG => "SIGURG",
+        libc::SIGXCPU => "SIGXCPU",
+        libc::SIGXFSZ => "SIGXFSZ",
+        libc::SIGVTALRM => "SIGVTALRM",
+        libc::SIGPROF => "SIGPROF",
+        libc::SIGWINCH => "SIGWINCH",
+        libc::SIGIO => "SIGIO",
+        libc::SIGSYS => "SIGSYS",
+        // The same platforms as for `SIGEMT` in `sigset_all_usual`.
+        #[cfg(any(
+            not(target_os = "linux"),
+            all(
+                target_os = "linux",
+                any(
+                    target_arch = "sparc",
+                    target_arch = "sparc64",
+                    target_arch = "mips",
+                    target_arch = "mips64"
+                )
+            )
+        ))]
+        libc::SIGEMT => "SIGEMT",
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        libc::SIGPWR => "SIGPWR",
+        #[cfg(any(
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "macos"
+        ))]
+        libc::SIGINFO => "SIGINFO",
+        _ => return None,
+    })
+}
+
+/// Returns the mask as it was before the change.
+#[doc = async_signal_and_fork_safe!()]
+fn change_signal_mask_of_current_thread(how: MaskOp, set: &SigSet) -> SigSet {
+    let mut old = SigSet::empty();
+    // SAFETY: The arguments are proper, because `set` is initialized, `how` is only one of the
+    // allowed values, and `old` is valid for writing.
+    let _r = unsafe { libc::pthread_sigmask(how.how(), set.as_raw(), old.as_raw_mut()) };
     debug_abort_assert_eq!(0, _r, b"will succeed");
+    old
+}
+
+
+/// Which clock the timestamp-capturing features use.
+///
+/// Both are read via `clock_gettime()`, which is async-signal-safe (and is usually done via the
+/// vDSO without a system call).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[non_exhaustive]
+pub enum ClockSource {
+    /// `CLOCK_MONOTONIC`, which is portable, but which doesn't advance while the system is
+    /// suspended.
+    #[default]
+    Monotonic,
+    /// `CLOCK_BOOTTIME`, which also counts the time while the system is suspended, so that
+    /// e.g. "time since last signal" is correct across a laptop's sleep.  It's Linux-specific,
+    /// and so `CLOCK_MONOTONIC` is used instead on other OSs.
+    Boottime,
+}
+
+impl ClockSource {
+    fn clock_id(self) -> libc::clockid_t {
+        match self {
+            Self::Monotonic => libc::CLOCK_MONOTONIC,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Self::Boottime => libc::CLOCK_BOOTTIME,
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            Self::Boottime => libc::CLOCK_MONOTONIC,
+        }
+    }
+
+    /// The current time of the clock, in nanoseconds.
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn now_nanos(self) -> u64 { clock_nanos(self.clock_id()) }
+}
+
+/// Which clock a timed wait, e.g. of [`crate::consume_loop_timed`], measures its timeout by.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[non_exhaustive]
+pub enum WaitClock {
+    /// `CLOCK_MONOTONIC`, which isn't affected by adjustments of the system's time (e.g. by NTP,
+    /// or manually), and so the wait is as long as the timeout.
+    ///
+    /// With glibc, this waits via `sem_clockwait()` by this clock directly.  Elsewhere, which
+    /// doesn't have that, this waits via `sem_timedwait()` by a deadline of `CLOCK_REALTIME`,
+    /// but the time that actually elapsed is measured by this clock, and so if the system's time
+    /// is adjusted forward, which would end the wait too early, then this waits again for the
+    /// rest.  An adjustment backward can still lengthen the wait then, because that can't be
+    /// noticed while blocked.
+    #[default]
+    Monotonic,
+    /// `CLOCK_REALTIME`, as plain `sem_timedwait()` waits by, without any correction, and so
+    /// adjustments of the system's time do shorten or lengthen the wait.  E.g. for a timeout
+    /// that should follow the wall clock.
+    Realtime,
+}
+
+/// The current time of the given clock, in nanoseconds.
+#[doc = async_signal_and_fork_safe!()]
+fn clock_nanos(clock_id: libc::clockid_t) -> u64 {
+    let ts = clock_now(clock_id);
+    let secs = u64::try_from(ts.tv_sec).unwrap_or(0);
+    let nanos = u64::try_from(ts.tv_nsec).unwrap_or(0);
+    secs.saturating_mul(1_000_000_000).saturating_add(nanos)
+}
+
+/// The current time of the given clock.
+#[doc = async_signal_and_fork_safe!()]
+fn clock_now(clock_id: libc::clockid_t) -> libc::timespec {
+    use core::mem::MaybeUninit;
+
+    let mut ts = MaybeUninit::<libc::timespec>::zeroed();
+    // SAFETY: The arguments are proper, and `ts` is valid, aligned, and unaliased.
+    let _r = unsafe { libc::clock_gettime(clock_id, ts.as_mut_ptr()) };
+    debug_abort_assert_eq!(0, _r, b"`clock_gettime()` will succeed");
+    // SAFETY: It was zeroed, and `clock_gettime()` initialized it.
+    unsafe { ts.assume_init() }
+}
+
+/// The absolute time that is `timeout` from now, by the given clock, as `sem_timedwait()` and
+/// `sem_clockwait()` require.  Saturates at the greatest time that can be represented.
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn deadline(
+    clock_id: libc::clockid_t,
+    timeout: core::time::Duration,
+) -> libc::timespec {
+    const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+    let mut deadline = clock_now(clock_id);
+    let secs = libc::time_t::try_from(timeout.as_secs()).unwrap_or(libc::time_t::MAX);
+    deadline.tv_sec = deadline.tv_sec.saturating_add(secs);
+    // Both are less than a second, and so this can't overflow.
+    let nanos =
+        u32::try_from(deadline.tv_nsec).unwrap_or(0).saturating_add(timeout.subsec_nanos());
+    let nanos = if nanos >= NANOS_PER_SEC {
+        deadline.tv_sec = deadline.tv_sec.saturating_add(1);
+        nanos.saturating_sub(NANOS_PER_SEC)
+    } else {
+        nanos
+    };
+    #[allow(clippy::unnecessary_fallible_conversions)] // Not infallible on all platforms.
+    {
+        deadline.tv_nsec = nanos.try_into().unwrap_or(0);
+    }
+    deadline
 }
 
 
 /// An async-signal-safe "panic" that can be used from within a signal handler.
 #[inline]
-pub(crate) fn abort(msg: &[u8]) -> ! {
+pub(crate) fn abort(msg: &[u8]) -> ! { abort_with(&[msg]) }
+
+/// Like [`abort`], but `num` is also written, in decimal, after `msg`.  E.g. to tell which signal
+/// number was wrong.
+#[cfg_attr(any(not(debug_assertions), feature = "no_handler_debug_check"), allow(dead_code))]
+#[inline]
+pub(crate) fn abort_with_num(msg: &[u8], num: SignalNumber) -> ! {
+    let mut buf = [0; DECIMAL_LEN];
+    abort_with(&[msg, decimal(num, &mut buf)])
+}
+
+/// Enough for any `SignalNumber`, including the sign.
+const DECIMAL_LEN: usize = 11;
+
+/// Render `num` in decimal, without allocating, into the end of `buf`, and return that part.
+///
+/// This is async-signal-safe.
+fn decimal(num: SignalNumber, buf: &mut [u8; DECIMAL_LEN]) -> &[u8] {
+    #![allow(clippy::integer_division)]
+
+    let mut rest = num.unsigned_abs();
+    let mut start = buf.len();
+    loop {
+        start = start.saturating_sub(1);
+        let digit = u8::try_from(rest % 10).unwrap_or(0);
+        if let Some(b) = buf.get_mut(start) {
+            *b = b'0'.saturating_add(digit);
+        }
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    if num < 0 {
+        start = start.saturating_sub(1);
+        if let Some(b) = buf.get_mut(start) {
+            *b = b'-';
+        }
+    }
+    buf.get(start ..).unwrap_or_default()
+}
+
+fn abort_with(msg_parts: &[&[u8]]) -> ! {
     fn ewrite(msg: &[u8]) {
         use core::{ffi::c_void, hint};
         const LIMIT: u16 = 10;
@@ -426,7 +967,9 @@ pub(crate) fn abort(msg: &[u8]) -> ! {
     }
 
     ewrite(b"Internal Abort: ");
-    ewrite(msg);
+    for part in msg_parts {
+        ewrite(part);
+    }
     ewrite(b"\n");
 
     // SAFETY: The call is proper.
@@ -434,3 +977,28 @@ pub(crate) fn abort(msg: &[u8]) -> ! {
         libc::abort();
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal() {
+        let mut buf = [0; DECIMAL_LEN];
+        assert_eq!(super::decimal(0, &mut buf), b"0");
+        assert_eq!(super::decimal(10, &mut buf), b"10");
+        assert_eq!(super::decimal(-7, &mut buf), b"-7");
+        assert_eq!(super::decimal(SignalNumber::MAX, &mut buf), b"2147483647");
+        assert_eq!(super::decimal(SignalNumber::MIN, &mut buf), b"-2147483648");
+    }
+
+    #[test]
+    fn signal_name() {
+        assert_eq!(super::signal_name(libc::SIGTERM), Some("SIGTERM"));
+        assert_eq!(super::signal_name(libc::SIGIOT), Some("SIGABRT"));
+        assert_eq!(super::signal_name(crate::resolve_signum(crate::rt(0))), None);
+        assert_eq!(super::signal_name(0), None);
+        assert_eq!(super::signal_name(-1), None);
+    }
+}
@@ -0,0 +1,80 @@
+use core::{ffi::c_void,
+           mem::MaybeUninit,
+           ptr::{self, NonNull}};
+use errno::{errno, Errno};
+
+
+/// Allocate a stack of `size` bytes, and register it with `sigaltstack()` as the alternate signal
+/// stack of the current thread, on which handlers installed with `SA_ONSTACK` will execute.  E.g.
+/// so that a signal caused by overflowing the normal stack can still be handled.
+///
+/// This is only for the current thread, and so it must be done by each thread that a signal
+/// could be delivered to whose handler was installed with `SA_ONSTACK` (e.g. by
+/// [`crate::install_handler_on_altstack`]), else that handler executes on the normal stack of
+/// such a thread.
+///
+/// The stack is allocated via `mmap()`, which doesn't need an allocator.  Dropping the returned
+/// [`AltStack`] restores the previous alternate stack (or lack of one) and frees the stack.
+///
+/// # Errors
+/// If `size` is too small (less than `MINSIGSTKSZ`), or if allocating or registering fails.
+/// `errno` is returned to indicate the error.
+#[inline]
+pub fn install_alt_stack(size: usize) -> Result<AltStack, Errno> {
+    // SAFETY: The arguments are proper, for an anonymous mapping.
+    let m = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if m == libc::MAP_FAILED {
+        return Err(errno());
+    }
+    let map = NonNull::new(m).ok_or(Errno(libc::ENOMEM))?;
+
+    let new = libc::stack_t { ss_sp: map.as_ptr(), ss_flags: 0, ss_size: size };
+    let mut old = MaybeUninit::<libc::stack_t>::zeroed();
+    // SAFETY: The arguments are proper, because `new` describes our mapping and `old` is valid,
+    // aligned, and unaliased.
+    let r = unsafe { libc::sigaltstack(&new, old.as_mut_ptr()) };
+    if r == 0 {
+        // SAFETY: It was zeroed, and `sigaltstack()` initialized it.
+        let old = unsafe { old.assume_init() };
+        Ok(AltStack { map, size, old })
+    } else {
+        let e = errno();
+        // SAFETY: The arguments are those of our mapping, which isn't used.
+        let _r = unsafe { libc::munmap(map.as_ptr(), size) };
+        Err(e)
+    }
+}
+
+
+/// Represents that an alternate signal stack, as made by [`install_alt_stack`], is registered
+/// for the current thread.  Dropping this restores the previous one and frees this one.
+///
+/// This isn't `Send`, because it must be dropped by the same thread that it was made by.
+#[derive(Debug)]
+pub struct AltStack {
+    map:  NonNull<c_void>,
+    size: usize,
+    old:  libc::stack_t,
+}
+
+impl Drop for AltStack {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: The arguments are proper, because `self.old` was given by `sigaltstack()`.
+        let r = unsafe { libc::sigaltstack(&self.old, ptr::null_mut()) };
+        // Fails only if a handler is executing on our stack, in which case it can't be freed.
+        if r == 0 {
+            // SAFETY: The arguments are those of our mapping, which isn't used anymore.
+            let _r = unsafe { libc::munmap(self.map.as_ptr(), self.size) };
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use super::{SigAction, SigSet};
+use crate::{AtomicUInt as _, SignalNumber};
+use core::sync::atomic::{AtomicBool, AtomicU64,
+                         Ordering::{Acquire, Relaxed, Release}};
+
+
+/// How many signal numbers, starting from zero, have a counter.  Enough for all the OSs we know
+/// of (e.g. `SIGRTMAX` is 64 on Linux and 126 on FreeBSD).  Greater ones are skipped.
+const SLOTS: usize = 129;
+
+/// The counter of each signal number, indexed by the number.
+static COUNTS: [AtomicU64; SLOTS] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicU64 = AtomicU64::new(0);
+    [ZERO; SLOTS]
+};
+
+/// Whether a [`CatchAllHandle`] currently exists.
+static IS_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+
+/// The signal handler for all the signal numbers, that only counts.
+extern "C" fn catch_all_handler(signo: SignalNumber) {
+    if let Some(counter) = usize::try_from(signo).ok().and_then(|i| COUNTS.get(i)) {
+        let _new = counter.saturating_incr();
+    }
+}
+
+
+/// Install a handler, that only counts deliveries, for every catchable signal, to observe all
+/// the signals that the process receives without needing to know which ones in advance.  E.g.
+/// for a diagnostic tool, like a lightweight `strace` of signals.
+///
+/// This is for every signal in [`SigSet::full_usual`] that the OS accepts a handler for, which
+/// excludes those that can't be caught (`SIGKILL`, `SIGSTOP`) and those that indicate
+/// exceptional conditions (e.g. `SIGSEGV`, `SIGABRT`), since only counting those would hide
+/// crashes (or loop forever re-executing a faulting instruction).  Signal numbers that the OS or
+/// the C library rejects (`EINVAL`, e.g. those reserved internally by it) are skipped.
+///
+/// Beware that this has broad side effects: it replaces the default dispositions of very many
+/// signals, and so e.g. `SIGTERM`, `SIGINT`, `SIGHUP`, `SIGPIPE`, and `SIGALRM` will no longer
+/// terminate the process, `SIGTSTP` will no longer stop it, and it also replaces any other
+/// handlers that were installed for these.
+///
+/// The counters are global, and so only one [`CatchAllHandle`] may exist at a time.  Returns
+/// `None` if one already exists.
+#[must_use]
+#[inline]
+pub fn install_catch_all() -> Option<CatchAllHandle> {
+    if IS_INSTALLED.swap(true, Acquire) {
+        return None;
+    }
+    for counter in &COUNTS {
+        counter.store(0, Relaxed);
+    }
+
+    let mut installed = SigSet::empty();
+    let template = SigAction::default().mask_all().restart_intr();
+    for signum in SigSet::full_usual().iter() {
+        if usize::try_from(signum).map_or(true, |i| i >= SLOTS) {
+            continue;
+        }
+        let mut action = template.clone();
+        action.set_handler(catch_all_handler);
+        // SAFETY: `catch_all_handler` is async-signal-safe.
+        if unsafe { action.install(signum) }.is_ok() {
+            let _r = installed.insert(signum);
+        }
+    }
+
+    Some(CatchAllHandle { installed })
+}
+
+
+/// Represents that the handling of [`install_catch_all`] is installed.  Dropping this uninstalls
+/// it, by resetting the dispositions of those signals to their defaults.
+#[derive(Debug)]
+pub struct CatchAllHandle {
+    installed: SigSet,
+}
+
+impl CatchAllHandle {
+    /// The signal numbers that the handler was installed for.
+    #[must_use]
+    #[inline]
+    pub fn signals(&self) -> SigSet { self.installed }
+
+    /// The count of deliveries of each signal number that the handler was installed for, in
+    /// ascending order of the numbers, including those with a count of zero.  The counts are
+    /// saturating, and each is a snapshot as of when it's yielded.
+    #[inline]
+    pub fn counts(&self) -> impl Iterator<Item = (SignalNumber, u64)> + '_ {
+        self.installed.iter().filter_map(|signum| {
+            let counter = usize::try_from(signum).ok().and_then(|i| COUNTS.get(i))?;
+            Some((signum, counter.load(Relaxed)))
+        })
+    }
+}
+
+impl Drop for CatchAllHandle {
+    #[inline]
+    fn drop(&mut self) {
+        for signum in self.installed.iter() {
+            // SAFETY: `SIG_DFL` handling is async-signal-safe, because no user function is
+            // called.
+            let _r = unsafe { SigAction::default().install(signum) };
+        }
+        IS_INSTALLED.store(false, Release);
+    }
+}
@@ -0,0 +1,143 @@
+use super::ClockSource;
+use crate::SignalNumber;
+use core::{ffi::{c_uint, CStr},
+           mem::size_of,
+           ptr::{self, NonNull},
+           slice,
+           sync::atomic::{AtomicU64, Ordering::Relaxed}};
+use errno::{errno, Errno};
+
+
+/// A fixed-capacity ring of records of signal deliveries, kept in a memory-mapped file so that
+/// the records survive a crash of the process, for post-mortem analysis of e.g. why it died.
+///
+/// The layout of the file, as native-endian `u64`s, is: the total count of records ever
+/// recorded, followed by `capacity` records which are each the signal number and the time, in
+/// nanoseconds, of the clock chosen by [`Self::with_clock`] (`CLOCK_MONOTONIC` by default).  When
+/// the ring is full, the oldest records are overwritten, and so the file holds the last
+/// `capacity` records, where the oldest is at the index of the total modulo `capacity` (when the
+/// total exceeds `capacity`).
+///
+/// Recording is done via [`Self::record`], which is async-signal-safe, e.g. from
+/// [`SignalReceipt::on_delivery`](crate::SignalReceipt::on_delivery).
+#[derive(Debug)]
+pub struct CrashLog {
+    map:      NonNull<AtomicU64>,
+    len:      usize,
+    capacity: u64,
+    clock:    ClockSource,
+}
+
+// SAFETY: The mapped memory is owned by `CrashLog` and is only accessed via atomics.
+unsafe impl Send for CrashLog {}
+// SAFETY: The mapped memory is owned by `CrashLog` and is only accessed via atomics.
+unsafe impl Sync for CrashLog {}
+
+impl CrashLog {
+    const WORDS_PER_RECORD: usize = 2;
+
+    /// Create, or truncate, the file at `path`, size it for `capacity` records, and map it into
+    /// memory.
+    ///
+    /// This must be done before installing handlers that record into it, because this is not
+    /// async-signal-safe.  Because this truncates, a log from a previous run should be examined
+    /// (or moved) before this is done with the same `path`.
+    ///
+    /// # Errors
+    /// If `capacity` is zero or too large, or if opening, sizing, or mapping the file fails.
+    /// `errno` is returned to indicate the error.
+    #[inline]
+    pub fn new(path: &CStr, capacity: usize) -> Result<Self, Errno> {
+        let words = capacity
+            .checked_mul(Self::WORDS_PER_RECORD)
+            .and_then(|w| w.checked_add(1))
+            .filter(|_| capacity >= 1);
+        let len = words.and_then(|w| w.checked_mul(size_of::<u64>()));
+        let (Some(words), Some(len)) = (words, len) else { return Err(Errno(libc::EINVAL)) };
+        let file_len = libc::off_t::try_from(len).or(Err(Errno(libc::EFBIG)))?;
+        let capacity = u64::try_from(capacity).or(Err(Errno(libc::EINVAL)))?;
+
+        let flags = libc::O_RDWR | libc::O_CREAT | libc::O_TRUNC | libc::O_CLOEXEC;
+        let mode: c_uint = 0o644;
+        // SAFETY: The arguments are proper, because `path` is a valid C string.
+        let fd = unsafe { libc::open(path.as_ptr(), flags, mode) };
+        if fd == -1 {
+            return Err(errno());
+        }
+
+        // SAFETY: The arguments are proper, because `fd` is open.  Extending the file fills it
+        // with zeros, and so the total starts at zero.
+        let r = unsafe { libc::ftruncate(fd, file_len) };
+        let map = if r == 0 {
+            // SAFETY: The arguments are proper, because `fd` is open and is of size `len`.
+            let m = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    fd,
+                    0,
+                )
+            };
+            if m == libc::MAP_FAILED {
+                Err(errno())
+            } else {
+                Ok(m)
+            }
+        } else {
+            Err(errno())
+        };
+
+        // The mapping remains after the file descriptor is closed.
+        // SAFETY: The argument is proper, because `fd` is open and is only used here.
+        let _r = unsafe { libc::close(fd) };
+
+        let map = NonNull::new(map?.cast::<AtomicU64>()).ok_or(Errno(libc::ENOMEM))?;
+        // The mapping is page-aligned, and so it's aligned for `AtomicU64`.
+        Ok(Self { map, len: words, capacity, clock: ClockSource::default() })
+    }
+
+    /// Use the given clock for the times of the records.
+    #[must_use]
+    #[inline]
+    pub fn with_clock(mut self, clock: ClockSource) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn words(&self) -> &[AtomicU64] {
+        // SAFETY: The mapping is valid for `self.len` zero-initialized words for as long as
+        // `self` lives, and it's only accessed via atomics.
+        unsafe { slice::from_raw_parts(self.map.as_ptr(), self.len) }
+    }
+
+    /// Record delivery of the given signal number, with the current time.
+    ///
+    /// This is async-signal-safe, and so it's safe for this to be called from a signal handler.
+    /// It only does atomic operations on the mapped memory, and it never blocks or allocates.
+    #[inline]
+    pub fn record(&self, signum: SignalNumber) {
+        let words = self.words();
+        if let Some((total, records)) = words.split_first() {
+            let seq = total.fetch_add(1, Relaxed);
+            let index = seq.checked_rem(self.capacity).and_then(|i| usize::try_from(i).ok());
+            let index = index.unwrap_or(0);
+            let start = index.saturating_mul(Self::WORDS_PER_RECORD);
+            if let Some([num, time]) = records.get(start ..).and_then(|r| r.get(.. 2)) {
+                num.store(u64::from(signum.unsigned_abs()), Relaxed);
+                time.store(self.clock.now_nanos(), Relaxed);
+            }
+        }
+    }
+}
+
+impl Drop for CrashLog {
+    /// Unmap the memory.  The file remains, with its records.
+    #[inline]
+    fn drop(&mut self) {
+        let len = self.len.saturating_mul(size_of::<u64>());
+        // SAFETY: The arguments are those of our mapping, which isn't accessed anymore.
+        let _r = unsafe { libc::munmap(self.map.as_ptr().cast(), len) };
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{Receipt, SignalNumber};
+
+
+/// Make a delegate that maps each [`Receipt`] to a task, via the given `task_factory` which is
+/// given the signal number and current count, and gives that task to the given `enqueue`
+/// function or closure.  E.g. for pushing onto the queue of a thread pool, to decouple the
+/// processing of signals from their receipt.
+///
+/// Intended to be given to [`consume_count_then_delegate`](crate::consume_count_then_delegate),
+/// which is called in a normal context (not in a signal handler), and so `enqueue` can access
+/// the queue freely.  (The delegates declared in uses of the [`premade`](crate::premade!) macro
+/// must be non-capturing, and so this isn't usable with that.)
+///
+/// If the queue is bounded and `enqueue` blocks when it's full, that blocks the consuming thread
+/// which delays its processing of further receipts.  Those are still counted meanwhile, and so
+/// are still processed after `enqueue` returns, but possibly coalesced into fewer tasks.
+#[inline]
+pub fn enqueue_on_receipt<U: Copy, B, C, T>(
+    mut enqueue: impl FnMut(T),
+    mut task_factory: impl FnMut(SignalNumber, U) -> T,
+) -> impl FnMut(&mut Receipt<U, B, C>) {
+    move |receipt| enqueue(task_factory(receipt.sig_num, receipt.cur_count))
+}
+
+/// Make a delegate that calls the given `notifier` once per [`Receipt`], e.g. for a signal that
+/// is used as a heartbeat "ping" to prove liveness to a watchdog.  The `notifier` is whatever the
+/// watchdog needs, e.g. `sd_notify("WATCHDOG=1")` or touching a file.
+///
+/// Intended to be given to [`consume_count_then_delegate`](crate::consume_count_then_delegate),
+/// like [`enqueue_on_receipt`].  Because the notification is done by the consuming thread in a
+/// normal context (not in a signal handler), it also proves that that thread is still making
+/// progress on processing receipts, which a notification from the signal handler itself
+/// wouldn't.
+///
+/// Multiple pings received within a single iteration of the consuming loop are coalesced into
+/// one notification.
+#[inline]
+pub fn notify_watchdog<U, B, C>(notifier: impl Fn()) -> impl FnMut(&mut Receipt<U, B, C>) {
+    move |_receipt| notifier()
+}
@@ -0,0 +1,224 @@
+use super::{abort, sigset_all_usual, sigset_empty};
+use crate::SignalNumber;
+use core::{fmt::{self, Debug, Formatter},
+           mem::{size_of, MaybeUninit}};
+use errno::{errno, Errno};
+
+
+/// A set of signal numbers.  A typed wrapper of `sigset_t`.
+///
+/// All the operations are async-signal-safe, because they only use `sigemptyset()`,
+/// `sigfillset()`, `sigaddset()`, `sigdelset()`, and `sigismember()`.
+#[derive(Clone, Copy)]
+pub struct SigSet(libc::sigset_t);
+
+
+impl SigSet {
+    /// Upper bound of the signal numbers that a `sigset_t` could possibly represent.  Numbers
+    /// that are not valid on the platform are never members.
+    const BITS: usize = 8 * size_of::<libc::sigset_t>();
+
+    /// Uses one of our helper functions to initialize a new set.
+    fn init(sigset_func: unsafe fn(set: *mut libc::sigset_t)) -> Self {
+        let mut set = MaybeUninit::<libc::sigset_t>::zeroed();
+        // SAFETY: The argument is valid, aligned, and unaliased. It's allowed to be
+        // uninitialized.  `sigset_func` is only one of our two helper functions.
+        unsafe {
+            sigset_func(set.as_mut_ptr());
+        }
+        // SAFETY: We just initialized it.
+        Self(unsafe { set.assume_init() })
+    }
+
+    /// Make a set that has no signals.
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn empty() -> Self { Self::init(sigset_empty) }
+
+    /// Make a set that has almost all signals.
+    #[doc = except_signals!()]
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn full_usual() -> Self { Self::init(sigset_all_usual) }
+
+    /// Make a set that has only the given signal numbers.
+    #[doc = async_signal_and_fork_safe!()]
+    ///
+    /// # Errors
+    /// If any of the given signal numbers is invalid.  `EINVAL` is returned.
+    #[inline]
+    pub fn from_signals(signums: &[SignalNumber]) -> Result<Self, Errno> {
+        let mut set = Self::empty();
+        for &signum in signums {
+            set.insert(signum)?;
+        }
+        Ok(set)
+    }
+
+    /// Add the given signal number to the set.
+    #[doc = async_signal_and_fork_safe!()]
+    ///
+    /// # Errors
+    /// If the signal number is invalid.  `EINVAL` is returned.
+    #[inline]
+    pub fn insert(&mut self, signum: SignalNumber) -> Result<(), Errno> {
+        // SAFETY: The arguments are proper, because `self.0` was initialized.
+        let r = unsafe { libc::sigaddset(&mut self.0, signum) };
+        if r == 0 { Ok(()) } else { Err(errno()) }
+    }
+
+    /// Remove the given signal number from the set.
+    #[doc = async_signal_and_fork_safe!()]
+    ///
+    /// # Errors
+    /// If the signal number is invalid.  `EINVAL` is returned.
+    #[inline]
+    pub fn remove(&mut self, signum: SignalNumber) -> Result<(), Errno> {
+        // SAFETY: The arguments are proper, because `self.0` was initialized.
+        let r = unsafe { libc::sigdelset(&mut self.0, signum) };
+        if r == 0 { Ok(()) } else { Err(errno()) }
+    }
+
+    /// Whether the given signal number is in the set.  An invalid signal number never is.
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn contains(&self, signum: SignalNumber) -> bool {
+        // SAFETY: The arguments are proper, because `self.0` was initialized.
+        let r = unsafe { libc::sigismember(&self.0, signum) };
+        r == 1
+    }
+
+    /// Iterate over the signal numbers that are in the set, in ascending order.
+    #[doc = async_signal_and_fork_safe!()]
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = SignalNumber> + '_ {
+        (1 ..= Self::BITS)
+            .map_while(|n| SignalNumber::try_from(n).ok())
+            .filter(|&signum| self.contains(signum))
+    }
+
+    /// Make a set that has all the signals that satisfy `pred`.
+    fn from_pred(pred: impl Fn(SignalNumber) -> bool) -> Self {
+        let mut set = Self::empty();
+        for signum in (1 ..= Self::BITS).map_while(|n| SignalNumber::try_from(n).ok()) {
+            if pred(signum) {
+                let _r = set.insert(signum);
+                debug_abort_assert!(_r.is_ok(), b"will succeed, because it's a member of a set");
+            }
+        }
+        set
+    }
+
+    /// Make a set that has the signals that are in either `self` or `other`.
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_pred(|signum| self.contains(signum) || other.contains(signum))
+    }
+
+    /// Make a set that has the signals that are in both `self` and `other`.
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::from_pred(|signum| self.contains(signum) && other.contains(signum))
+    }
+
+    /// Make a set that has the signals that are in `self` but not in `other`.
+    #[doc = async_signal_and_fork_safe!()]
+    #[must_use]
+    #[inline]
+    pub fn difference(&self, other: &Self) -> Self {
+        Self::from_pred(|signum| self.contains(signum) && !other.contains(signum))
+    }
+
+    /// Get the underlying `sigset_t`, e.g. to pass to other `libc` functions.
+    #[must_use]
+    #[inline]
+    pub fn as_raw(&self) -> &libc::sigset_t { &self.0 }
+
+    /// For `libc` functions that give a set.
+    pub(super) fn as_raw_mut(&mut self) -> &mut libc::sigset_t { &mut self.0 }
+}
+
+
+impl Default for SigSet {
+    #[inline]
+    fn default() -> Self { Self::empty() }
+}
+
+impl PartialEq for SigSet {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.iter().eq(other.iter()) }
+}
+
+impl Eq for SigSet {}
+
+impl Debug for SigSet {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use libc::{SIGHUP, SIGINT, SIGKILL, SIGSEGV, SIGTERM, SIGUSR1, SIGUSR2};
+
+    #[test]
+    fn basic() {
+        let mut s = SigSet::empty();
+        assert!(s.iter().next().is_none());
+        assert_eq!(s, SigSet::default());
+        s.insert(SIGUSR1).unwrap();
+        s.insert(SIGHUP).unwrap();
+        assert!(s.contains(SIGUSR1) && s.contains(SIGHUP) && !s.contains(SIGUSR2));
+        assert!(s.iter().eq([SIGHUP, SIGUSR1]));
+        s.remove(SIGUSR1).unwrap();
+        s.remove(SIGUSR2).unwrap();
+        assert!(s.iter().eq([SIGHUP]));
+        assert_eq!(s.insert(0), Err(Errno(libc::EINVAL)));
+        assert_eq!(s.insert(-1), Err(Errno(libc::EINVAL)));
+        assert!(!s.contains(0));
+    }
+
+    #[test]
+    fn full_usual() {
+        let s = SigSet::full_usual();
+        assert!(s.contains(SIGHUP) && s.contains(SIGTERM) && s.contains(SIGUSR2));
+        assert!(!s.contains(SIGSEGV) && !s.contains(SIGKILL));
+    }
+
+    #[test]
+    fn set_ops() {
+        let a = SigSet::from_signals(&[SIGHUP, SIGINT, SIGUSR1]).unwrap();
+        let b = SigSet::from_signals(&[SIGINT, SIGUSR1, SIGUSR2, SIGTERM]).unwrap();
+        let empty = SigSet::empty();
+
+        assert!(a.union(&b).iter().eq([SIGHUP, SIGINT, SIGUSR1, SIGUSR2, SIGTERM]));
+        assert!(a.intersection(&b).iter().eq([SIGINT, SIGUSR1]));
+        assert!(a.difference(&b).iter().eq([SIGHUP]));
+        assert!(b.difference(&a).iter().eq([SIGUSR2, SIGTERM]));
+
+        assert_eq!(a.union(&empty), a);
+        assert_eq!(a.intersection(&empty), empty);
+        assert_eq!(a.difference(&empty), a);
+        assert_eq!(a.difference(&a), empty);
+        assert_eq!(a.union(&b), b.union(&a));
+        assert_eq!(a.intersection(&b), b.intersection(&a));
+
+        let full = SigSet::full_usual();
+        assert_eq!(full.intersection(&a), a);
+        assert_eq!(full.union(&a), full);
+        assert_eq!(empty.difference(&full), empty);
+
+        assert_eq!(SigSet::from_signals(&[SIGHUP, 0]), Err(Errno(libc::EINVAL)));
+    }
+}
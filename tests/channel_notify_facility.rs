@@ -1,6 +1,7 @@
 #![cfg(test)] // Suppress `clippy::tests_outside_test_module`.
 #![allow(
     clippy::assertions_on_result_states,
+    clippy::little_endian_bytes,
     clippy::shadow_unrelated,
     clippy::unreachable,
     clippy::unwrap_used,
@@ -9,16 +10,17 @@
 
 use channel_notify_facility_premade::SignalsChannel;
 use libc::{SIGURG, SIGUSR1, SIGUSR2};
-use signals_receipts::{channel_notify_facility::{FinishError, InstallError, Receiver,
-                                                 SendError, Sender, SignalsChannel as _,
+use signals_receipts::{channel_notify_facility::{FacilityStateKind, FinishError, InstallError,
+                                                 Receiver, SendError, Sender,
+                                                 SignalsChannel as _, TryRecvError,
                                                  UninstallError},
                        SignalNumber};
-use std::{sync::mpsc::{self, TryRecvError},
+use std::{sync::{mpsc, Mutex},
           thread};
 
 #[path = "help/util.rs"]
 mod util;
-use util::spawn_raise;
+use util::{serial, spawn_raise};
 
 
 signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2, }
@@ -54,12 +56,10 @@ impl Sender for CustomSender {
 #[test]
 #[allow(clippy::too_many_lines)]
 fn main() {
-    trait Recv<N> {
-        fn recv(&self) -> N;
-    }
-    impl<N, S> Recv<N> for Receiver<N, S> {
-        fn recv(&self) -> N { self.as_ref().recv().unwrap() }
-    }
+    static TRANSITIONS: Mutex<Vec<FacilityStateKind>> = Mutex::new(Vec::new());
+
+    let _serial = serial();
+    SignalsChannel::set_state_observer(|kind| TRANSITIONS.lock().unwrap().push(kind));
 
     assert!(SignalsChannel::is_finished());
     assert!(matches!(
@@ -71,10 +71,22 @@ fn main() {
     let receiver: Receiver<SignalNumber, _> = SignalsChannel::install(None).unwrap();
     assert!(SignalsChannel::is_installed());
 
+    assert!(!receiver.has_pending());
+    spawn_raise(SIGUSR1);
+    while !receiver.has_pending() {
+        thread::yield_now();
+    }
+    assert!(receiver.has_pending()); // Still, because it wasn't consumed.
+    assert_eq!(receiver.try_recv().unwrap(), SIGUSR1);
+    assert!(!receiver.has_pending());
+
     spawn_raise(SIGUSR1);
-    assert_eq!(receiver.recv(), SIGUSR1);
+    assert_eq!(receiver.recv().unwrap(), SIGUSR1);
     spawn_raise(SIGUSR2);
-    assert_eq!(receiver.recv(), SIGUSR2);
+    assert_eq!(receiver.recv().unwrap(), SIGUSR2);
+    assert_eq!(receiver.try_iter().next(), None);
+    spawn_raise(SIGUSR1);
+    assert_eq!(receiver.iter().next(), Some(SIGUSR1));
 
     let r = SignalsChannel::uninstall_with_outside_channel();
     assert!(matches!(r, Err(UninstallError::WrongMethod)));
@@ -88,22 +100,22 @@ fn main() {
         additional::SignalsChannel::install::<SignalNumber>(Some(1)).unwrap()
     };
     spawn_raise(SIGURG);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     spawn_raise(SIGURG);
     spawn_raise(SIGUSR2);
-    assert_eq!(receiver.recv(), SIGUSR2);
+    assert_eq!(receiver.recv().unwrap(), SIGUSR2);
     spawn_raise(SIGURG);
     spawn_raise(SIGUSR1);
-    assert_eq!(receiver.recv(), SIGUSR1);
-    assert_eq!(additional.recv(), SIGURG);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(receiver.recv().unwrap(), SIGUSR1);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     let r = SignalsChannel::uninstall(receiver);
     assert!(r.is_ok());
     assert!(SignalsChannel::is_dormant());
     spawn_raise(SIGURG);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     let receiver: Receiver<CustomRepr, _> = SignalsChannel::install(Some(0)).unwrap();
     assert!(SignalsChannel::is_installed());
@@ -120,16 +132,16 @@ fn main() {
 
     spawn_raise(SIGUSR2); // Ignored and not sent - `try_from` fails for this.
     spawn_raise(SIGUSR1);
-    assert!(matches!(receiver.recv(), CustomRepr::UserDefOne));
-    assert!(matches!(receiver.as_ref().try_recv(), Err(TryRecvError::Empty)));
+    assert!(matches!(receiver.recv().unwrap(), CustomRepr::UserDefOne));
+    assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
     spawn_raise(SIGUSR1);
-    assert!(matches!(receiver.recv(), CustomRepr::UserDefOne));
+    assert!(matches!(receiver.recv().unwrap(), CustomRepr::UserDefOne));
 
     let r = SignalsChannel::finish(receiver);
     assert!(r.is_ok());
     assert!(SignalsChannel::is_finished());
     spawn_raise(SIGURG);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     let (sender, receiver) = mpsc::channel::<CustomRepr>();
     SignalsChannel::install_with_outside_channel(sender).unwrap();
@@ -149,7 +161,7 @@ fn main() {
     spawn_raise(SIGURG);
     spawn_raise(SIGUSR1);
     assert!(matches!(t.join().unwrap(), Ok(CustomRepr::UserDefOne)));
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     let r = SignalsChannel::uninstall_with_outside_channel();
     assert!(r.is_ok());
@@ -157,7 +169,7 @@ fn main() {
     // `receiver` was already dropped (when `t` finished), as required by
     // `uninstall_with_outside_channel`.
     spawn_raise(SIGURG);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     let (sender, receiver) = mpsc::sync_channel(1);
     SignalsChannel::install_with_outside_channel(CustomSender(sender)).unwrap();
@@ -165,7 +177,7 @@ fn main() {
     spawn_raise(SIGURG);
     spawn_raise(SIGUSR2);
     assert_eq!(receiver.recv().unwrap(), SIGUSR2);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
 
     let r = SignalsChannel::finish_with_outside_channel();
     assert!(r.is_ok());
@@ -176,7 +188,14 @@ fn main() {
     ));
     assert!(SignalsChannel::is_finished());
     spawn_raise(SIGURG);
-    assert_eq!(additional.recv(), SIGURG);
+    assert_eq!(additional.recv().unwrap(), SIGURG);
+
+    {
+        use FacilityStateKind::{Dormant, Installed, Nothing};
+        assert_eq!(*TRANSITIONS.lock().unwrap(), [
+            Installed, Dormant, Installed, Nothing, Installed, Dormant, Installed, Nothing
+        ]);
+    }
 }
 
 
@@ -184,3 +203,545 @@ fn main() {
 fn without_commas() {
     signals_receipts::channel_notify_facility! { SIGALRM SIGCHLD SIGHUP SIGTTOU SIGXFSZ }
 }
+
+
+mod ack {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use core::time::Duration;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{InstallConfig, SignalsChannel as _},
+                           SignalNumber};
+    use std::sync::mpsc::RecvTimeoutError;
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        // The other settings still apply.
+        let config = InstallConfig::default().acked().thread_name("acked");
+        let receiver = SignalsChannel::install_with_config::<SignalNumber>(config).unwrap();
+
+        raise(SIGUSR1);
+        assert_eq!(receiver.recv(), Ok(SIGUSR1));
+
+        // Not sent until the previous is acknowledged, but still counted meanwhile.
+        raise(SIGUSR2);
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(200)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        receiver.ack();
+        assert_eq!(receiver.recv(), Ok(SIGUSR2));
+        receiver.ack();
+        // Extra acknowledging doesn't allow more to be in-flight.
+        receiver.ack();
+
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        let first = receiver.recv().unwrap();
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(200)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        receiver.ack();
+        let second = receiver.recv().unwrap();
+        assert_ne!(first, second);
+
+        // Finishing doesn't wait for the last acknowledgment.
+        SignalsChannel::finish(receiver).unwrap();
+    }
+}
+
+
+mod broadcast {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use libc::{SIGHUP, SIGUSR1};
+    use signals_receipts::{channel_notify_facility::{InstallError, RecvError,
+                                                     SignalsChannel as _},
+                           SignalNumber};
+
+
+    signals_receipts::channel_notify_facility! { SIGHUP, SIGUSR1 }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        let broadcast = SignalsChannel::install_broadcast::<SignalNumber>(Some(1)).unwrap();
+        assert!(matches!(
+            SignalsChannel::install_broadcast::<SignalNumber>(None),
+            Err(InstallError::AlreadyInstalled { .. })
+        ));
+
+        let first = broadcast.subscribe();
+        let second = broadcast.clone().subscribe();
+        raise(SIGHUP);
+        assert_eq!(first.recv(), Ok(SIGHUP));
+        assert_eq!(second.recv(), Ok(SIGHUP));
+
+        // A disconnected subscriber is pruned without affecting the others, and subscribing can
+        // be done at any time.
+        drop(first);
+        let third = broadcast.subscribe();
+        raise(SIGUSR1);
+        assert_eq!(second.recv(), Ok(SIGUSR1));
+        assert_eq!(third.recv(), Ok(SIGUSR1));
+        raise(SIGHUP);
+        assert_eq!(second.recv(), Ok(SIGHUP));
+        assert_eq!(third.recv(), Ok(SIGHUP));
+
+        drop(broadcast);
+        SignalsChannel::uninstall_with_outside_channel().unwrap();
+        // Disconnected, since both the `Broadcast` and the thread's sender were dropped.
+        assert_eq!(second.recv(), Err(RecvError));
+        drop((second, third));
+        SignalsChannel::finish_with_outside_channel().unwrap();
+    }
+}
+
+
+mod coalesce {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use core::time::Duration;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{CoalescePolicy, InstallConfig,
+                                                     SignalsChannel as _},
+                           SignalNumber};
+    use std::thread;
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    /// Returns how many notifications were sent for 3 receipts of `SIGUSR2` that were counted
+    /// together.
+    fn notified(policy: CoalescePolicy) -> usize {
+        let config = InstallConfig::default().channel_bound(1).coalesce(policy);
+        let receiver = SignalsChannel::install_with_config::<SignalNumber>(config).unwrap();
+
+        // Fill the channel, and then block the thread while sending, so that it doesn't process
+        // the following until unblocked.
+        raise(SIGUSR1);
+        thread::sleep(Duration::from_millis(100));
+        raise(SIGUSR1);
+        thread::sleep(Duration::from_millis(100));
+        for _ in 0 .. 3 {
+            raise(SIGUSR2);
+        }
+        assert_eq!(receiver.recv(), Ok(SIGUSR1));
+        assert_eq!(receiver.recv(), Ok(SIGUSR1));
+
+        // Processed after the `SIGUSR2` receipts, and so it marks the end of their notifications.
+        raise(SIGUSR1);
+        let count = receiver.as_ref().iter().take_while(|&signum| signum != SIGUSR1).count();
+        SignalsChannel::finish(receiver).unwrap();
+        count
+    }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        assert_eq!(notified(CoalescePolicy::default()), 1);
+        assert_eq!(notified(CoalescePolicy::One), 1);
+        assert_eq!(notified(CoalescePolicy::PerCount), 3);
+        assert_eq!(notified(CoalescePolicy::UpTo(2)), 2);
+        assert_eq!(notified(CoalescePolicy::UpTo(5)), 3);
+        assert_eq!(notified(CoalescePolicy::UpTo(0)), 1);
+    }
+}
+
+
+#[cfg(feature = "crossbeam")]
+mod crossbeam {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{SendError, Sender, SignalsChannel as _},
+                           SignalNumber};
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    #[test]
+    fn send_errors() {
+        let (sender, receiver) = crossbeam_channel::bounded::<u8>(1);
+        assert!(matches!(Sender::send(&sender, SIGUSR1), Ok(())));
+        assert!(matches!(Sender::send(&sender, SIGUSR2), Err(SendError::Full)));
+        assert!(matches!(Sender::send(&sender, -1), Err(SendError::Ignored)));
+        assert_eq!(receiver.recv().unwrap(), u8::try_from(SIGUSR1).unwrap());
+        drop(receiver);
+        assert!(matches!(Sender::send(&sender, SIGUSR2), Err(SendError::Disconnected)));
+    }
+
+
+    #[test]
+    fn outside_channel() {
+        let _serial = serial();
+
+        let (sender, receiver) = crossbeam_channel::unbounded::<SignalNumber>();
+        SignalsChannel::install_with_outside_channel(sender).unwrap();
+        raise(SIGUSR1);
+        assert_eq!(receiver.recv().unwrap(), SIGUSR1);
+        raise(SIGUSR2);
+        assert_eq!(receiver.recv().unwrap(), SIGUSR2);
+        drop(receiver);
+        SignalsChannel::uninstall_with_outside_channel().unwrap();
+    }
+}
+
+
+mod finish_drained {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use core::time::Duration;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{FinishError, SignalsChannel as _},
+                           SignalNumber};
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        // A bound of 1 makes the "signals-receipt" thread block on sending the second
+        // notification, until it's received by `finish_drained`.
+        let receiver = SignalsChannel::install::<SignalNumber>(Some(1)).unwrap();
+
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+
+        let mut drained = Vec::new();
+        let r = SignalsChannel::finish_drained(receiver, Duration::from_secs(60), |sig_num| {
+            drained.push(sig_num);
+        });
+        assert!(matches!(r, Ok(())));
+        assert!(SignalsChannel::is_finished());
+        drained.sort_unstable();
+        assert_eq!(drained, [SIGUSR1, SIGUSR2]);
+
+        // Re-installing still works, and draining with nothing pending just finishes.
+        let receiver = SignalsChannel::install::<SignalNumber>(None).unwrap();
+        let r = SignalsChannel::finish_drained(receiver, Duration::ZERO, drop);
+        assert!(!matches!(r, Err(FinishError::AlreadyFinished | FinishError::WrongMethod)));
+        assert!(SignalsChannel::is_finished());
+    }
+}
+
+
+mod install_config {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{InstallConfig, InstallError,
+                                                     SignalsChannel as _},
+                           SignalNumber};
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        let larger = InstallConfig::default().channel_bound(1).stack_size(1 << 20);
+        let receiver = SignalsChannel::install_with_config::<SignalNumber>(larger).unwrap();
+        raise(SIGUSR1);
+        assert_eq!(receiver.recv(), Ok(SIGUSR1));
+        SignalsChannel::finish(receiver).unwrap();
+
+        // Too large of a stack can't be created, and then nothing is installed.
+        let too_large = InstallConfig::default().stack_size(usize::MAX);
+        let r = SignalsChannel::install_with_config::<SignalNumber>(too_large);
+        assert!(matches!(r, Err(InstallError::ThreadCreateFailed(_))));
+        assert!(SignalsChannel::is_finished());
+
+        for invalid in ["longer-than-15-b", "nul\0"] {
+            let r2 = SignalsChannel::install_with_config::<SignalNumber>(
+                InstallConfig::default().thread_name(invalid),
+            );
+            assert!(matches!(r2, Err(InstallError::InvalidThreadName { .. })));
+            assert!(SignalsChannel::is_finished());
+        }
+
+        let named = InstallConfig::default().thread_name("my-facility");
+        let receiver1 = SignalsChannel::install_with_config::<SignalNumber>(named).unwrap();
+        // Ensure the thread is running, which is when it names itself.
+        raise(SIGUSR1);
+        assert_eq!(receiver1.recv(), Ok(SIGUSR1));
+        #[cfg(target_os = "linux")]
+        assert!(thread_names().contains(&"my-facility".to_owned()));
+        SignalsChannel::finish(receiver1).unwrap();
+
+        // The default is the same as `install(None)`.
+        let default = InstallConfig::default();
+        let receiver2 = SignalsChannel::install_with_config::<SignalNumber>(default).unwrap();
+        raise(SIGUSR2);
+        assert_eq!(receiver2.recv(), Ok(SIGUSR2));
+        SignalsChannel::finish(receiver2).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    fn thread_names() -> Vec<String> {
+        std::fs::read_dir("/proc/self/task")
+            .unwrap()
+            .map(|task| {
+                let comm = std::fs::read_to_string(task.unwrap().path().join("comm")).unwrap();
+                comm.trim_end().to_owned()
+            })
+            .collect()
+    }
+}
+
+
+mod lossy {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use core::{sync::atomic::{AtomicU32, Ordering::Relaxed},
+               time::Duration};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{InstallConfig, SendError,
+                                                     SignalsChannel as _},
+                           SignalNumber};
+    use std::{sync::mpsc::TryRecvError, thread};
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    static FULL_ERRORS: AtomicU32 = AtomicU32::new(0);
+
+    fn on_send_error(_signum: SignalNumber, error: SendError) {
+        if matches!(error, SendError::Full) {
+            FULL_ERRORS.fetch_add(1, Relaxed);
+        }
+    }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        let config = InstallConfig::default().bounded_lossy(1).on_send_error(on_send_error);
+        let receiver = SignalsChannel::install_with_config::<SignalNumber>(config).unwrap();
+
+        // The channel has room for only one, and so the other is dropped instead of the thread
+        // blocking.  Which one is dropped depends on the order they're processed in.
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        while receiver.dropped() == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(matches!(receiver.recv(), Ok(SIGUSR1 | SIGUSR2)));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(receiver.dropped(), 1);
+        // The drop was also given to the config's callback.
+        assert_eq!(FULL_ERRORS.load(Relaxed), 1);
+
+        // There's room again.
+        raise(SIGUSR2);
+        assert_eq!(receiver.recv(), Ok(SIGUSR2));
+        assert_eq!(receiver.dropped(), 1);
+
+        SignalsChannel::finish(receiver).unwrap();
+    }
+}
+
+
+mod on_send_error {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use core::sync::atomic::{AtomicI32, Ordering::Relaxed};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{InstallConfig, SendError,
+                                                     SignalsChannel as _},
+                           SignalNumber};
+    use std::sync::Mutex;
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    /// Only `SIGUSR1` can be converted to this, and so notifications of `SIGUSR2` are ignored.
+    #[derive(Debug, PartialEq)]
+    struct OnlyUsr1;
+
+    impl TryFrom<SignalNumber> for OnlyUsr1 {
+        type Error = ();
+
+        fn try_from(signum: SignalNumber) -> Result<Self, Self::Error> {
+            if signum == SIGUSR1 { Ok(Self) } else { Err(()) }
+        }
+    }
+
+
+    static FAILED_SIGNUM: AtomicI32 = AtomicI32::new(0);
+    static FAILED_ERROR: Mutex<Option<SendError>> = Mutex::new(None);
+
+    fn on_send_error(signum: SignalNumber, error: SendError) {
+        FAILED_SIGNUM.store(signum, Relaxed);
+        *FAILED_ERROR.lock().unwrap() = Some(error);
+    }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        let config = InstallConfig::default().on_send_error(on_send_error);
+        let receiver = SignalsChannel::install_with_config::<OnlyUsr1>(config).unwrap();
+
+        raise(SIGUSR2);
+        raise(SIGUSR1);
+        assert_eq!(receiver.recv(), Ok(OnlyUsr1));
+        // This is processed in a later iteration of the thread than `SIGUSR2` was.
+        raise(SIGUSR1);
+        assert_eq!(receiver.recv(), Ok(OnlyUsr1));
+        assert_eq!(FAILED_SIGNUM.load(Relaxed), SIGUSR2);
+        assert_eq!(*FAILED_ERROR.lock().unwrap(), Some(SendError::Ignored));
+
+        SignalsChannel::finish(receiver).unwrap();
+    }
+}
+
+
+mod socket {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{SendError, Sender as _, SignalsChannel as _,
+                                                     SocketSender},
+                           SignalNumber};
+    use std::os::unix::net::UnixDatagram;
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    fn recv_signum(peer: &UnixDatagram) -> SignalNumber {
+        let mut datagram = [0; 4];
+        assert_eq!(peer.recv(&mut datagram).unwrap(), 4);
+        i32::from_le_bytes(datagram)
+    }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        let (socket, peer) = UnixDatagram::pair().unwrap();
+        SignalsChannel::install_with_outside_channel(SocketSender::new(socket)).unwrap();
+        raise(SIGUSR1);
+        assert_eq!(recv_signum(&peer), SIGUSR1);
+        raise(SIGUSR2);
+        assert_eq!(recv_signum(&peer), SIGUSR2);
+        drop(peer);
+        SignalsChannel::finish_with_outside_channel().unwrap();
+    }
+
+
+    #[test]
+    fn errors() {
+        let (socket, peer) = UnixDatagram::pair().unwrap();
+        let sender = SocketSender::new(socket);
+        sender.socket().set_nonblocking(true).unwrap();
+        let full = (0 .. 1_000_000).find_map(|_| sender.send(SIGUSR1).err());
+        assert_eq!(full, Some(SendError::Full));
+        assert_eq!(recv_signum(&peer), SIGUSR1);
+
+        drop(peer);
+        assert_eq!(sender.send(SIGUSR1), Err(SendError::Disconnected));
+    }
+}
+
+
+#[cfg(feature = "tokio")]
+mod tokio {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{channel_notify_facility::{SendError, Sender, SignalsChannel as _},
+                           SignalNumber};
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    #[test]
+    fn send_errors() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<u8>();
+        assert!(matches!(Sender::send(&sender, SIGUSR1), Ok(())));
+        assert!(matches!(Sender::send(&sender, -1), Err(SendError::Ignored)));
+        assert_eq!(receiver.blocking_recv(), Some(u8::try_from(SIGUSR1).unwrap()));
+        drop(receiver);
+        assert!(matches!(Sender::send(&sender, SIGUSR2), Err(SendError::Disconnected)));
+    }
+
+
+    #[test]
+    fn install_tokio() {
+        let _serial = serial();
+
+        let mut receiver = SignalsChannel::install_tokio::<SignalNumber>(1).unwrap();
+        raise(SIGUSR1);
+        // (An async task would `.recv().await` instead.)
+        assert_eq!(receiver.blocking_recv(), Some(SIGUSR1));
+        raise(SIGUSR2);
+        assert_eq!(receiver.blocking_recv(), Some(SIGUSR2));
+        drop(receiver);
+        SignalsChannel::finish_with_outside_channel().unwrap();
+    }
+}
+
+
+mod with_fn {
+    use crate::util::{raise, serial};
+    use channel_notify_facility_premade::SignalsChannel;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::channel_notify_facility::{InstallError, SendError, SignalsChannel as _};
+    use std::sync::mpsc;
+
+
+    signals_receipts::channel_notify_facility! { SIGUSR1, SIGUSR2 }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut calls = 0_u32;
+        SignalsChannel::install_with_fn(move |signum| {
+            calls = calls.saturating_add(1);
+            sender.send((signum, calls)).or(Err(SendError::Disconnected))
+        })
+        .unwrap();
+        assert!(matches!(
+            SignalsChannel::install_with_fn(|_| Ok(())),
+            Err(InstallError::AlreadyInstalled { .. })
+        ));
+
+        raise(SIGUSR1);
+        assert_eq!(receiver.recv(), Ok((SIGUSR1, 1)));
+        raise(SIGUSR2);
+        assert_eq!(receiver.recv(), Ok((SIGUSR2, 2)));
+
+        SignalsChannel::finish_with_outside_channel().unwrap();
+        // The closure, and so its sender, was dropped.
+        assert_eq!(receiver.recv(), Err(mpsc::RecvError));
+    }
+}
@@ -0,0 +1,899 @@
+#![cfg(test)] // Suppress `clippy::tests_outside_test_module`.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::as_conversions,
+    clippy::fn_to_numeric_cast_any,
+    clippy::indexing_slicing,
+    clippy::missing_inline_in_public_items,
+    clippy::panic,
+    clippy::panic_in_result_fn,
+    clippy::std_instead_of_core,
+    clippy::unwrap_used,
+    unsafe_code,
+    unused_crate_dependencies // Ignore the lib crate's deps that are supplied here also.
+)]
+
+#[path = "help/util.rs"]
+mod util;
+
+
+mod fallible {
+    use core::{ops::ControlFlow, sync::atomic::AtomicBool};
+    use signals_receipts::{consume_loop_fallible, ConsumeLoopError, Consumer, WaitSource};
+
+
+    /// Like a semaphore that can't be initialized, e.g. due to tight limits.
+    struct Uninitializable;
+
+    impl WaitSource for Uninitializable {
+        fn try_init(&self, _limit: u64) -> bool { false }
+
+        fn wait(&self) -> Result<(), ()> { panic!("must not wait without being initialized") }
+
+        fn post(&self) -> Result<(), ()> { Err(()) }
+    }
+
+
+    #[test]
+    fn fallible() {
+        let continue_flag = AtomicBool::new(true);
+        let mut consume = |()| -> ControlFlow<(), ()> { panic!("must not be called") };
+        let mut consumers: [&mut Consumer; 1] = [&mut consume];
+        let r = consume_loop_fallible(
+            true,
+            Uninitializable,
+            3,
+            (),
+            &mut consumers,
+            &continue_flag,
+            (),
+        );
+        assert_eq!(r, Err(ConsumeLoopError::SemaphoreInit));
+        assert_eq!(
+            ConsumeLoopError::SemaphoreInit.to_string(),
+            "semaphore initialization failed"
+        );
+    }
+}
+
+
+mod independent {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow,
+               pin::Pin,
+               sync::atomic::{AtomicBool, AtomicU32},
+               time::Duration};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{consume_loop, install_handler, Consumer, Semaphore,
+                           SemaphoreMethods as _, SemaphoreRef, SignalNumber, SignalReceipt};
+    use std::thread;
+
+
+    /// Each signal has its own counter and its own semaphore.
+    struct Independent;
+
+    macro_rules! independent {
+        ($($signum:ident)+) => { $(
+            impl SignalReceipt<$signum> for Independent {
+                type AtomicUInt = AtomicU32;
+
+                fn counter() -> &'static Self::AtomicUInt {
+                    static COUNTER: AtomicU32 = AtomicU32::new(0);
+                    &COUNTER
+                }
+
+                fn semaphore() -> Option<SemaphoreRef<'static>> {
+                    semaphore::<$signum>().sem_ref().ok()
+                }
+            }
+        )+ };
+    }
+
+    independent! { SIGUSR1 SIGUSR2 }
+
+    fn semaphore<const SIGNUM: SignalNumber>() -> Pin<&'static Semaphore> {
+        static SEMAPHORES: [Semaphore; 2] = [Semaphore::uninit(), Semaphore::uninit()];
+        Pin::static_ref(&SEMAPHORES[usize::from(SIGNUM == SIGUSR2)])
+    }
+
+    /// Consume on a dedicated thread, which finishes upon the first receipt of its signal.
+    fn spawn_consumer<const SIGNUM: SignalNumber>() -> thread::JoinHandle<SignalNumber>
+    where
+        Independent: SignalReceipt<SIGNUM, AtomicUInt = AtomicU32>,
+    {
+        semaphore::<SIGNUM>().init().unwrap();
+        install_handler::<SIGNUM, Independent>(true, true);
+
+        thread::spawn(|| {
+            static CONTINUE_FLAG: AtomicBool = AtomicBool::new(true);
+            let mut consume = |()| {
+                if Independent::take_count() >= 1 {
+                    ControlFlow::Break(SIGNUM)
+                } else {
+                    ControlFlow::Continue(())
+                }
+            };
+            let mut consumers: [&mut Consumer<SignalNumber>; 1] = [&mut consume];
+            consume_loop(true, semaphore::<SIGNUM>(), 0, (), &mut consumers, &CONTINUE_FLAG, 0)
+        })
+    }
+
+
+    #[test]
+    fn independent() {
+        let _serial = serial();
+
+        let t1 = spawn_consumer::<SIGUSR1>();
+        let t2 = spawn_consumer::<SIGUSR2>();
+
+        raise(SIGUSR1);
+        assert_eq!(t1.join().unwrap(), SIGUSR1);
+
+        // The other thread wasn't woken, because its semaphore wasn't posted.
+        thread::sleep(Duration::from_millis(100));
+        assert!(!t2.is_finished());
+
+        raise(SIGUSR2);
+        assert_eq!(t2.join().unwrap(), SIGUSR2);
+    }
+}
+
+
+mod masking {
+    use crate::util::{raise, serial};
+    use core::{mem::MaybeUninit,
+               ptr,
+               sync::atomic::{AtomicBool, AtomicU64, Ordering::Relaxed}};
+    use libc::{SIGHUP, SIGSEGV, SIGTERM, SIGWINCH};
+    use signals_receipts::{install_handler_masking, uninstall_handler, SemaphoreRef,
+                           SignalReceipt};
+
+
+    static TERM_MASKED: AtomicBool = AtomicBool::new(false);
+    static WINCH_MASKED: AtomicBool = AtomicBool::new(true);
+    static SEGV_MASKED: AtomicBool = AtomicBool::new(true);
+
+    struct Masking;
+
+    impl SignalReceipt<SIGHUP> for Masking {
+        type AtomicUInt = AtomicU64;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+
+        fn on_delivery() {
+            let mask = current_thread_mask();
+            let is_masked = |signum| {
+                // SAFETY: The arguments are proper.
+                #[allow(unsafe_code)]
+                let r = unsafe { libc::sigismember(&mask, signum) };
+                r == 1
+            };
+            TERM_MASKED.store(is_masked(SIGTERM), Relaxed);
+            WINCH_MASKED.store(is_masked(SIGWINCH), Relaxed);
+            SEGV_MASKED.store(is_masked(SIGSEGV), Relaxed);
+        }
+    }
+
+    fn current_thread_mask() -> libc::sigset_t {
+        #![allow(unsafe_code)]
+
+        let mut cur = MaybeUninit::<libc::sigset_t>::zeroed();
+        // SAFETY: The arguments are proper.
+        let r = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, ptr::null(), cur.as_mut_ptr()) };
+        assert_eq!(r, 0, "will succeed");
+        // SAFETY: It was zeroed, and `pthread_sigmask()` initialized it.
+        unsafe { cur.assume_init() }
+    }
+
+
+    #[test]
+    fn masking() {
+        let _serial = serial();
+
+        // `SIGSEGV` is exceptional, and so it isn't masked even though it's given.
+        install_handler_masking::<SIGHUP, Masking>(&[SIGTERM, SIGSEGV], true);
+        raise(SIGHUP);
+        assert_eq!(Masking::take_count(), 1);
+        assert!(TERM_MASKED.load(Relaxed));
+        assert!(!WINCH_MASKED.load(Relaxed));
+        assert!(!SEGV_MASKED.load(Relaxed));
+        uninstall_handler::<SIGHUP>();
+    }
+}
+
+
+mod no_defer {
+    use crate::util::{raise, serial};
+    use core::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering::Relaxed};
+    use libc::SIGURG;
+    use signals_receipts::{install_handler, install_handler_with_flags, reset_counter,
+                           uninstall_handler, SemaphoreRef, SigActionFlags, SignalReceipt};
+
+
+    static DEPTH: AtomicU8 = AtomicU8::new(0);
+    static MAX_DEPTH: AtomicU8 = AtomicU8::new(0);
+    static REENTER: AtomicBool = AtomicBool::new(false);
+
+    struct Reentrant;
+
+    impl SignalReceipt<SIGURG> for Reentrant {
+        type AtomicUInt = AtomicU64;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+
+        /// Raise the signal again, once, from within the handler, and record how deeply nested
+        /// the handler executions were.
+        fn on_delivery() {
+            let depth = DEPTH.fetch_add(1, Relaxed) + 1;
+            MAX_DEPTH.fetch_max(depth, Relaxed);
+            if REENTER.swap(false, Relaxed) {
+                raise(SIGURG);
+            }
+            DEPTH.fetch_sub(1, Relaxed);
+        }
+    }
+
+    fn raise_reentering() -> u8 {
+        MAX_DEPTH.store(0, Relaxed);
+        REENTER.store(true, Relaxed);
+        raise(SIGURG);
+        MAX_DEPTH.load(Relaxed)
+    }
+
+
+    #[test]
+    fn no_defer() {
+        let _serial = serial();
+
+        // Deferred by default, and so the inner delivery only happens after the handler returns.
+        install_handler::<SIGURG, Reentrant>(true, false);
+        assert_eq!(raise_reentering(), 1);
+        assert_eq!(Reentrant::take_count(), 2);
+
+        // Also when masking all, the signal itself isn't masked.
+        install_handler_with_flags::<SIGURG, Reentrant>(
+            SigActionFlags::default().mask_all().no_defer(),
+        );
+        assert_eq!(raise_reentering(), 2);
+        assert_eq!(Reentrant::take_count(), 2);
+
+        install_handler_with_flags::<SIGURG, Reentrant>(SigActionFlags::default().no_defer());
+        assert_eq!(raise_reentering(), 2);
+        assert_eq!(Reentrant::take_count(), 2);
+
+        reset_counter::<SIGURG, Reentrant>();
+        uninstall_handler::<SIGURG>();
+    }
+}
+
+
+mod old_disposition {
+    use crate::util::{raise, serial};
+    use core::{mem::MaybeUninit, ptr};
+    use libc::SIGUSR2;
+    use signals_receipts::{install_handler_returning_old, is_handler_installed,
+                           uninstall_handler_restoring_old, SemaphoreRef, SignalReceipt};
+    use std::sync::atomic::AtomicU32;
+
+
+    struct Temporary;
+
+    impl SignalReceipt<SIGUSR2> for Temporary {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+
+    fn sigaction(act: Option<&libc::sigaction>) -> libc::sigaction {
+        let mut old = MaybeUninit::<libc::sigaction>::zeroed();
+        let act = act.map_or(ptr::null(), ptr::from_ref);
+        // SAFETY: The arguments are proper.
+        let r = unsafe { libc::sigaction(SIGUSR2, act, old.as_mut_ptr()) };
+        assert_eq!(r, 0);
+        // SAFETY: Initialized by the successful call.
+        unsafe { old.assume_init() }
+    }
+
+
+    #[test]
+    fn old_disposition() {
+        let _serial = serial();
+
+        // The application's disposition: ignored, with a flag, and with a signal in the mask.
+        let mut app = sigaction(None);
+        app.sa_sigaction = libc::SIG_IGN;
+        app.sa_flags = libc::SA_RESTART;
+        // SAFETY: The argument is proper.
+        unsafe {
+            libc::sigemptyset(&mut app.sa_mask);
+        }
+        // SAFETY: The arguments are proper.
+        unsafe {
+            libc::sigaddset(&mut app.sa_mask, libc::SIGUSR1);
+        }
+        let _prev = sigaction(Some(&app));
+
+        let old = install_handler_returning_old::<SIGUSR2, Temporary>(true, false);
+        assert_eq!(old.signum(), SIGUSR2);
+        assert!(is_handler_installed::<SIGUSR2, Temporary>());
+        raise(SIGUSR2);
+        assert_eq!(Temporary::take_count(), 1);
+
+        uninstall_handler_restoring_old(old);
+        assert!(!is_handler_installed::<SIGUSR2, Temporary>());
+        // Ignored again, and so this doesn't terminate the process, nor is it counted.
+        raise(SIGUSR2);
+        assert_eq!(Temporary::take_count(), 0);
+
+        let restored = sigaction(None);
+        assert_eq!(restored.sa_sigaction, libc::SIG_IGN);
+        assert_ne!(restored.sa_flags & libc::SA_RESTART, 0);
+        // SAFETY: The argument is proper.
+        assert_eq!(unsafe { libc::sigismember(&restored.sa_mask, libc::SIGUSR1) }, 1);
+    }
+}
+
+
+mod overflow {
+    use crate::util::{raise, serial};
+    use core::sync::atomic::{AtomicBool, AtomicU8};
+    use libc::SIGURG;
+    use signals_receipts::{install_handler, peek_counter, reset_counter, uninstall_handler,
+                           SemaphoreRef, SignalReceipt};
+
+
+    struct Narrow;
+
+    impl SignalReceipt<SIGURG> for Narrow {
+        type AtomicUInt = AtomicU8;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU8 = AtomicU8::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+
+        fn overflow_flag() -> Option<&'static AtomicBool> {
+            static OVERFLOWED: AtomicBool = AtomicBool::new(false);
+            Some(&OVERFLOWED)
+        }
+    }
+
+
+    #[test]
+    fn overflow() {
+        let _serial = serial();
+
+        install_handler::<SIGURG, Narrow>(true, false);
+
+        for _ in 0 .. u8::MAX {
+            raise(SIGURG);
+        }
+        assert_eq!(peek_counter::<SIGURG, Narrow>(), u8::MAX);
+        assert!(!Narrow::overflowed());
+
+        raise(SIGURG);
+        assert_eq!(peek_counter::<SIGURG, Narrow>(), u8::MAX);
+        assert!(Narrow::overflowed());
+        assert!(Narrow::take_overflowed());
+        assert!(!Narrow::overflowed());
+
+        raise(SIGURG);
+        assert!(Narrow::overflowed());
+        reset_counter::<SIGURG, Narrow>();
+        assert!(!Narrow::overflowed());
+        assert_eq!(peek_counter::<SIGURG, Narrow>(), 0);
+
+        uninstall_handler::<SIGURG>();
+    }
+}
+
+
+#[cfg(feature = "registry")]
+mod registry {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow,
+               pin::Pin,
+               sync::atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+               time::Duration};
+    use libc::SIGUSR1;
+    use signals_receipts::{consume_loop_dynamic, install_handler, peek_counter, uninstall_handler,
+                           ConsumerRegistry, Semaphore, SemaphoreMethods as _, SemaphoreRef,
+                           SignalReceipt};
+    use std::thread;
+
+
+    static SEMAPHORE: Semaphore = Semaphore::uninit();
+    static REGISTRY: ConsumerRegistry = ConsumerRegistry::new();
+    static TAKEN: AtomicU64 = AtomicU64::new(0);
+
+    struct Handled;
+
+    impl SignalReceipt<SIGUSR1> for Handled {
+        type AtomicUInt = AtomicU64;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> {
+            Pin::static_ref(&SEMAPHORE).sem_ref().ok()
+        }
+    }
+
+
+    #[test]
+    fn registry() {
+        static CONTINUE_FLAG: AtomicBool = AtomicBool::new(true);
+
+        let _serial = serial();
+
+        Pin::static_ref(&SEMAPHORE).init().unwrap();
+        install_handler::<SIGUSR1, Handled>(true, true);
+        let consumer = thread::spawn(|| {
+            let sem = Pin::static_ref(&SEMAPHORE);
+            consume_loop_dynamic(true, sem, 0, (), &REGISTRY, &CONTINUE_FLAG, ());
+        });
+
+        // Nothing is registered yet, and so nothing is taken.
+        raise(SIGUSR1);
+        assert!(REGISTRY.is_empty());
+
+        let taker = REGISTRY.register(|()| {
+            TAKEN.fetch_add(Handled::take_count(), Relaxed);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(REGISTRY.len(), 1);
+        raise(SIGUSR1);
+        // Picked up upon the wake-up, and so it takes what had accumulated before.
+        while TAKEN.load(Relaxed) < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(TAKEN.load(Relaxed), 2);
+
+        assert!(REGISTRY.unregister(taker));
+        assert!(!REGISTRY.unregister(taker));
+        let _breaker = REGISTRY.register(|()| ControlFlow::Break(()));
+        raise(SIGUSR1);
+        consumer.join().unwrap();
+        // The unregistered one wasn't called anymore.
+        assert_eq!(TAKEN.load(Relaxed), 2);
+        assert_eq!(peek_counter::<SIGUSR1, Handled>(), 1);
+
+        uninstall_handler::<SIGUSR1>();
+    }
+}
+
+
+mod shared_handler {
+    use crate::util::{raise, serial};
+    use core::sync::atomic::AtomicU32;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{handler, install_handler, uninstall_handler, SemaphoreRef,
+                           SignalNumber, SignalReceipt};
+
+
+    struct Shared;
+
+    impl SignalReceipt<SIGUSR1> for Shared {
+        type AtomicUInt = AtomicU32;
+
+        // Otherwise, the delivery of `SIGUSR2` would abort, with `debug_assertions`.
+        const VERIFY_SIGNO: bool = false;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+
+    #[test]
+    fn shared_handler() {
+        let _serial = serial();
+
+        install_handler::<SIGUSR1, Shared>(true, true);
+        // The same monomorphization, for another signal number.
+        let shared: extern "C" fn(SignalNumber) = handler::<SIGUSR1, Shared>;
+        #[allow(unsafe_code)]
+        // SAFETY: The arguments are proper, and `handler` is async-signal-safe.
+        let prev = unsafe { libc::signal(SIGUSR2, shared as libc::sighandler_t) };
+        assert_ne!(prev, libc::SIG_ERR);
+
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        assert_eq!(Shared::take_count(), 2);
+
+        uninstall_handler::<SIGUSR1>();
+        uninstall_handler::<SIGUSR2>();
+    }
+}
+
+
+mod sig_info {
+    use crate::util::{send_signal_to_proc, serial};
+    use libc::{SIGUSR1, SI_USER};
+    use signals_receipts::{install_handler_with_info, uninstall_handler, SemaphoreRef,
+                           SigInfoSlot, SignalReceipt, SignalReceiptWithInfo};
+    use std::sync::atomic::AtomicU32;
+
+
+    struct WithInfo;
+
+    impl SignalReceipt<SIGUSR1> for WithInfo {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+    impl SignalReceiptWithInfo<SIGUSR1> for WithInfo {
+        fn info_slot() -> &'static SigInfoSlot {
+            static SLOT: SigInfoSlot = SigInfoSlot::new();
+            &SLOT
+        }
+    }
+
+
+    #[test]
+    fn sig_info() {
+        let _serial = serial();
+
+        assert_eq!(WithInfo::info_slot().load(), None);
+
+        install_handler_with_info::<SIGUSR1, WithInfo>(true, false);
+        let pid = libc::pid_t::try_from(std::process::id()).unwrap();
+        // Not `raise()`, which isn't `SI_USER` on some OSs.
+        assert!(send_signal_to_proc(SIGUSR1, pid));
+        while WithInfo::take_count() == 0 {
+            std::thread::yield_now();
+        }
+        uninstall_handler::<SIGUSR1>();
+
+        let info = WithInfo::info_slot().load().unwrap();
+        assert_eq!(info.signo, SIGUSR1);
+        assert_eq!(info.code, SI_USER);
+        assert_eq!(info.pid, pid);
+        #[allow(unsafe_code)]
+        // SAFETY: Always succeeds.
+        let uid = unsafe { libc::getuid() };
+        assert_eq!(info.uid, uid);
+    }
+}
+
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sig_value {
+    use crate::util::serial;
+    use core::{ffi::c_int, ops::ControlFlow, ptr};
+    use signals_receipts::{consume_values_then_delegate, install_handler_with_value,
+                           resolve_signum, rt, uninstall_handler, SemaphoreRef, SigValueQueue,
+                           SignalNumber, SignalReceipt, SignalReceiptWithValue};
+    use std::sync::atomic::AtomicU32;
+
+
+    const SIGRT_MSG: SignalNumber = rt(1);
+
+    struct WithValue;
+
+    impl SignalReceipt<SIGRT_MSG> for WithValue {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+    impl SignalReceiptWithValue<SIGRT_MSG> for WithValue {
+        fn value_queue() -> &'static SigValueQueue {
+            static QUEUE: SigValueQueue = SigValueQueue::new();
+            &QUEUE
+        }
+    }
+
+    fn send_value(value: c_int) {
+        #![allow(unsafe_code)]
+        let mut sigval = libc::sigval { sival_ptr: ptr::null_mut() };
+        // SAFETY: `sival_int` is at the start of the C `union`.
+        unsafe { ptr::addr_of_mut!(sigval).cast::<c_int>().write(value); }
+        // SAFETY: Always safe.
+        let pid = unsafe { libc::getpid() };
+        // SAFETY: The arguments are proper.
+        let r = unsafe { libc::sigqueue(pid, resolve_signum(SIGRT_MSG), sigval) };
+        assert_eq!(r, 0);
+    }
+
+
+    #[test]
+    fn sig_value() {
+        let _serial = serial();
+
+        install_handler_with_value::<SIGRT_MSG, WithValue>(true, false);
+        for value in [7, -1, 7] {
+            send_value(value);
+        }
+        while WithValue::peek_count() < 3 {
+            std::thread::yield_now();
+        }
+        uninstall_handler::<SIGRT_MSG>();
+
+        // Each value, in order, without coalescing.
+        let flow = consume_values_then_delegate::<SIGRT_MSG, WithValue, _, (), _>(
+            Vec::new(),
+            |receipt| {
+                assert_eq!(receipt.sig_num, resolve_signum(SIGRT_MSG));
+                let value = receipt.value;
+                receipt.get_state_mut().push(value);
+            },
+        );
+        assert_eq!(flow, ControlFlow::Continue(vec![7, -1, 7]));
+        assert_eq!(WithValue::peek_count(), 0);
+        assert_eq!(WithValue::value_queue().take_dropped(), 0);
+    }
+
+    #[test]
+    fn queue() {
+        let queue = SigValueQueue::new();
+        assert_eq!(queue.pop(), None);
+
+        // Multiple laps.
+        for lap in 0 .. 3 {
+            for i in 0 .. SigValueQueue::CAPACITY {
+                assert!(queue.push(c_int::try_from(i).unwrap() + lap));
+            }
+            // Full.
+            assert!(!queue.push(-1));
+            assert_eq!(queue.take_dropped(), 1);
+            assert_eq!(queue.take_dropped(), 0);
+            for i in 0 .. SigValueQueue::CAPACITY {
+                assert_eq!(queue.pop(), Some(c_int::try_from(i).unwrap() + lap));
+            }
+            assert_eq!(queue.pop(), None);
+        }
+    }
+}
+
+
+mod threshold {
+    use core::sync::atomic::{AtomicU32, AtomicU64, Ordering::Relaxed};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{AtomicUInt as _, SemaphoreRef, SignalReceipt};
+    use std::thread;
+
+
+    struct Debounced;
+
+    impl SignalReceipt<SIGUSR1> for Debounced {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+    impl SignalReceipt<SIGUSR2> for Debounced {
+        type AtomicUInt = AtomicU64;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+
+    #[test]
+    fn threshold() {
+        type S = Debounced;
+        let counter = <S as SignalReceipt<SIGUSR1>>::counter();
+
+        counter.store(2, Relaxed);
+        assert_eq!(<S as SignalReceipt<SIGUSR1>>::take_count_if_at_least(3), None);
+        assert_eq!(counter.load(Relaxed), 2);
+
+        counter.store(3, Relaxed);
+        assert_eq!(<S as SignalReceipt<SIGUSR1>>::take_count_if_at_least(3), Some(3));
+        assert_eq!(counter.load(Relaxed), 0);
+
+        // Zero is always taken, even when nothing was counted.
+        assert_eq!(<S as SignalReceipt<SIGUSR1>>::take_count_if_at_least(0), Some(0));
+    }
+
+    #[test]
+    fn concurrent() {
+        const THREADS: u64 = 4;
+        const INCRS: u64 = 10_000;
+        let counter = <Debounced as SignalReceipt<SIGUSR2>>::counter();
+
+        let incrementers: Vec<_> = core::iter::repeat_with(|| {
+            thread::spawn(|| {
+                for _ in 0 .. INCRS {
+                    let _count = counter.saturating_incr();
+                }
+            })
+        })
+        .take(THREADS.try_into().unwrap_or_default())
+        .collect();
+        let mut taken = 0;
+        while incrementers.iter().any(|t| !t.is_finished()) {
+            if let Some(count) =
+                <Debounced as SignalReceipt<SIGUSR2>>::take_count_if_at_least(100)
+            {
+                assert!(count >= 100);
+                taken += count;
+            }
+        }
+        for t in incrementers {
+            t.join().unwrap_or_default();
+        }
+        // None were lost.
+        assert_eq!(taken + counter.load(Relaxed), THREADS * INCRS);
+    }
+}
+
+
+mod try_install {
+    use crate::util::serial;
+    use errno::Errno;
+    use libc::{SIGKILL, SIGUSR1};
+    use signals_receipts::{is_handler_installed, try_install_handler, try_uninstall_handler,
+                           SemaphoreRef, SignalReceipt};
+    use std::sync::atomic::AtomicU32;
+
+
+    struct Counted;
+
+    impl<const SIGNUM: i32> SignalReceipt<SIGNUM> for Counted {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+
+    #[test]
+    fn try_install() {
+        let _serial = serial();
+
+        assert_eq!(try_install_handler::<SIGUSR1, Counted>(true, false), Ok(()));
+        assert!(is_handler_installed::<SIGUSR1, Counted>());
+        assert_eq!(try_uninstall_handler::<SIGUSR1>(), Ok(()));
+        assert!(!is_handler_installed::<SIGUSR1, Counted>());
+
+        // `SIGKILL` can't be caught, and so the OS rejects it.
+        let err = try_install_handler::<SIGKILL, Counted>(true, false).unwrap_err();
+        assert_eq!((err.signum, err.errno), (SIGKILL, Errno(libc::EINVAL)));
+        assert!(err.to_string().starts_with("installing for signal number 9 failed: "));
+    }
+}
+
+
+mod wait_source {
+    use core::{cell::Cell,
+               ops::ControlFlow,
+               sync::atomic::{AtomicBool, Ordering::Relaxed}};
+    use signals_receipts::{consume_loop, Consumer, WaitSource};
+    use std::sync::Mutex;
+
+
+    /// A mock that the test drives manually, instead of a real semaphore.
+    #[derive(Default)]
+    struct Mock {
+        value: Cell<u32>,
+        waits: Cell<u32>,
+    }
+
+    impl WaitSource for &Mock {
+        fn try_init(&self, _limit: u64) -> bool { true }
+
+        fn wait(&self) -> Result<(), ()> {
+            self.waits.set(self.waits.get() + 1);
+            match self.value.get() {
+                0 => panic!("would block forever"),
+                v => self.value.set(v - 1),
+            }
+            Ok(())
+        }
+
+        fn post(&self) -> Result<(), ()> {
+            self.value.set(self.value.get() + 1);
+            Ok(())
+        }
+    }
+
+
+    #[test]
+    fn dispatch() {
+        static CALLS: Mutex<Vec<(&str, u32)>> = Mutex::new(Vec::new());
+
+        let mock = Mock::default();
+        for _ in 0 .. 3 {
+            (&mock).post().unwrap();
+        }
+        let continue_flag = AtomicBool::new(true);
+
+        let mut first = |n| {
+            CALLS.lock().unwrap().push(("first", n));
+            ControlFlow::Continue(n + 1)
+        };
+        let mut second = |n| {
+            CALLS.lock().unwrap().push(("second", n));
+            if n >= 5 { ControlFlow::Break(n) } else { ControlFlow::Continue(n + 1) }
+        };
+        let mut consumers: [&mut Consumer<u32, u32>; 2] = [&mut first, &mut second];
+
+        let r = consume_loop(false, &mock, 0, 0, &mut consumers, &continue_flag, 99);
+        assert_eq!(r, 5);
+        assert_eq!(*CALLS.lock().unwrap(), [
+            ("first", 0),
+            ("second", 1),
+            ("first", 2),
+            ("second", 3),
+            ("first", 4),
+            ("second", 5)
+        ]);
+        // Waited after each full iteration that continued.
+        assert_eq!(mock.waits.get(), 2);
+        assert_eq!(mock.value.get(), 1);
+    }
+
+
+    #[test]
+    fn discontinued() {
+        static CONTINUE_FLAG: AtomicBool = AtomicBool::new(false);
+
+        let mock = Mock::default();
+        let mut never = |()| -> ControlFlow<&str> { panic!("must not be called") };
+        let mut consumers1: [&mut Consumer<&str>; 1] = [&mut never];
+
+        let r1 = consume_loop(false, &mock, 0, (), &mut consumers1, &CONTINUE_FLAG, "finished");
+        assert_eq!(r1, "finished");
+
+        // Toggled by a consumer, which stops the loop before any more consumers are called.
+        CONTINUE_FLAG.store(true, Relaxed);
+        let mut stop = |()| {
+            CONTINUE_FLAG.store(false, Relaxed);
+            ControlFlow::Continue(())
+        };
+        let mut consumers2: [&mut Consumer<&str>; 2] = [&mut stop, &mut never];
+
+        let r2 = consume_loop(false, &mock, 0, (), &mut consumers2, &CONTINUE_FLAG, "finished");
+        assert_eq!(r2, "finished");
+        assert_eq!(mock.waits.get(), 0);
+    }
+}
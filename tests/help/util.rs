@@ -1,7 +1,8 @@
 #![allow(dead_code)]
 
 use signals_receipts::SignalNumber;
-use std::thread;
+use std::{sync::{Mutex, MutexGuard, PoisonError},
+          thread};
 
 
 pub(crate) fn raise(signum: SignalNumber) {
@@ -27,3 +28,12 @@ pub(crate) fn send_signal_to_proc(signum: SignalNumber, pid: libc::pid_t) -> boo
 
 
 pub(crate) fn spawn_raise(signum: SignalNumber) { thread::spawn(move || raise(signum)); }
+
+
+/// The tests of the same binary run concurrently, but the dispositions of the signals are for the
+/// whole process, and so each test that delivers signals holds this, to take turns.
+pub(crate) fn serial() -> MutexGuard<'static, ()> {
+    static SERIAL: Mutex<()> = Mutex::new(());
+    // A failed test doesn't prevent the others from running.
+    SERIAL.lock().unwrap_or_else(PoisonError::into_inner)
+}
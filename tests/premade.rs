@@ -0,0 +1,1697 @@
+#![cfg(test)] // Suppress `clippy::tests_outside_test_module`.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::assertions_on_result_states,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::print_stdout,
+    clippy::shadow_unrelated,
+    clippy::std_instead_of_core,
+    clippy::unreachable,
+    clippy::unwrap_used,
+    unused_crate_dependencies // Ignore the lib crate's deps that are supplied here also.
+)]
+
+#[path = "help/util.rs"]
+mod util;
+
+
+mod always {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::SIGUSR1;
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        // (Zero-count calls, total count.)
+        type Continue = (u64, u64);
+        type Break = ();
+
+        SIGUSR1 {always} => |receipt| {
+            let cur_count = receipt.cur_count;
+            let state = receipt.get_state_mut();
+            if cur_count == 0 {
+                state.0 += 1;
+            }
+            state.1 += cur_count;
+        };
+        // Never delivered, and so never called.
+        SIGUSR2 => |receipt| { receipt.break_loop(); };
+    }
+
+
+    #[test]
+    fn always() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        // Called even though nothing was delivered.
+        assert_eq!(SignalsReceipts::consume_once((0, 0)), ControlFlow::Continue((1, 0)));
+        assert_eq!(SignalsReceipts::consume_once((1, 0)), ControlFlow::Continue((2, 0)));
+
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        assert_eq!(SignalsReceipts::consume_once((2, 0)), ControlFlow::Continue((2, 2)));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod cleanup_then_default {
+    // This doesn't use the `premade!` macro, because the delegate captures.
+
+    use crate::util::{raise, serial};
+    use core::{pin::Pin,
+               sync::atomic::{AtomicBool, AtomicU32}};
+    use libc::SIGTERM;
+    use signals_receipts::{cleanup_then_default, consume_count_then_delegate, consume_loop,
+                           install_handler, Consumer, Semaphore, SemaphoreMethods as _,
+                           SemaphoreRef, SignalReceipt};
+    use std::{env, os::unix::process::ExitStatusExt as _, process::Command, thread};
+
+
+    /// When this is set, the test is running as the child process.
+    const CHILD_ENV_VAR: &str = "SIGNALS_RECEIPTS_TEST_CHILD";
+    const CLEANED_UP: &str = "cleaned up";
+
+    struct Direct;
+
+    fn semaphore() -> Pin<&'static Semaphore> {
+        static SEMAPHORE: Semaphore = Semaphore::uninit();
+        Pin::static_ref(&SEMAPHORE)
+    }
+
+    impl SignalReceipt<SIGTERM> for Direct {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { semaphore().sem_ref().ok() }
+    }
+
+
+    fn child() {
+        semaphore().init().unwrap();
+        install_handler::<SIGTERM, Direct>(true, true);
+
+        let consumer = thread::spawn(|| {
+            static CONTINUE_FLAG: AtomicBool = AtomicBool::new(true);
+            let mut delegate = cleanup_then_default(|| println!("{CLEANED_UP}"));
+            let mut consume = move |()| {
+                consume_count_then_delegate::<SIGTERM, Direct, _, _, _>((), &mut delegate)
+            };
+            let mut consumers: [&mut Consumer; 1] = [&mut consume];
+            consume_loop(true, semaphore(), 0, (), &mut consumers, &CONTINUE_FLAG, ());
+        });
+
+        raise(SIGTERM);
+        consumer.join().unwrap(); // Never returns, because the process is terminated.
+        unreachable!("must have been terminated");
+    }
+
+
+    #[test]
+    fn cleanup_then_default_terminates() {
+        let _serial = serial();
+
+        if env::var_os(CHILD_ENV_VAR).is_some() {
+            return child();
+        }
+
+        let output = Command::new(env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "cleanup_then_default::cleanup_then_default_terminates",
+                "--nocapture",
+            ])
+            .env(CHILD_ENV_VAR, "1")
+            .output()
+            .unwrap();
+
+        assert_eq!(output.status.signal(), Some(SIGTERM));
+        assert!(String::from_utf8_lossy(&output.stdout).contains(CLEANED_UP));
+    }
+}
+
+
+mod drain_all {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::{SIGURG, SIGUSR1, SIGUSR2};
+    use signals_receipts::{peek_counter, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = ();
+
+        SIGUSR1 => |receipt| { *receipt.get_state_mut() += receipt.cur_count; };
+        SIGUSR2: AtomicU8 => |receipt| {
+            *receipt.get_state_mut() += u64::from(receipt.cur_count);
+        };
+        SIGURG => |receipt| { *receipt.get_state_mut() += receipt.cur_count; };
+    }
+
+
+    #[test]
+    fn drain_all() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        assert_eq!(SignalsReceipts::drain_all().count(), 0);
+        assert_eq!(SignalsReceipts::pending().count(), 0);
+
+        for _ in 0 .. 3 {
+            raise(SIGURG);
+        }
+        raise(SIGUSR2);
+
+        // Only loaded, not taken.
+        assert_eq!(SignalsReceipts::pending().collect::<Vec<_>>(), [(SIGUSR2, 1), (SIGURG, 3)]);
+        assert_eq!(peek_counter::<SIGURG, SignalsReceipts>(), 3);
+
+        let drained = SignalsReceipts::drain_all();
+        // Already taken, before iterating.
+        assert_eq!(peek_counter::<SIGURG, SignalsReceipts>(), 0);
+        assert_eq!(peek_counter::<SIGUSR2, SignalsReceipts>(), 0);
+        // In declared order, and without the zero count of `SIGUSR1`.
+        assert_eq!(drained.collect::<Vec<_>>(), [(SIGUSR2, 1), (SIGURG, 3)]);
+
+        // The delegates have nothing left to process.
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(0));
+        raise(SIGUSR1);
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(1));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod drain_and_finish {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::{SIGURG, SIGUSR1};
+    use signals_receipts::{peek_counter, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = &'static str;
+
+        SIGUSR1 => |receipt| { *receipt.get_state_mut() += receipt.cur_count; };
+        SIGURG => |receipt| { *receipt.get_state_mut() += 10 * receipt.cur_count; };
+    }
+
+
+    #[test]
+    fn drain_and_finish() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        assert!(SignalsReceipts::is_consuming());
+
+        // The consuming thread won't process these, so that they're the backlog.
+        SignalsReceipts::pause();
+        let consumer = thread::spawn(|| SignalsReceipts::consume_loop_with(true, 0, "finished"));
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        raise(SIGURG);
+
+        // The backlog is processed, by this thread, instead of lost, and only once the consuming
+        // thread has returned.
+        let mut finished = None;
+        let flow = SignalsReceipts::drain_and_finish(0, || finished = consumer.join().ok());
+        assert_eq!(flow, ControlFlow::Continue(12));
+        assert_eq!(finished, Some("finished"));
+        assert!(!SignalsReceipts::is_consuming());
+
+        // With the handlers now uninstalled, this will just be ignored, and not counted.
+        raise(SIGURG);
+        assert_eq!(peek_counter::<SIGURG, SignalsReceipts>(), 0);
+        // A consuming loop started now immediately sees the false continue-flag.
+        assert_eq!(SignalsReceipts::consume_loop_with(false, 0, "finished"), "finished");
+    }
+}
+
+
+mod epilogue {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = Vec<&'static str>;
+        type Break = Vec<&'static str>;
+
+        {callback} => |mut state| {
+            state.push("begin");
+            core::ops::ControlFlow::Continue(state)
+        };
+        {epilogue} => |mut state| {
+            state.push("commit");
+            if state.contains(&"last") {
+                core::ops::ControlFlow::Break(state)
+            } else {
+                core::ops::ControlFlow::Continue(state)
+            }
+        };
+        SIGUSR1 => |receipt| receipt.get_state_mut().push("usr1");
+        SIGUSR2 => |receipt| receipt.get_state_mut().push("last");
+    }
+
+
+    #[test]
+    fn epilogue() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        // Called each iteration, even when no delegates are.
+        assert_eq!(
+            SignalsReceipts::consume_once(vec![]),
+            ControlFlow::Continue(vec!["begin", "commit"])
+        );
+
+        raise(SIGUSR1);
+        assert_eq!(
+            SignalsReceipts::consume_once(vec![]),
+            ControlFlow::Continue(vec!["begin", "usr1", "commit"])
+        );
+
+        // Can break the loop, after all the delegates.
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        assert_eq!(
+            SignalsReceipts::consume_once(vec![]),
+            ControlFlow::Break(vec!["begin", "usr1", "last", "commit"])
+        );
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+#[cfg(feature = "generations")]
+mod generations {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::SIGUSR1;
+    use signals_receipts::{Premade as _, SignalReceipt};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = ();
+
+        SIGUSR1 => |receipt| { *receipt.get_state_mut() = receipt.cur_count; };
+    }
+
+    fn generation() -> Option<u64> { <SignalsReceipts as SignalReceipt<SIGUSR1>>::generation() }
+
+
+    #[test]
+    fn generations() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(generation(), Some(0));
+
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        assert_eq!(generation(), Some(3));
+
+        // The count is taken, but the generation remains.
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(3));
+        assert_eq!(generation(), Some(3));
+
+        raise(SIGUSR1);
+        assert_eq!(generation(), Some(4));
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(1));
+
+        // Not reset by re-installing, unlike the counter.
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(generation(), Some(4));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod group {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::{SIGHUP, SIGINT, SIGTERM, SIGUSR1, SIGUSR2};
+    use signals_receipts::{Premade as _, Receipt};
+
+
+    /// (Count of `SIGINT`, count of others, times `SIGHUP` was seen.)
+    type Counts = (u64, u64, u64);
+
+    fn shutdown(receipt: &mut Receipt<u64, (), Counts>) {
+        let (sig_num, cur_count) = (receipt.sig_num, receipt.cur_count);
+        let state = receipt.get_state_mut();
+        if sig_num == SIGINT {
+            state.0 += cur_count;
+        } else {
+            state.1 += cur_count;
+        }
+    }
+
+    signals_receipts::premade! {
+        mod grouped {
+            type Continue = crate::group::Counts;
+            type Break = ();
+
+            [SIGINT, SIGTERM, SIGUSR1] => crate::group::shutdown;
+            SIGHUP => |receipt| { receipt.get_state_mut().2 += 1; };
+            [SIGUSR2,] => |receipt| { receipt.break_loop(); };
+        }
+    }
+
+
+    #[test]
+    fn group() {
+        use grouped::SignalsReceipts;
+
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(SignalsReceipts::declared_signals().iter().count(), 5);
+
+        raise(SIGINT);
+        raise(SIGTERM);
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        raise(SIGHUP);
+        assert_eq!(SignalsReceipts::consume_once((0, 0, 0)), ControlFlow::Continue((1, 3, 1)));
+
+        raise(SIGUSR2);
+        assert_eq!(SignalsReceipts::consume_once((0, 0, 0)), ControlFlow::Break(()));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod guarded {
+    use crate::util::serial;
+    use core::sync::atomic::Ordering::Relaxed;
+    use libc::SIGUSR1;
+    use signals_receipts::{util::{current_disposition, Disposition},
+                           Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        SIGUSR1 => |_| {};
+    }
+
+
+    #[test]
+    fn guarded() {
+        let _serial = serial();
+
+        {
+            let _guard = SignalsReceipts::install_all_handlers_guarded();
+            assert_eq!(current_disposition(SIGUSR1), Ok(Disposition::Custom));
+        }
+        assert_eq!(current_disposition(SIGUSR1), Ok(Disposition::Default));
+        assert!(SignalsReceipts::continue_flag().load(Relaxed));
+
+        // Also on an early return, e.g. by a panic.
+        let r = std::panic::catch_unwind(|| {
+            let _guard = SignalsReceipts::install_all_handlers_guarded().finishing();
+            assert_eq!(current_disposition(SIGUSR1), Ok(Disposition::Custom));
+            panic!("early");
+        });
+        assert!(r.is_err());
+        assert_eq!(current_disposition(SIGUSR1), Ok(Disposition::Default));
+        // It was finished.
+        assert!(!SignalsReceipts::continue_flag().load(Relaxed));
+    }
+}
+
+
+mod install_one {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::{SIGINT, SIGURG, SIGWINCH};
+    use signals_receipts::{is_handler_installed, InstallOneError, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    // Both have default dispositions of ignoring.
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = ();
+
+        SIGURG => |receipt| { *receipt.get_state_mut() = receipt.cur_count; };
+        SIGWINCH => |_| ();
+    }
+
+
+    #[test]
+    fn install_one() {
+        let _serial = serial();
+
+        assert_eq!(SignalsReceipts::install_one(SIGURG, true, true), Ok(()));
+        assert!(is_handler_installed::<SIGURG, SignalsReceipts>());
+        assert!(!is_handler_installed::<SIGWINCH, SignalsReceipts>());
+
+        // Not declared.
+        assert_eq!(SignalsReceipts::install_one(SIGINT, true, true),
+                   Err(InstallOneError::Undeclared(SIGINT)));
+        assert_eq!(SignalsReceipts::uninstall_one(SIGINT), Err(()));
+
+        // Only the installed one is counted.
+        raise(SIGURG);
+        raise(SIGWINCH);
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(1));
+
+        assert_eq!(SignalsReceipts::uninstall_one(SIGURG), Ok(()));
+        assert!(!is_handler_installed::<SIGURG, SignalsReceipts>());
+    }
+}
+
+
+mod on_start {
+    use crate::util::{raise, serial};
+    use core::sync::atomic::{AtomicU64, Ordering::Relaxed};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+    use std::{sync::mpsc, thread};
+
+
+    static CALLBACK_CALLS: AtomicU64 = AtomicU64::new(0);
+
+    signals_receipts::premade! {
+        (use crate::on_start::CALLBACK_CALLS;)
+        type Continue = u64;
+        type Break = u64;
+
+        {callback} => |state| {
+            use core::{ops::ControlFlow, sync::atomic::Ordering::Relaxed};
+
+            CALLBACK_CALLS.fetch_add(1, Relaxed);
+            ControlFlow::Continue(state)
+        };
+        SIGUSR1 => |receipt| { *receipt.get_state_mut() += receipt.cur_count; };
+        SIGUSR2 => |receipt| {
+            let total = *receipt.get_state_ref();
+            receipt.break_loop_with(total);
+        };
+    }
+
+
+    #[test]
+    fn on_start() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        let (ready_sender, ready) = mpsc::channel();
+        let consumer = thread::spawn(move || {
+            SignalsReceipts::consume_loop_with_start(
+                true,
+                0,
+                |state| {
+                    // Called before any iteration.
+                    assert_eq!(CALLBACK_CALLS.load(Relaxed), 0);
+                    *state = 100;
+                    ready_sender.send(()).unwrap();
+                },
+                0,
+            )
+        });
+
+        ready.recv().unwrap();
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        // The state that `on_start` set was given to the delegates.
+        assert!(consumer.join().unwrap() >= 101);
+        // Only once, even though there were multiple iterations.
+        assert!(ready.recv().is_err());
+
+        SignalsReceipts::finish();
+    }
+}
+
+
+mod once_with_count {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::SIGUSR1;
+    use signals_receipts::{consume_once_with_count, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        SIGUSR1 => |_| ();
+    }
+
+
+    #[test]
+    fn once_with_count() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        let mut calls = 0;
+
+        // Nothing was delivered, and so the action is skipped entirely.
+        let flow = consume_once_with_count::<SIGUSR1, SignalsReceipts, _, (), _>(0, |_, _| {
+            calls += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(0));
+        assert_eq!(calls, 0);
+
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+
+        // Called only once, with the coalesced count.
+        let flow = consume_once_with_count::<SIGUSR1, SignalsReceipts, _, (), _>(0, |count, sum| {
+            calls += 1;
+            ControlFlow::Continue(sum + count)
+        });
+        assert_eq!(flow, ControlFlow::Continue(3));
+        assert_eq!(calls, 1);
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod pause {
+    use crate::util::{raise, serial};
+    use core::{sync::atomic::{AtomicU64, Ordering::Relaxed},
+               time::Duration};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{peek_counter, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    static CALLS: AtomicU64 = AtomicU64::new(0);
+    static LAST_COUNT: AtomicU64 = AtomicU64::new(0);
+
+    signals_receipts::premade! {
+        (use crate::pause::{CALLS, LAST_COUNT};)
+        type Continue = u64;
+        type Break = u64;
+
+        SIGUSR1 => |receipt| {
+            use core::sync::atomic::Ordering::Relaxed;
+
+            CALLS.fetch_add(1, Relaxed);
+            LAST_COUNT.store(receipt.cur_count, Relaxed);
+            *receipt.get_state_mut() += receipt.cur_count;
+        };
+        SIGUSR2 => |receipt| {
+            let total = *receipt.get_state_ref();
+            receipt.break_loop_with(total);
+        };
+    }
+
+
+    #[test]
+    fn pause() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        SignalsReceipts::pause();
+        let consumer = thread::spawn(|| SignalsReceipts::consume_loop_with(true, 0, 0));
+
+        // Each of these wakes the consuming thread, but it doesn't process them while paused.
+        for _ in 0 .. 5 {
+            raise(SIGUSR1);
+        }
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(CALLS.load(Relaxed), 0);
+        assert_eq!(peek_counter::<SIGUSR1, SignalsReceipts>(), 5);
+
+        // The accumulated count is processed in one shot.
+        SignalsReceipts::resume();
+        while CALLS.load(Relaxed) == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(CALLS.load(Relaxed), 1);
+        assert_eq!(LAST_COUNT.load(Relaxed), 5);
+
+        raise(SIGUSR2);
+        assert_eq!(consumer.join().unwrap(), 5);
+
+        // Finishing doesn't leave it paused for the next time.
+        SignalsReceipts::pause();
+        SignalsReceipts::finish();
+        assert!(!SignalsReceipts::pause_flag().load(Relaxed));
+    }
+}
+
+
+mod poll {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = u64;
+
+        SIGUSR1 => |receipt| {
+            *receipt.get_state_mut() += receipt.cur_count;
+        };
+        SIGUSR2 => |receipt| {
+            let total = *receipt.get_state_ref();
+            receipt.break_loop_with(total);
+        };
+    }
+
+
+    #[test]
+    fn poll() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        // Nothing pending, and it doesn't block.
+        assert_eq!(SignalsReceipts::poll_once(0), ControlFlow::Continue(0));
+
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        assert_eq!(SignalsReceipts::poll_once(0), ControlFlow::Continue(2));
+        // The wakeups, that no thread waited for, didn't accumulate.
+        if let Some(pending) = SignalsReceipts::pending_wakeups() {
+            assert_eq!(pending, 0);
+        }
+
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        assert_eq!(SignalsReceipts::poll_once(2), ControlFlow::Break(3));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod quiesce {
+    use crate::util::{raise, serial};
+    use core::{pin::Pin,
+               sync::atomic::{AtomicU32, Ordering::Relaxed}};
+    use libc::{SIGQUIT, SIGURG, SIGUSR1};
+    use sem_safe::{non_named::Semaphore as _, plaster::non_named::Semaphore};
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    static PROCESSED_USR1: AtomicU32 = AtomicU32::new(0);
+    static PROCESSED_USR2: AtomicU32 = AtomicU32::new(0);
+
+
+    signals_receipts::premade! {
+        (use crate::quiesce::{raise, PROCESSED_USR1, PROCESSED_USR2};
+         use core::sync::atomic::Ordering::Relaxed;)
+
+        type Continue = [SemaphoreRef<'static>; 2];
+        type Break = &'static str;
+
+        // This order of declaration of these determines the order of their execution within the
+        // same iteration of consume_loop.  This order is essential to this test.
+
+        SIGUSR2 => |_| { PROCESSED_USR2.fetch_add(1, Relaxed); };
+        SIGQUIT => |receipt| {
+            let [quitter_sem, finisher_sem] = receipt.get_state_ref();
+            // Counted after its delegate already ran in this iteration, and before the quiescing,
+            // and so it must still be processed by the final iteration.
+            raise(libc::SIGUSR2);
+            // Tell the finisher_thread to do the quiesce operation.
+            finisher_sem.post().unwrap();
+            // Wait until the finisher_thread indicates it did.  Unlike with `finish`, the
+            // remaining delegates will still be run after this.
+            quitter_sem.wait().unwrap();
+        };
+        SIGUSR1 => |_| { PROCESSED_USR1.fetch_add(1, Relaxed); };
+        SIGURG => |_| unreachable!(); // The handlers being uninstalled prevents running this.
+    }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        // Ensure both of these signals have been delivered and counted already, to ensure the
+        // consume_loop will process them in the same loop iteration.
+        raise(SIGUSR1);
+        raise(SIGQUIT);
+
+        let [quitter_sem, finisher_sem] = {
+            static SEMAPHORES: [Semaphore; 2] = [Semaphore::uninit(), Semaphore::uninit()];
+            [&SEMAPHORES[0], &SEMAPHORES[1]].map(|s| Pin::static_ref(s).init().unwrap())
+        };
+
+        let receipts_thread = thread::spawn(move || {
+            SignalsReceipts::consume_loop_with(false, [quitter_sem, finisher_sem], "finished")
+        });
+
+        let quiescer_thread = thread::spawn(move || {
+            // Wait to be told to do the quiesce operation.
+            finisher_sem.wait().unwrap();
+            SignalsReceipts::quiesce();
+            // With the handlers now uninstalled, this will just be ignored.
+            raise(SIGURG);
+            // Tell the receipts_thread that the quiesce operation was done.
+            quitter_sem.post().unwrap();
+        });
+
+        let v = receipts_thread.join().unwrap();
+        assert_eq!(v, "finished");
+        assert!(!SignalsReceipts::continue_flag().load(Relaxed));
+
+        quiescer_thread.join().unwrap();
+
+        // Everything counted before quiescing was processed.
+        assert_eq!(PROCESSED_USR1.load(Relaxed), 1);
+        assert_eq!(PROCESSED_USR2.load(Relaxed), 1);
+
+        // With the handlers now uninstalled, this will just be ignored.
+        raise(SIGURG);
+    }
+}
+
+
+mod receipt {
+    use core::ops::ControlFlow;
+    use libc::{SIGHUP, SIGTERM};
+    use signals_receipts::Receipt;
+
+
+    /// An example of a user's delegate, which is tested here without any signals.
+    fn delegate(receipt: &mut Receipt<u64, &'static str, u64>) {
+        if receipt.sig_num == SIGTERM {
+            receipt.break_loop_with("terminated");
+        } else {
+            let count = receipt.cur_count;
+            receipt.update_state(|total| *total += count);
+        }
+    }
+
+
+    #[test]
+    fn new_for_test() {
+        let mut receipt = Receipt::new_for_test(SIGHUP, 3, 10);
+        assert_eq!(receipt.flow, ControlFlow::Continue(10));
+        delegate(&mut receipt);
+        assert_eq!(*receipt.get_state_ref(), 13);
+
+        let mut receipt = Receipt::new_for_test(SIGTERM, 1, 0);
+        delegate(&mut receipt);
+        assert_eq!(receipt.flow, ControlFlow::Break("terminated"));
+    }
+
+    #[test]
+    fn signal_name() {
+        assert_eq!(Receipt::<u64>::new_for_test(SIGHUP, 1, ()).signal_name(), Some("SIGHUP"));
+        assert_eq!(Receipt::<u64>::new_for_test(SIGTERM, 1, ()).signal_name(), Some("SIGTERM"));
+    }
+
+    #[test]
+    fn break_if() {
+        let mut receipt = Receipt::<u64, &str, u64>::new_for_test(SIGHUP, 1, 7);
+        receipt.break_if(false, "not");
+        receipt.break_if_with(false, || unreachable!());
+        assert_eq!(receipt.flow, ControlFlow::Continue(7));
+        receipt.break_if_with(true, || "lazy");
+        assert_eq!(receipt.flow, ControlFlow::Break("lazy"));
+
+        let mut receipt = Receipt::<u64, &str, u64>::new_for_test(SIGHUP, 1, 7);
+        receipt.break_if(true, "eager");
+        assert_eq!(receipt.flow, ControlFlow::Break("eager"));
+    }
+}
+
+
+mod restart {
+    use crate::util::{raise, serial};
+    use core::time::Duration;
+    use libc::SIGUSR1;
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    signals_receipts::premade! {
+        type Continue = u32;
+        type Break = u32;
+
+        SIGUSR1 => |receipt| {
+            let state = *receipt.get_state_ref();
+            receipt.break_loop_with(state);
+        };
+    }
+
+
+    #[test]
+    fn main() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        // Counted before consuming, so the first iteration will break.
+        raise(SIGUSR1);
+        let v = SignalsReceipts::consume_loop_with(false, 1, 0);
+        assert_eq!(v, 1);
+
+        // Counted while not consuming, which is preserved when not reinstalling.
+        raise(SIGUSR1);
+        let v = SignalsReceipts::restart_consume_loop_with(false, false, v, |prev| prev + 1, 0);
+        assert_eq!(v, 2);
+
+        SignalsReceipts::finish();
+
+        // After finishing, reinstalling is needed for receipts to be counted again.
+        let raiser = thread::spawn(|| {
+            thread::sleep(Duration::from_secs(1));
+            raise(SIGUSR1);
+        });
+        let v = SignalsReceipts::restart_consume_loop_with(false, true, v, |prev| prev * 10, 0);
+        assert_eq!(v, 20);
+
+        raiser.join().unwrap();
+        SignalsReceipts::finish();
+    }
+}
+
+
+mod retaining {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow, sync::atomic::{AtomicU8, Ordering::Relaxed}};
+    use libc::SIGUSR1;
+    use signals_receipts::{peek_counter, Premade as _, SignalReceipt};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::panic::{self, AssertUnwindSafe};
+
+
+    static CALLS: AtomicU8 = AtomicU8::new(0);
+
+    signals_receipts::premade! {
+        (use crate::retaining::{raise, CALLS};
+         use core::sync::atomic::Ordering::Relaxed;)
+        type Continue = u64;
+        type Break = ();
+
+        SIGUSR1 {retaining} => |receipt| {
+            match CALLS.fetch_add(1, Relaxed) {
+                0 => panic!("first time fails"),
+                // Delivered while processing, and so not included in this count.
+                1 => raise(receipt.sig_num),
+                _ => {},
+            }
+            *receipt.get_state_mut() += receipt.cur_count;
+        };
+    }
+
+
+    fn is_dirty() -> bool {
+        let (word, bit) = <SignalsReceipts as SignalReceipt<SIGUSR1>>::dirty_bit().unwrap();
+        word.load(Relaxed) & (1 << bit) != 0
+    }
+
+
+    #[test]
+    fn retaining() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        let r = panic::catch_unwind(AssertUnwindSafe(|| SignalsReceipts::consume_once(0)));
+        assert!(r.is_err());
+        // The failed delegate left the count intact, and the dirty bit set.
+        assert_eq!(peek_counter::<SIGUSR1, SignalsReceipts>(), 2);
+        assert!(is_dirty());
+
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(2));
+        // The one that was delivered during the delegate wasn't lost.
+        assert_eq!(peek_counter::<SIGUSR1, SignalsReceipts>(), 1);
+        assert!(is_dirty());
+
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(1));
+        assert_eq!(peek_counter::<SIGUSR1, SignalsReceipts>(), 0);
+        // Handled, and so not revisited.
+        assert!(!is_dirty());
+        assert_eq!(SignalsReceipts::consume_once(0), ControlFlow::Continue(0));
+        // The delegate isn't called again without new deliveries.
+        assert_eq!(CALLS.load(Relaxed), 3);
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod rt {
+    use crate::util::{raise, serial};
+    use core::ops::ControlFlow;
+    use libc::SIGRTMIN;
+    use signals_receipts::{peek_counter, resolve_signum, rt, Premade as _, SignalNumber};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = [(SignalNumber, u64); 2];
+        type Break = ();
+
+        SIGRT_A = rt(0) => |receipt| {
+            let seen = (receipt.sig_num, receipt.cur_count);
+            receipt.get_state_mut()[0] = seen;
+        };
+        SIGRT_B = rt(2): AtomicU8 => |receipt| {
+            let seen = (receipt.sig_num, receipt.cur_count.into());
+            receipt.get_state_mut()[1] = seen;
+        };
+    }
+
+
+    #[test]
+    fn real_time() {
+        let _serial = serial();
+
+        let (a, b): (SignalNumber, SignalNumber) = (SIGRTMIN(), SIGRTMIN() + 2);
+        assert_eq!(resolve_signum(rt(0)), a);
+        assert_eq!(resolve_signum(rt(2)), b);
+        assert_eq!(resolve_signum(libc::SIGUSR1), libc::SIGUSR1);
+
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(SignalsReceipts::verify_all_handlers(), Ok(()));
+        assert!(SignalsReceipts::declared_signals().contains(a));
+        assert!(SignalsReceipts::declared_signals().contains(b));
+
+        raise(a);
+        for _ in 0 .. 3 {
+            raise(b);
+        }
+        assert_eq!(peek_counter::<{ rt(2) }, SignalsReceipts>(), 3);
+
+        let seen = SignalsReceipts::consume_once([(0, 0); 2]);
+        assert_eq!(seen, ControlFlow::Continue([(a, 1), (b, 3)]));
+
+        // As if received other than via our handler, e.g. from a `signalfd`.
+        assert!(SignalsReceipts::count_delivery(a));
+        assert_eq!(peek_counter::<{ rt(0) }, SignalsReceipts>(), 1);
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+#[cfg(feature = "serde")]
+mod serde {
+    use core::ops::ControlFlow;
+    use signals_receipts::Receipt;
+
+
+    #[test]
+    fn round_trip() {
+        let mut receipt = Receipt::<u8, (), u32>::new_for_test(libc::SIGUSR1, 3, 7);
+        receipt.received_at = Some(42);
+        let json = serde_json::to_string(&receipt).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"sig_num":{},"cur_count":3,"received_at":42}}"#, libc::SIGUSR1)
+        );
+
+        let back: Receipt<u8, (), u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.sig_num, libc::SIGUSR1);
+        assert_eq!(back.cur_count, 3);
+        assert_eq!(back.received_at, Some(42));
+        // The state isn't serialized, and so it's the default.
+        assert_eq!(back.flow, ControlFlow::Continue(0));
+    }
+}
+
+
+mod signal_enum {
+    use libc::{SIGINT, SIGUSR1, SIGUSR2};
+    use signals_receipts::SignalNumber;
+    use signals_receipts_premade::Signal;
+
+
+    signals_receipts::premade! {
+        SIGUSR1 => |_| {};
+        SIGUSR2 => |_| {};
+    }
+
+
+    #[test]
+    fn signal_enum() {
+        assert_eq!(Signal::SIGUSR1.signum(), SIGUSR1);
+        assert_eq!(SignalNumber::from(Signal::SIGUSR2), SIGUSR2);
+
+        assert_eq!(Signal::try_from(SIGUSR1), Ok(Signal::SIGUSR1));
+        assert_eq!(SIGUSR2.try_into(), Ok(Signal::SIGUSR2));
+        assert_eq!(Signal::try_from(SIGINT), Err(SIGINT));
+    }
+}
+
+
+#[cfg(feature = "signalfd")]
+mod signalfd {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow,
+               sync::atomic::{AtomicU64, Ordering::Relaxed}};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{signalfd::SignalFd, util::mask_all_signals_of_current_thread};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    static USR1: AtomicU64 = AtomicU64::new(0);
+
+    signals_receipts::premade! {
+        (use crate::signalfd::USR1;)
+        type Continue = u32;
+        type Break = ();
+
+        SIGUSR1 => |receipt| {
+            use core::sync::atomic::Ordering::Relaxed;
+
+            USR1.fetch_add(receipt.cur_count, Relaxed);
+            *receipt.get_state_mut() += 1;
+        };
+        SIGUSR2 => |receipt| receipt.break_loop();
+    }
+
+
+    #[test]
+    fn signalfd() {
+        let _serial = serial();
+
+        // Only the current thread is involved, and so only it needs to block them.
+        mask_all_signals_of_current_thread();
+        let fd = SignalFd::for_premade::<SignalsReceipts>().unwrap();
+        assert!(fd.as_raw_fd() >= 0);
+
+        // Nothing pending yet.
+        assert_eq!(fd.drain::<SignalsReceipts>(0).unwrap(), ControlFlow::Continue(0));
+
+        // Blocked, and so left pending, to be read from the file descriptor.
+        raise(SIGUSR1);
+        assert_eq!(fd.drain::<SignalsReceipts>(0).unwrap(), ControlFlow::Continue(1));
+        assert_eq!(USR1.load(Relaxed), 1);
+
+        raise(SIGUSR2);
+        assert_eq!(fd.drain::<SignalsReceipts>(1).unwrap(), ControlFlow::Break(()));
+    }
+}
+
+
+#[cfg(feature = "sigwait")]
+mod sigwait {
+    use crate::util::{raise, serial};
+    use core::{sync::atomic::Ordering::Relaxed, time::Duration};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{sigwait::sync_consume_loop, util::mask_all_signals_of_current_thread,
+                           Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = u64;
+
+        SIGUSR1 => |receipt| {
+            *receipt.get_state_mut() += receipt.cur_count;
+        };
+        SIGUSR2 => |receipt| {
+            let total = *receipt.get_state_ref();
+            receipt.break_loop_with(total);
+        };
+    }
+
+
+    #[test]
+    fn sigwait() {
+        let _serial = serial();
+
+        // Only this thread waits, and so these must be pending for it, not for the process.
+        mask_all_signals_of_current_thread();
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+
+        // No handlers are installed, and yet the delegates process them, in the normal context.
+        let mut infos = Vec::new();
+        let total = sync_consume_loop::<SignalsReceipts>(None, 0, |info| infos.push(*info), 0);
+        assert_eq!(total, 1);
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].signo, SIGUSR1);
+        assert_eq!(infos[1].signo, SIGUSR2);
+        assert_eq!(infos[0].pid, i32::try_from(std::process::id()).unwrap_or(0));
+
+        // With a timeout, the continue-flag is checked even without any signals.
+        let stopper = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            SignalsReceipts::continue_flag().store(false, Relaxed);
+        });
+        let finished =
+            sync_consume_loop::<SignalsReceipts>(Some(Duration::from_millis(10)), 0, |_| {}, 7);
+        assert_eq!(finished, 7);
+        stopper.join().unwrap_or_default();
+    }
+}
+
+
+#[cfg(feature = "spawn")]
+mod spawn {
+    use crate::util::{raise, serial};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = u64;
+
+        SIGUSR1 => |receipt| { *receipt.get_state_mut() += receipt.cur_count; };
+        SIGUSR2 => |receipt| {
+            let total = *receipt.get_state_ref();
+            receipt.break_loop_with(total);
+        };
+    }
+
+
+    #[test]
+    fn spawn() {
+        let _serial = serial();
+
+        let first = SignalsReceipts::install_and_spawn().unwrap();
+        raise(SIGUSR1);
+        raise(SIGUSR2);
+        assert_eq!(first.join().unwrap(), 1);
+
+        // Again, but finished instead of broken, and so the default is returned.
+        let second = SignalsReceipts::install_and_spawn().unwrap();
+        assert_eq!(second.finish().unwrap(), 0);
+        assert!(SignalsReceipts::verify_all_handlers().is_err());
+    }
+}
+
+
+mod stop_consuming {
+    use crate::util::{raise, serial};
+    use libc::{SIGUSR1, SIGUSR2};
+    use signals_receipts::{peek_counter, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    signals_receipts::premade! {
+        type Continue = u64;
+        type Break = u64;
+
+        SIGUSR1 => |receipt| { *receipt.get_state_mut() += receipt.cur_count; };
+        SIGUSR2 => |receipt| {
+            let total = *receipt.get_state_ref();
+            receipt.break_loop_with(total);
+        };
+    }
+
+    fn spawn_consumer() -> thread::JoinHandle<u64> {
+        thread::spawn(|| SignalsReceipts::consume_loop_with(true, 0, u64::MAX))
+    }
+
+
+    #[test]
+    fn stop_consuming() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        assert!(SignalsReceipts::is_consuming());
+
+        let first = spawn_consumer();
+        SignalsReceipts::stop_consuming();
+        assert_eq!(first.join().unwrap(), u64::MAX);
+        assert!(!SignalsReceipts::is_consuming());
+
+        // Still installed, and so these are counted while no thread consumes.
+        SignalsReceipts::verify_all_handlers().unwrap();
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+        assert_eq!(peek_counter::<SIGUSR1, SignalsReceipts>(), 2);
+
+        // A fresh thread processes the backlog.
+        SignalsReceipts::reset_continue_flag();
+        assert!(SignalsReceipts::is_consuming());
+        let second = spawn_consumer();
+        raise(SIGUSR2);
+        assert_eq!(second.join().unwrap(), 2);
+
+        SignalsReceipts::finish();
+        assert!(!SignalsReceipts::is_consuming());
+    }
+}
+
+
+#[cfg(not(target_os = "macos"))] // It doesn't have `sem_timedwait()`.
+mod timed {
+    use crate::util::{raise, serial};
+    use core::{sync::atomic::{AtomicU32, Ordering::Relaxed},
+               time::Duration};
+    use libc::SIGUSR1;
+    use signals_receipts::{util::WaitClock, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::{thread, time::Instant};
+
+
+    static RECEIVED: AtomicU32 = AtomicU32::new(0);
+
+    signals_receipts::premade! {
+        (use crate::timed::RECEIVED;)
+        type Continue = u32;
+        type Break = u32;
+
+        // Called upon each timeout, without any signals, for periodic work.
+        {callback} => |ticks| {
+            use core::ops::ControlFlow;
+
+            if ticks == 3 { ControlFlow::Break(ticks) } else { ControlFlow::Continue(ticks + 1) }
+        };
+        SIGUSR1 => |receipt| {
+            use core::sync::atomic::Ordering::Relaxed;
+
+            let _prev = RECEIVED.fetch_add(receipt.cur_count.try_into().unwrap(), Relaxed);
+        };
+    }
+
+
+    #[test]
+    fn timed() {
+        const TIMEOUT: Duration = Duration::from_millis(10);
+
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+
+        for clock in [WaitClock::Monotonic, WaitClock::Realtime] {
+            RECEIVED.store(0, Relaxed);
+            raise(SIGUSR1);
+            let start = Instant::now();
+            // On another thread, because the masking remains after the loop returns.
+            let consumer = thread::spawn(move || {
+                SignalsReceipts::consume_loop_with_timeout(true, TIMEOUT, clock, 0, u32::MAX)
+            });
+            let ticks = consumer.join().unwrap();
+            assert_eq!(ticks, 3);
+            assert_eq!(RECEIVED.load(Relaxed), 1);
+            // The delivery's wakeup (once the semaphore was initialized, by the first round)
+            // might have ended one of the waits early, but it timed out before each of the
+            // others.
+            assert!(start.elapsed() >= TIMEOUT * 2);
+        }
+
+        SignalsReceipts::finish();
+    }
+}
+
+
+#[cfg(feature = "timestamps")]
+mod timestamps {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow,
+               sync::atomic::{AtomicU64, Ordering::Relaxed}};
+    use libc::SIGUSR1;
+    use signals_receipts::{util::ClockSource, Premade as _, SignalReceipt};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    static RECEIVED_AT: AtomicU64 = AtomicU64::new(0);
+
+    signals_receipts::premade! {
+        (use crate::timestamps::RECEIVED_AT;)
+        type Continue = ();
+        type Break = ();
+        {clock} => Boottime;
+
+        SIGUSR1 => |receipt| {
+            use core::sync::atomic::Ordering::Relaxed;
+
+            RECEIVED_AT.store(receipt.received_at.unwrap(), Relaxed);
+        };
+    }
+
+
+    #[test]
+    fn timestamps() {
+        let _serial = serial();
+
+        assert_eq!(<SignalsReceipts as SignalReceipt<SIGUSR1>>::CLOCK, ClockSource::Boottime);
+
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(<SignalsReceipts as SignalReceipt<SIGUSR1>>::last_received_nanos(), None);
+
+        // By the declared clock.
+        let before = ClockSource::Boottime.now_nanos();
+        raise(SIGUSR1);
+        let after = ClockSource::Boottime.now_nanos();
+
+        let last = <SignalsReceipts as SignalReceipt<SIGUSR1>>::last_received_nanos().unwrap();
+        assert!(before <= last && last <= after);
+
+        assert_eq!(SignalsReceipts::consume_once(()), ControlFlow::Continue(()));
+        assert_eq!(RECEIVED_AT.load(Relaxed), last);
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod try_install_all {
+    use crate::util::serial;
+    use libc::{SIGRTMIN, SIGURG, SIGUSR1, SIGUSR2};
+    use signals_receipts::{util::{current_disposition, mask_all_signals_of_current_thread_saving,
+                                  Disposition, SigSet},
+                           Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        SIGUSR1 => |_| ();
+        SIGUSR2 => |_| ();
+        // Beyond `SIGRTMAX`, and so invalid.
+        SIGRT_INVALID = rt(200) => |_| ();
+        SIGURG => |_| ();
+    }
+
+
+    #[test]
+    fn rolled_back() {
+        let _serial = serial();
+
+        let error =
+            SignalsReceipts::try_install_all_handlers_with(true, true, false).unwrap_err();
+        assert_eq!(error.failed.signum, SIGRTMIN() + 200);
+        assert_eq!(error.failed.errno.0, libc::EINVAL);
+
+        let mut expected = SigSet::empty();
+        expected.insert(SIGUSR1).unwrap();
+        expected.insert(SIGUSR2).unwrap();
+        assert_eq!(error.rolled_back, expected);
+
+        for signum in [SIGUSR1, SIGUSR2, SIGURG] {
+            assert_eq!(current_disposition(signum), Ok(Disposition::Default));
+        }
+
+        // The mask that blocked them while installing was restored.
+        let guard = mask_all_signals_of_current_thread_saving();
+        assert!([SIGUSR1, SIGUSR2, SIGURG].iter().all(|&signum| !guard.saved().contains(signum)));
+    }
+}
+
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod values {
+    use crate::util::serial;
+    use core::{ffi::c_int, ops::ControlFlow, ptr};
+    use signals_receipts::{is_handler_installed, is_handler_with_value_installed, peek_counter,
+                           resolve_signum, rt, Premade as _, SignalReceiptWithValue};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        type Continue = Vec<core::ffi::c_int>;
+        type Break = core::ffi::c_int;
+
+        SIGRT_MSG = rt(1): AtomicU8 {values} => |receipt| {
+            if receipt.value < 0 {
+                receipt.break_loop_with(receipt.value);
+            } else {
+                let value = receipt.value;
+                receipt.get_state_mut().push(value);
+            }
+        };
+    }
+
+
+    fn send_value(value: c_int) {
+        #![allow(unsafe_code)]
+        let mut sigval = libc::sigval { sival_ptr: ptr::null_mut() };
+        // SAFETY: `sival_int` is at the start of the C `union`.
+        unsafe { ptr::addr_of_mut!(sigval).cast::<c_int>().write(value); }
+        // SAFETY: Always safe.
+        let pid = unsafe { libc::getpid() };
+        // SAFETY: The arguments are proper.
+        let r = unsafe { libc::sigqueue(pid, resolve_signum(rt(1)), sigval) };
+        assert_eq!(r, 0);
+    }
+
+    /// `sigqueue()` is process-directed, and so the deliveries might be to another thread.
+    fn wait_for(count: u8) {
+        while peek_counter::<{ rt(1) }, SignalsReceipts>() < count {
+            std::thread::yield_now();
+        }
+    }
+
+
+    #[test]
+    fn values() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(SignalsReceipts::verify_all_handlers(), Ok(()));
+        assert!(is_handler_with_value_installed::<{ rt(1) }, SignalsReceipts>());
+        assert!(!is_handler_installed::<{ rt(1) }, SignalsReceipts>());
+
+        for value in [7, 0, 7] {
+            send_value(value);
+        }
+        wait_for(3);
+        // Each value, in order, without coalescing.
+        let flow = SignalsReceipts::consume_once(Vec::new());
+        assert_eq!(flow, ControlFlow::Continue(vec![7, 0, 7]));
+        assert_eq!(peek_counter::<{ rt(1) }, SignalsReceipts>(), 0);
+
+        // The delegate can break, and the rest remain queued.
+        for value in [1, -1, 2] {
+            send_value(value);
+        }
+        wait_for(3);
+        assert_eq!(SignalsReceipts::consume_once(Vec::new()), ControlFlow::Break(-1));
+        assert_eq!(SignalsReceipts::consume_once(Vec::new()), ControlFlow::Continue(vec![2]));
+        let queue = <SignalsReceipts as SignalReceiptWithValue<{ rt(1) }>>::value_queue();
+        assert_eq!(queue.take_dropped(), 0);
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod verify {
+    use crate::util::serial;
+    use libc::{SIGURG, SIGWINCH};
+    use signals_receipts::{is_handler_installed, uninstall_handler, util::SigSet, Premade as _};
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    // Both have default dispositions of ignoring.
+    signals_receipts::premade! {
+        SIGURG => |_| ();
+        SIGWINCH => |_| ();
+    }
+
+
+    #[test]
+    fn verify() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers_verified().unwrap();
+        assert!(is_handler_installed::<SIGWINCH, SignalsReceipts>());
+
+        // Simulate something else clobbering one of our handlers.
+        thread::spawn(uninstall_handler::<SIGWINCH>).join().unwrap();
+        assert!(!is_handler_installed::<SIGWINCH, SignalsReceipts>());
+        assert!(is_handler_installed::<SIGURG, SignalsReceipts>());
+
+        let err = SignalsReceipts::verify_all_handlers().unwrap_err();
+        assert_eq!(err.unverified, SigSet::from_signals(&[SIGWINCH]).unwrap());
+
+        SignalsReceipts::install_all_handlers_verified().unwrap();
+        SignalsReceipts::finish();
+        assert!(SignalsReceipts::verify_all_handlers().is_err());
+    }
+}
+
+
+mod wake {
+    use crate::util::serial;
+    use core::{sync::atomic::{AtomicU64, Ordering::Relaxed},
+               time::Duration};
+    use signals_receipts::Premade as _;
+    use signals_receipts_premade::SignalsReceipts;
+    use std::thread;
+
+
+    static ITERATIONS: AtomicU64 = AtomicU64::new(0);
+    static RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+    signals_receipts::premade! {
+        (use crate::wake::{ITERATIONS, RECEIVED};)
+
+        {callback} => |state| {
+            use core::{ops::ControlFlow, sync::atomic::Ordering::Relaxed};
+
+            ITERATIONS.fetch_add(1, Relaxed);
+            ControlFlow::Continue(state)
+        };
+        SIGUSR1 => |receipt| {
+            use core::sync::atomic::Ordering::Relaxed;
+
+            RECEIVED.fetch_add(receipt.cur_count, Relaxed);
+        };
+    }
+
+
+    fn wait_for_iterations(at_least: u64) {
+        while ITERATIONS.load(Relaxed) < at_least {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+
+    #[test]
+    fn wake() {
+        let _serial = serial();
+
+        SignalsReceipts::install_all_handlers();
+        let consumer = thread::spawn(SignalsReceipts::consume_loop);
+        wait_for_iterations(1);
+
+        // Each wakes the loop for an extra iteration, without any signal.
+        SignalsReceipts::wake();
+        wait_for_iterations(2);
+        SignalsReceipts::wake();
+        wait_for_iterations(3);
+        assert_eq!(RECEIVED.load(Relaxed), 0);
+        // And the loop continues normally.
+        assert!(!consumer.is_finished());
+
+        SignalsReceipts::finish();
+        consumer.join().unwrap();
+    }
+}
+
+
+mod wake_every {
+    use crate::util::{raise, serial};
+    use core::sync::atomic::Ordering::Relaxed;
+    use libc::SIGURG;
+    use signals_receipts::{Premade, SemaphoreMethods as _, SignalReceipt};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    signals_receipts::premade! {
+        {wake_every} => 10;
+        SIGURG => |_| ();
+    }
+
+
+    #[test]
+    fn wake_every() {
+        let _serial = serial();
+
+        assert_eq!(<SignalsReceipts as SignalReceipt<SIGURG>>::WAKE_EVERY, 10);
+
+        SignalsReceipts::install_all_handlers();
+        // Initialize the semaphore manually, since we don't do `consume_loop`.
+        let sem = <SignalsReceipts as Premade>::semaphore().init().unwrap();
+
+        for _ in 0 .. 25 {
+            raise(SIGURG);
+        }
+        // Every delivery is counted, but only every 10th posted the semaphore.
+        assert_eq!(<SignalsReceipts as SignalReceipt<SIGURG>>::counter().load(Relaxed), 25);
+        assert_eq!(sem.try_wait(), Ok(()));
+        assert_eq!(sem.try_wait(), Ok(()));
+        assert_eq!(sem.try_wait(), Err(()));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod widths {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow,
+               sync::atomic::{AtomicU16, AtomicU64, AtomicU8}};
+    use libc::{SIGURG, SIGUSR1, SIGUSR2};
+    use signals_receipts::{Premade as _, SignalReceipt};
+    use signals_receipts_premade::SignalsReceipts;
+
+
+    // The annotated `let`s check that each delegate's `cur_count` is of the integer type of the
+    // `AtomicUInt` declared for its signal, or of `u64` by default.
+    signals_receipts::premade! {
+        type Continue = (u8, u16, u64);
+        type Break = ();
+
+        SIGUSR1: AtomicU8 => |receipt| {
+            let count: u8 = receipt.cur_count;
+            receipt.get_state_mut().0 = count;
+        };
+        SIGUSR2: core::sync::atomic::AtomicU16 => |receipt| {
+            let count: u16 = receipt.cur_count;
+            receipt.get_state_mut().1 = count;
+        };
+        SIGURG => |receipt| {
+            let count: u64 = receipt.cur_count;
+            receipt.get_state_mut().2 = count;
+        };
+    }
+
+
+    #[test]
+    fn widths() {
+        let _serial = serial();
+
+        let _u8: &AtomicU8 = <SignalsReceipts as SignalReceipt<SIGUSR1>>::counter();
+        let _u16: &AtomicU16 = <SignalsReceipts as SignalReceipt<SIGUSR2>>::counter();
+        let _u64: &AtomicU64 = <SignalsReceipts as SignalReceipt<SIGURG>>::counter();
+
+        SignalsReceipts::install_all_handlers();
+
+        raise(SIGUSR1);
+        for _ in 0 .. 2 {
+            raise(SIGUSR2);
+        }
+        for _ in 0 .. 3 {
+            raise(SIGURG);
+        }
+        assert_eq!(SignalsReceipts::consume_once((0, 0, 0)), ControlFlow::Continue((1, 2, 3)));
+
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
@@ -8,7 +8,7 @@
 
 use cfg_if::cfg_if;
 use core::{ffi::c_int, sync::atomic::Ordering::Relaxed};
-use signals_receipts::{Premade, SemaphoreMethods as _, SignalNumber, SignalReceipt};
+use signals_receipts::{peek_counter, Premade, SemaphoreMethods as _, SignalNumber, SignalReceipt};
 use signals_receipts_premade::SignalsReceipts;
 use std::thread;
 
@@ -53,11 +53,18 @@ fn semaphore_count() -> c_int {
     } }
 }
 
+fn is_dirty() -> bool {
+    let (word, bit) = <SignalsReceipts as SignalReceipt<SIG>>::dirty_bit().unwrap();
+    word.load(Relaxed) & (1 << bit) != 0
+}
+
 fn assert_values(flag: bool, sig_count: u64, sem_count: c_int) {
     assert_eq!(continue_flag(), flag);
     assert_eq!(signal_delivery_count(), sig_count);
     #[cfg(not(target_os = "macos"))]
     assert_eq!(semaphore_count(), sem_count);
+    #[cfg(not(target_os = "macos"))]
+    assert_eq!(SignalsReceipts::pending_wakeups(), Some(sem_count));
 }
 
 
@@ -72,11 +79,24 @@ fn main() {
     <SignalsReceipts as Premade>::semaphore().init().unwrap();
 
     assert_values(true, 0, 0);
+    assert!(!is_dirty());
+    assert!(!SignalsReceipts::coalescing_observed_for(SIG));
 
-    for _ in 0 .. 1_000 {
+    raise(SIG);
+    assert!(!SignalsReceipts::coalescing_observed_for(SIG));
+    for _ in 1 .. 1_000 {
         raise(SIG);
     }
     assert_values(true, 1_000, 1_000);
+    // Peeking doesn't reset the counter.
+    assert_eq!(peek_counter::<SIG, SignalsReceipts>(), 1_000);
+    assert_eq!(<SignalsReceipts as SignalReceipt<SIG>>::peek_count(), 1_000);
+    assert_values(true, 1_000, 1_000);
+    assert!(is_dirty());
+    assert!(SignalsReceipts::coalescing_observed_for(SIG));
+    assert!(!SignalsReceipts::coalescing_observed_for(libc::SIGUSR1)); // Not declared.
+    // The 64-bit counter isn't near saturating.
+    assert!(!SignalsReceipts::take_overflowed_for(SIG));
 
     SignalsReceipts::finish();
     assert_values(false, 1_000, 1_001);
@@ -86,9 +106,11 @@ fn main() {
     }
     assert_values(false, 1_000, 1_001);
 
-    // Re-installing the handling resets the counter(s) and the continue-flag.
+    // Re-installing the handling resets the counter(s), the coalescing flag(s), and the
+    // continue-flag.
     SignalsReceipts::install_all_handlers();
     assert_values(true, 0, 1_001);
+    assert!(!SignalsReceipts::coalescing_observed_for(SIG));
 
     // Now have a consuming thread running `consume_loop`.  Since the semaphore already has a
     // positive value, the loop will have to iterate that many times pointlessly.
@@ -105,4 +127,22 @@ fn main() {
     // no signals were received.  This is not desirable behavior, but it's just due to there not
     // being a way to forcibly reset the value of a semaphore, and it's harmless.
     assert_values(true, 0, 0);
+    // The dirty bit, left over from before re-installing, was cleared by the loop.
+    assert!(!is_dirty());
+
+    // Forcibly resetting the semaphore, with a limit, is possible by draining it.
+    for _ in 0 .. 10 {
+        raise(SIG);
+    }
+    assert_values(true, 10, 10);
+    assert_eq!(SignalsReceipts::reset_semaphore(3), 3);
+    assert_values(true, 10, 7);
+    assert_eq!(SignalsReceipts::reset_semaphore(100), 7);
+    assert_values(true, 10, 0);
+
+    // And re-installing can do that, which avoids the pointless iterations.
+    SignalsReceipts::finish();
+    assert_values(false, 10, 1);
+    SignalsReceipts::install_all_handlers_with(true, true, true);
+    assert_values(true, 0, 0);
 }
@@ -0,0 +1,389 @@
+#![cfg(test)] // Suppress `clippy::tests_outside_test_module`.
+#![allow(
+    clippy::host_endian_bytes,
+    clippy::indexing_slicing,
+    clippy::shadow_unrelated,
+    clippy::std_instead_of_core,
+    clippy::unwrap_used,
+    unused_crate_dependencies // Ignore the lib crate's deps that are supplied here also.
+)]
+
+#[path = "help/util.rs"]
+mod util;
+
+
+mod alt_stack {
+    use crate::util::{raise, serial};
+    use core::{mem::MaybeUninit,
+               ptr,
+               sync::atomic::{AtomicBool, AtomicU64, Ordering::Relaxed}};
+    use libc::SIGURG;
+    use signals_receipts::{install_handler_on_altstack, uninstall_handler,
+                           util::install_alt_stack, SemaphoreRef, SignalReceipt};
+
+
+    static WAS_ON_STACK: AtomicBool = AtomicBool::new(false);
+
+    struct OnStack;
+
+    impl SignalReceipt<SIGURG> for OnStack {
+        type AtomicUInt = AtomicU64;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+
+        fn on_delivery() { WAS_ON_STACK.store(is_on_alt_stack(), Relaxed); }
+    }
+
+    fn current_alt_stack() -> libc::stack_t {
+        #![allow(unsafe_code)]
+
+        let mut cur = MaybeUninit::<libc::stack_t>::zeroed();
+        // SAFETY: The arguments are proper.
+        let r = unsafe { libc::sigaltstack(ptr::null(), cur.as_mut_ptr()) };
+        assert_eq!(r, 0, "will succeed");
+        // SAFETY: It was zeroed, and `sigaltstack()` initialized it.
+        unsafe { cur.assume_init() }
+    }
+
+    fn is_on_alt_stack() -> bool { current_alt_stack().ss_flags & libc::SS_ONSTACK != 0 }
+
+
+    #[test]
+    fn alt_stack() {
+        const SIZE: usize = 1 << 16;
+
+        let _serial = serial();
+
+        install_handler_on_altstack::<SIGURG, OnStack>(true, false);
+
+        // (The Rust standard library might've already registered one for this thread, to detect
+        // stack overflow, and so using the normal stack can't be tested here.)
+        let previous = current_alt_stack();
+        let alt_stack = install_alt_stack(SIZE).unwrap();
+        assert_eq!(current_alt_stack().ss_size, SIZE);
+        raise(SIGURG);
+        assert_eq!(OnStack::take_count(), 1);
+        assert!(WAS_ON_STACK.load(Relaxed));
+
+        drop(alt_stack);
+        let restored = current_alt_stack();
+        assert_eq!((restored.ss_sp, restored.ss_size), (previous.ss_sp, previous.ss_size));
+
+        assert_eq!(install_alt_stack(1).unwrap_err().0, libc::ENOMEM);
+
+        uninstall_handler::<SIGURG>();
+    }
+}
+
+
+mod catch_all {
+    use crate::util::{raise, serial};
+    use libc::{SIGINT, SIGKILL, SIGSEGV, SIGTERM, SIGURG, SIGUSR1};
+    use signals_receipts::util::install_catch_all;
+
+
+    #[test]
+    fn catch_all() {
+        let _serial = serial();
+
+        let handle = install_catch_all().unwrap();
+        assert!(install_catch_all().is_none());
+
+        let signals = handle.signals();
+        for caught in [SIGINT, SIGTERM, SIGURG, SIGUSR1] {
+            assert!(signals.contains(caught));
+        }
+        for not_caught in [SIGKILL, SIGSEGV] {
+            assert!(!signals.contains(not_caught));
+        }
+
+        // These would terminate the process by default.
+        raise(SIGTERM);
+        raise(SIGUSR1);
+        raise(SIGUSR1);
+
+        let count_of = |signum| handle.counts().find(|&(s, _)| s == signum).unwrap().1;
+        assert_eq!(count_of(SIGTERM), 1);
+        assert_eq!(count_of(SIGUSR1), 2);
+        assert_eq!(count_of(SIGINT), 0);
+        assert_eq!(handle.counts().count(), signals.iter().count());
+
+        drop(handle);
+        let handle = install_catch_all().unwrap();
+        assert_eq!(handle.counts().map(|(_, count)| count).sum::<u64>(), 0);
+    }
+}
+
+
+mod crash_log {
+    use crate::util::{raise, serial};
+    use libc::SIGURG;
+    use signals_receipts::{install_handler, uninstall_handler,
+                           util::{ClockSource, CrashLog},
+                           SemaphoreRef, SignalReceipt};
+    use std::{ffi::CString,
+              fs,
+              sync::{atomic::AtomicU32, OnceLock}};
+
+
+    static LOG: OnceLock<CrashLog> = OnceLock::new();
+
+    struct Logged;
+
+    impl SignalReceipt<SIGURG> for Logged {
+        type AtomicUInt = AtomicU32;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+
+        fn on_delivery() {
+            if let Some(log) = LOG.get() {
+                log.record(SIGURG);
+            }
+        }
+    }
+
+
+    #[test]
+    fn crash_log() {
+        const CAPACITY: usize = 2;
+
+        let _serial = serial();
+
+        let path =
+            std::env::temp_dir().join(format!("signals_receipts-{}.log", std::process::id()));
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        CrashLog::new(&c_path, 0).unwrap_err();
+        let log = CrashLog::new(&c_path, CAPACITY).unwrap().with_clock(ClockSource::Boottime);
+        LOG.set(log).ok().unwrap();
+
+        install_handler::<SIGURG, Logged>(true, false);
+        for _ in 0 .. 3 {
+            raise(SIGURG);
+        }
+        uninstall_handler::<SIGURG>();
+        assert_eq!(Logged::take_count(), 3);
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let words = bytes
+            .chunks_exact(8)
+            .map(|w| u64::from_ne_bytes(w.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(words.len(), 1 + 2 * CAPACITY);
+        assert_eq!(words[0], 3);
+        let signum = u64::try_from(SIGURG).unwrap();
+        // The third record overwrote the first, and so it's the newest.
+        assert_eq!([words[1], words[3]], [signum, signum]);
+        assert!(words[2] >= words[4]);
+        assert!(words[2] <= ClockSource::Boottime.now_nanos());
+    }
+}
+
+
+#[cfg(feature = "premade")]
+mod delegates {
+    use crate::util::{raise, serial};
+    use core::{ops::ControlFlow, sync::atomic::{AtomicU32, Ordering::Relaxed}};
+    use libc::{SIGURG, SIGWINCH};
+    use signals_receipts::{consume_count_then_delegate,
+                           util::{enqueue_on_receipt, notify_watchdog},
+                           Premade as _};
+
+    // Each test has its own signal, so that they don't interfere when run concurrently.  Those
+    // have default dispositions of ignoring.
+    signals_receipts::premade! {
+        mod enqueue {
+            SIGURG => |_| ();
+        }
+    }
+
+    signals_receipts::premade! {
+        mod watchdog {
+            SIGWINCH => |_| ();
+        }
+    }
+
+
+    #[test]
+    fn enqueues_tasks() {
+        use enqueue::SignalsReceipts;
+
+        let _serial = serial();
+
+        let mut queue = Vec::new();
+        let mut consume = || {
+            let delegate =
+                enqueue_on_receipt(|task| queue.push(task), |sig_num, count| (sig_num, count));
+            consume_count_then_delegate::<SIGURG, SignalsReceipts, _, (), ()>((), delegate)
+        };
+
+        SignalsReceipts::install_all_handlers();
+        raise(SIGURG);
+        raise(SIGURG);
+        assert_eq!(consume(), ControlFlow::Continue(()));
+        // Not without new deliveries.
+        assert_eq!(consume(), ControlFlow::Continue(()));
+        raise(SIGURG);
+        assert_eq!(consume(), ControlFlow::Continue(()));
+        SignalsReceipts::uninstall_all_handlers();
+
+        // One task per receipt, with the coalesced counts.
+        assert_eq!(queue, [(SIGURG, 2), (SIGURG, 1)]);
+    }
+
+
+    #[test]
+    fn notifies_watchdog() {
+        use watchdog::SignalsReceipts;
+
+        let _serial = serial();
+
+        let notified = AtomicU32::new(0);
+        let consume = || {
+            let delegate = notify_watchdog(|| {
+                notified.fetch_add(1, Relaxed);
+            });
+            consume_count_then_delegate::<SIGWINCH, SignalsReceipts, _, (), ()>((), delegate)
+        };
+
+        SignalsReceipts::install_all_handlers();
+        assert_eq!(consume(), ControlFlow::Continue(()));
+        assert_eq!(notified.load(Relaxed), 0);
+        raise(SIGWINCH);
+        assert_eq!(consume(), ControlFlow::Continue(()));
+        assert_eq!(notified.load(Relaxed), 1);
+        // Pings received before the consuming are coalesced into one notification.
+        raise(SIGWINCH);
+        raise(SIGWINCH);
+        assert_eq!(consume(), ControlFlow::Continue(()));
+        assert_eq!(notified.load(Relaxed), 2);
+        SignalsReceipts::uninstall_all_handlers();
+    }
+}
+
+
+mod disposition {
+    use crate::util::serial;
+    use core::sync::atomic::AtomicU64;
+    use libc::SIGWINCH;
+    use signals_receipts::{install_handler, uninstall_handler,
+                           util::{current_disposition, Disposition},
+                           SemaphoreRef, SignalReceipt};
+
+
+    struct Handled;
+
+    impl SignalReceipt<SIGWINCH> for Handled {
+        type AtomicUInt = AtomicU64;
+
+        fn counter() -> &'static Self::AtomicUInt {
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            &COUNTER
+        }
+
+        fn semaphore() -> Option<SemaphoreRef<'static>> { None }
+    }
+
+    fn ignore(signum: libc::c_int) {
+        #![allow(unsafe_code)]
+        // SAFETY: The arguments are proper.
+        let r = unsafe { libc::signal(signum, libc::SIG_IGN) };
+        assert_ne!(r, libc::SIG_ERR, "will succeed");
+    }
+
+
+    #[test]
+    fn disposition() {
+        let _serial = serial();
+
+        assert_eq!(current_disposition(SIGWINCH), Ok(Disposition::Default));
+
+        install_handler::<SIGWINCH, Handled>(true, true);
+        assert_eq!(current_disposition(SIGWINCH), Ok(Disposition::Custom));
+
+        ignore(SIGWINCH);
+        assert_eq!(current_disposition(SIGWINCH), Ok(Disposition::Ignore));
+
+        uninstall_handler::<SIGWINCH>();
+        assert_eq!(current_disposition(SIGWINCH), Ok(Disposition::Default));
+
+        assert_eq!(current_disposition(-1), Err(()));
+    }
+}
+
+
+mod thread_mask {
+    use core::{mem::MaybeUninit, ptr};
+    use libc::{SIGUSR1, SIGUSR2, SIGWINCH};
+    use signals_receipts::{util::{mask_all_signals_of_current_thread,
+                                  mask_all_signals_of_current_thread_saving,
+                                  set_thread_signal_mask, MaskOp, SigSet},
+                           SignalNumber};
+    use std::thread;
+
+
+    fn is_blocked(signum: SignalNumber) -> bool {
+        #![allow(unsafe_code)]
+        let mut cur = MaybeUninit::<libc::sigset_t>::zeroed();
+        // SAFETY: The arguments are proper, and only the current mask is gotten.
+        let r = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, ptr::null(), cur.as_mut_ptr()) };
+        assert_eq!(r, 0, "will succeed");
+        // SAFETY: It was initialized by the above.
+        let is_member = unsafe { libc::sigismember(cur.as_ptr(), signum) };
+        is_member == 1
+    }
+
+
+    #[test]
+    fn thread_mask() {
+        // In another thread, to not affect the mask of the test harness's thread.
+        thread::spawn(|| {
+            mask_all_signals_of_current_thread();
+            assert!(is_blocked(SIGUSR1) && is_blocked(SIGUSR2));
+
+            set_thread_signal_mask(MaskOp::Unblock, &[SIGUSR1]).unwrap();
+            assert!(!is_blocked(SIGUSR1));
+            assert!(is_blocked(SIGUSR2));
+
+            set_thread_signal_mask(MaskOp::SetMask, &[SIGWINCH]).unwrap();
+            assert!(is_blocked(SIGWINCH));
+            assert!(!is_blocked(SIGUSR2));
+
+            set_thread_signal_mask(MaskOp::Block, &[SIGUSR2]).unwrap();
+            assert!(is_blocked(SIGUSR2) && is_blocked(SIGWINCH));
+
+            // Invalid, and so not changed.
+            assert!(set_thread_signal_mask(MaskOp::Unblock, &[SIGUSR2, -1]).is_err());
+            assert!(is_blocked(SIGUSR2));
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn saving() {
+        thread::spawn(|| {
+            set_thread_signal_mask(MaskOp::SetMask, &[SIGWINCH]).unwrap();
+            {
+                let guard = mask_all_signals_of_current_thread_saving();
+                assert_eq!(guard.saved(), &SigSet::from_signals(&[SIGWINCH]).unwrap());
+                assert!(is_blocked(SIGUSR1));
+            }
+            // Restored.
+            assert!(!is_blocked(SIGUSR1));
+            assert!(is_blocked(SIGWINCH));
+        })
+        .join()
+        .unwrap();
+    }
+}